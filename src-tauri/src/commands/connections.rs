@@ -1,14 +1,24 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
 use tauri::{AppHandle, Emitter, Manager, State};
 use tracing::{error, info};
 
 use crate::error::AppError;
-use crate::mcp::client::{McpClient, SharedConnections};
+use crate::mcp::client::{CallToolResult, McpClient, SharedConnections};
+use crate::mcp::container;
+use crate::mcp::env;
 use crate::mcp::oauth;
 use crate::mcp::proxy::ProxyState;
+use crate::mcp::sandbox::{self, SandboxTools};
+use crate::mcp::types::McpToolDef;
+use crate::persistence::{save_servers, save_tool_catalog};
+use crate::secrets::SecretStore;
+use crate::server_logs::{self, LogSource};
 use crate::state::{
-    ConnectionState, McpTool, ServerStatus, ServerTransport, SharedOAuthStore, SharedState,
+    AuthMode, ConnectionState, ContainerConfig, HttpMode, McpTool, ReplayConfig, SandboxConfig,
+    ServerConfig, ServerStatus, ServerTransport, SharedOAuthStore, SharedState,
 };
 
 #[tauri::command]
@@ -20,7 +30,7 @@ pub async fn connect_server(
     id: String,
 ) -> Result<(), AppError> {
     // Read config while holding the lock briefly
-    let server_config = {
+    let mut server_config = {
         let mut s = state.lock().unwrap();
         let server = s
             .servers
@@ -36,14 +46,7 @@ pub async fn connect_server(
 
         server.status = Some(ServerStatus::Connecting);
 
-        ServerConnectConfig {
-            transport: server.transport.clone(),
-            command: server.command.clone(),
-            args: server.args.clone().unwrap_or_default(),
-            env: server.env.clone().unwrap_or_default(),
-            url: server.url.clone(),
-            headers: server.headers.clone().unwrap_or_default(),
-        }
+        ServerConnectConfig::from(&*server)
     };
 
     let _ = app.emit(
@@ -51,6 +54,12 @@ pub async fn connect_server(
         serde_json::json!({ "serverId": id, "status": "connecting" }),
     );
 
+    if let Err(e) = resolve_connect_config(&app, &id, &mut server_config) {
+        emit_server_log(&app, &id, "error", &e.to_string());
+        mark_server_error(&app, &state, &id, &e.to_string());
+        return Err(e);
+    }
+
     // For HTTP transport, check if we have existing OAuth tokens
     let access_token = if matches!(server_config.transport, ServerTransport::Http) {
         resolve_access_token(&oauth_store, &id, &app).await
@@ -64,15 +73,39 @@ pub async fn connect_server(
             let command = server_config
                 .command
                 .ok_or_else(|| AppError::ConnectionFailed("No command specified".into()))?;
-            McpClient::connect_stdio(&app, &id, &command, &server_config.args, &server_config.env)
-                .await
+            log_sandbox_profile(
+                &app,
+                &id,
+                &command,
+                &server_config.args,
+                &server_config.sandbox,
+            );
+            McpClient::connect_stdio(
+                &app,
+                &id,
+                &command,
+                &server_config.args,
+                &server_config.env,
+                server_config.cwd.as_deref().map(Path::new),
+                server_config.sandbox.as_ref(),
+            )
+            .await
         }
         ServerTransport::Http => {
             let url = server_config
                 .url
                 .ok_or_else(|| AppError::ConnectionFailed("No URL specified".into()))?;
             emit_server_log(&app, &id, "info", &format!("Connecting to {url}"));
-            match McpClient::connect_http(&url, server_config.headers, access_token).await {
+            match McpClient::connect_http(
+                &app,
+                &id,
+                &url,
+                server_config.headers,
+                access_token,
+                server_config.http_mode,
+            )
+            .await
+            {
                 Ok(client) => {
                     emit_server_log(
                         &app,
@@ -88,6 +121,8 @@ pub async fn connect_server(
                 }
             }
         }
+        ServerTransport::Container => connect_container(&app, &id, server_config).await,
+        ServerTransport::Replay => connect_replay(&app, &id, server_config).await,
     };
 
     match client_result {
@@ -95,7 +130,10 @@ pub async fn connect_server(
             finalize_connection(&app, &state, &connections, &id, client).await?;
             Ok(())
         }
-        Err(AppError::AuthRequired(_)) => {
+        // Servers with configured credentials have nothing to sign in to; report the error as is
+        Err(AppError::AuthRequired(_) | AppError::AuthExpired(_))
+            if server_auth_mode(&state, &id) == AuthMode::Interactive =>
+        {
             info!("Server {id} requires OAuth authentication");
             mark_server_error(
                 &app,
@@ -110,6 +148,9 @@ pub async fn connect_server(
         }
         Err(e) => {
             error!("Failed to connect to server {id}: {e}");
+            if is_transport_mismatch(&e) {
+                forget_http_mode(&app, &state, &id);
+            }
             let error_message = e.to_string();
             mark_server_error(&app, &state, &id, &error_message);
             let _ = app.emit(
@@ -133,11 +174,10 @@ pub async fn disconnect_server(
     id: String,
 ) -> Result<(), AppError> {
     // Remove and shut down the live MCP client
-    {
-        let mut conns = connections.lock().await;
-        if let Some(client) = conns.remove(&id) {
-            client.shutdown();
-        }
+    let client = connections.lock().await.remove(&id);
+    if let Some(client) = client {
+        client.close_session().await;
+        client.shutdown();
     }
 
     // Update AppState
@@ -187,17 +227,7 @@ pub async fn reconnect_on_startup(app: AppHandle) {
             if server.status == Some(ServerStatus::Connected)
                 || server.status == Some(ServerStatus::Connecting)
             {
-                to_reconnect.push((
-                    server.id.clone(),
-                    ServerConnectConfig {
-                        transport: server.transport.clone(),
-                        command: server.command.clone(),
-                        args: server.args.clone().unwrap_or_default(),
-                        env: server.env.clone().unwrap_or_default(),
-                        url: server.url.clone(),
-                        headers: server.headers.clone().unwrap_or_default(),
-                    },
-                ));
+                to_reconnect.push((server.id.clone(), ServerConnectConfig::from(&*server)));
             }
             // Reset all to disconnected — real status comes from actual connections
             server.status = Some(ServerStatus::Disconnected);
@@ -227,7 +257,7 @@ pub async fn reconnect_on_startup(app: AppHandle) {
     let connections = app.state::<SharedConnections>();
     let oauth_store = app.state::<SharedOAuthStore>();
 
-    for (id, config) in servers_to_reconnect {
+    for (id, mut config) in servers_to_reconnect {
         // Skip if already connected/connecting (frontend's autoConnectServers may have raced us)
         {
             let mut s = state.lock().unwrap();
@@ -246,6 +276,13 @@ pub async fn reconnect_on_startup(app: AppHandle) {
             serde_json::json!({ "serverId": id, "status": "connecting" }),
         );
 
+        if let Err(e) = resolve_connect_config(&app, &id, &mut config) {
+            error!("Failed to reconnect server {id}: {e}");
+            emit_server_log(&app, &id, "error", &e.to_string());
+            mark_server_error(&app, &state, &id, &e.to_string());
+            continue;
+        }
+
         let access_token = if matches!(config.transport, ServerTransport::Http) {
             resolve_access_token(&oauth_store, &id, &app).await
         } else {
@@ -258,7 +295,17 @@ pub async fn reconnect_on_startup(app: AppHandle) {
                     error!("Server {id} has no command, skipping reconnect");
                     continue;
                 };
-                McpClient::connect_stdio(&app, &id, &command, &config.args, &config.env).await
+                log_sandbox_profile(&app, &id, &command, &config.args, &config.sandbox);
+                McpClient::connect_stdio(
+                    &app,
+                    &id,
+                    &command,
+                    &config.args,
+                    &config.env,
+                    config.cwd.as_deref().map(Path::new),
+                    config.sandbox.as_ref(),
+                )
+                .await
             }
             ServerTransport::Http => {
                 let Some(url) = config.url else {
@@ -266,7 +313,16 @@ pub async fn reconnect_on_startup(app: AppHandle) {
                     continue;
                 };
                 emit_server_log(&app, &id, "info", &format!("Connecting to {url}"));
-                match McpClient::connect_http(&url, config.headers, access_token).await {
+                match McpClient::connect_http(
+                    &app,
+                    &id,
+                    &url,
+                    config.headers,
+                    access_token,
+                    config.http_mode,
+                )
+                .await
+                {
                     Ok(client) => {
                         emit_server_log(
                             &app,
@@ -282,6 +338,8 @@ pub async fn reconnect_on_startup(app: AppHandle) {
                     }
                 }
             }
+            ServerTransport::Container => connect_container(&app, &id, config).await,
+            ServerTransport::Replay => connect_replay(&app, &id, config).await,
         };
 
         match client_result {
@@ -292,12 +350,42 @@ pub async fn reconnect_on_startup(app: AppHandle) {
             }
             Err(e) => {
                 error!("Failed to reconnect server {id}: {e}");
+                if is_transport_mismatch(&e) {
+                    forget_http_mode(&app, &state, &id);
+                }
                 mark_server_error(&app, &state, &id, &e.to_string());
             }
         }
     }
 }
 
+/// Call a tool. Expired HTTP sessions are renewed by the transport itself; an
+/// access token that couldn't be refreshed prompts the user to sign in again.
+pub(crate) async fn call_tool_prompting_sign_in(
+    app: &AppHandle,
+    id: &str,
+    client: Arc<McpClient>,
+    tool_name: &str,
+    arguments: serde_json::Value,
+) -> Result<CallToolResult, AppError> {
+    match client.call_tool(tool_name, arguments).await {
+        Err(AppError::AuthExpired(url))
+            if server_auth_mode(&app.state::<SharedState>(), id) == AuthMode::Interactive =>
+        {
+            // The transport already tried refreshing; the user has to sign in again
+            emit_server_log(
+                app,
+                id,
+                "error",
+                "Access token expired and could not be refreshed. Click Authorize to sign in.",
+            );
+            let _ = app.emit("oauth-required", serde_json::json!({ "serverId": id }));
+            Err(AppError::AuthExpired(url))
+        }
+        result => result,
+    }
+}
+
 // --- Private helpers ---
 
 /// Temporary struct to hold server config data extracted from the lock.
@@ -308,33 +396,287 @@ struct ServerConnectConfig {
     env: HashMap<String, String>,
     url: Option<String>,
     headers: HashMap<String, String>,
+    sandbox: Option<SandboxConfig>,
+    container: Option<ContainerConfig>,
+    http_mode: Option<HttpMode>,
+    cwd: Option<String>,
+    env_file: Vec<String>,
+    replay: Option<ReplayConfig>,
+}
+
+impl From<&ServerConfig> for ServerConnectConfig {
+    fn from(server: &ServerConfig) -> Self {
+        Self {
+            transport: server.transport.clone(),
+            command: server.command.clone(),
+            args: server.args.clone().unwrap_or_default(),
+            env: server.env.clone().unwrap_or_default(),
+            url: server.url.clone(),
+            headers: server.headers.clone().unwrap_or_default(),
+            sandbox: server.sandbox.clone(),
+            container: server.container.clone(),
+            http_mode: server.http_mode,
+            cwd: server.cwd.clone(),
+            env_file: server.env_file.clone().unwrap_or_default(),
+            replay: server.replay.clone(),
+        }
+    }
+}
+
+/// An HTTP server's URL and headers, resolved like on connect, for code that
+/// connects outside the usual flow (e.g. right after an OAuth sign-in).
+pub(crate) fn resolve_http_endpoint(
+    app: &AppHandle,
+    id: &str,
+) -> Result<(String, HashMap<String, String>), AppError> {
+    let mut config = {
+        let state = app.state::<SharedState>();
+        let s = state.lock().unwrap();
+        let server = s
+            .servers
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AppError::ServerNotFound(id.to_string()))?;
+        ServerConnectConfig::from(server)
+    };
+    resolve_connect_config(app, id, &mut config)?;
+    let url = config
+        .url
+        .ok_or_else(|| AppError::ConnectionFailed("No URL specified".into()))?;
+    Ok((url, config.headers))
+}
+
+/// Resolve the working directory, load env files and expand `${VAR}` and
+/// `${secret:NAME}` references. This happens only at connect time, so resolved
+/// values never reach app state or config.json.
+fn resolve_connect_config(
+    app: &AppHandle,
+    id: &str,
+    config: &mut ServerConnectConfig,
+) -> Result<(), AppError> {
+    let host_env = crate::shell_env::get().vars().clone();
+
+    let cwd = config.cwd.as_deref().map(|dir| {
+        let mut interp = env::Interpolator::new(&host_env);
+        env::resolve_path(&interp.expand(dir), None)
+    });
+    let file_vars = env::load_env_files(&config.env_file, cwd.as_deref(), &host_env)?;
+    config.cwd = cwd.map(|dir| dir.to_string_lossy().into_owned());
+
+    let mut scope = host_env;
+    scope.extend(file_vars.clone());
+    let mut interp = env::Interpolator::new(&scope);
+    for arg in &mut config.args {
+        *arg = interp.expand(arg);
+    }
+    interp.expand_values(&mut config.env);
+    interp.expand_values(&mut config.headers);
+    if let Some(url) = config.url.as_mut() {
+        *url = interp.expand(url);
+    }
+    if let Some(replay) = config.replay.as_mut() {
+        replay.fixture = interp.expand(&replay.fixture);
+    }
+    if !interp.unresolved().is_empty() {
+        emit_server_log(
+            app,
+            id,
+            "warn",
+            &format!(
+                "Unresolved variables (expanded to empty): {}",
+                interp.unresolved().join(", ")
+            ),
+        );
+    }
+
+    // Explicit env entries win over env files
+    let mut merged = file_vars;
+    merged.extend(std::mem::take(&mut config.env));
+    config.env = merged;
+
+    let secrets = app.state::<SecretStore>();
+    for arg in &mut config.args {
+        *arg = secrets.expand(arg)?;
+    }
+    secrets.expand_values(&mut config.env)?;
+    secrets.expand_values(&mut config.headers)?;
+    if let Some(url) = config.url.as_mut() {
+        *url = secrets.expand(url)?;
+    }
+    Ok(())
+}
+
+/// Open a replay server's fixture, mirroring progress into the server log.
+async fn connect_replay(
+    app: &AppHandle,
+    id: &str,
+    config: ServerConnectConfig,
+) -> Result<McpClient, AppError> {
+    let replay = config
+        .replay
+        .ok_or_else(|| AppError::ConnectionFailed("No fixture specified".into()))?;
+    emit_server_log(
+        app,
+        id,
+        "info",
+        &format!("Replaying fixture {}", replay.fixture),
+    );
+    match McpClient::connect_replay(app, id, &replay).await {
+        Ok(client) => {
+            emit_server_log(
+                app,
+                id,
+                "info",
+                &format!("Connected — {} recorded tools", client.tools.len()),
+            );
+            Ok(client)
+        }
+        Err(e) => {
+            emit_server_log(app, id, "error", &format!("Connection failed: {e}"));
+            Err(e)
+        }
+    }
 }
 
-/// Try to get a valid access token from stored OAuth state, refreshing if needed.
+/// Start a container server, mirroring connect progress into the server log.
+async fn connect_container(
+    app: &AppHandle,
+    id: &str,
+    config: ServerConnectConfig,
+) -> Result<McpClient, AppError> {
+    let container_config = config
+        .container
+        .ok_or_else(|| AppError::ConnectionFailed("No container image specified".into()))?;
+    emit_server_log(
+        app,
+        id,
+        "info",
+        &format!(
+            "Starting container {}",
+            container::image_ref(&container_config)
+        ),
+    );
+    match McpClient::connect_container(
+        app,
+        id,
+        &container_config,
+        &config.args,
+        &config.env,
+        config.headers,
+    )
+    .await
+    {
+        Ok(client) => {
+            emit_server_log(
+                app,
+                id,
+                "info",
+                &format!("Connected — {} tools available", client.tools.len()),
+            );
+            Ok(client)
+        }
+        Err(e) => {
+            emit_server_log(app, id, "error", &format!("Connection failed: {e}"));
+            Err(e)
+        }
+    }
+}
+
+/// Log the sandbox restrictions a stdio server is about to run under.
+fn log_sandbox_profile(
+    app: &AppHandle,
+    id: &str,
+    command: &str,
+    args: &[String],
+    sandbox: &Option<SandboxConfig>,
+) {
+    let Some(config) = sandbox.as_ref().filter(|s| s.enabled) else {
+        return;
+    };
+    let profile = sandbox::describe(config, &SandboxTools::detect(), command, args);
+    emit_server_log(
+        app,
+        id,
+        "info",
+        &format!("Sandbox: {}", profile.rules.join("; ")),
+    );
+}
+
+/// Get the access token for an HTTP server according to its auth mode. Stored
+/// tokens are used while valid; otherwise interactive OAuth tokens are refreshed,
+/// client credentials tokens are fetched anew, and static tokens are read from
+/// the secret store.
 async fn resolve_access_token(
     oauth_store: &SharedOAuthStore,
     id: &str,
     app: &AppHandle,
 ) -> Option<String> {
+    let mode = server_auth_mode(&app.state::<SharedState>(), id);
     let store = oauth_store.lock().await;
-    let oauth_state = store.get(id)?;
-    let tokens = oauth_state.tokens.as_ref()?;
+    let tokens = store
+        .get(id)
+        .filter(|s| s.mode == mode)
+        .and_then(|s| s.tokens.as_ref());
 
-    if !oauth::is_token_expired(tokens) {
+    if let Some(tokens) = tokens.filter(|t| !oauth::is_token_expired(t)) {
         return Some(tokens.access_token.clone());
     }
 
-    if tokens.refresh_token.is_some() {
-        drop(store);
-        match oauth::try_refresh_token(oauth_store, id, app).await {
-            Ok(new_token) => Some(new_token),
-            Err(e) => {
-                tracing::warn!("Token refresh failed: {e}, will try without token");
-                None
-            }
+    let renewable = match mode {
+        AuthMode::Interactive => tokens.is_some_and(|t| t.refresh_token.is_some()),
+        AuthMode::ClientCredentials | AuthMode::StaticToken => true,
+    };
+    drop(store);
+    if !renewable {
+        return None;
+    }
+
+    match oauth::try_refresh_token(oauth_store, id, app).await {
+        Ok(new_token) => Some(new_token),
+        Err(e) if mode == AuthMode::Interactive => {
+            tracing::warn!("Token refresh failed: {e}, will try without token");
+            None
+        }
+        Err(e) => {
+            emit_server_log(
+                app,
+                id,
+                "error",
+                &format!("Failed to get access token: {e}"),
+            );
+            None
         }
-    } else {
-        None
+    }
+}
+
+/// Auth mode configured for a server; interactive unless set otherwise.
+fn server_auth_mode(state: &SharedState, id: &str) -> AuthMode {
+    let s = state.lock().unwrap();
+    s.servers
+        .iter()
+        .find(|s| s.id == id)
+        .and_then(|s| s.oauth.as_ref())
+        .map(|o| o.mode)
+        .unwrap_or_default()
+}
+
+/// Whether a failed connect could mean the server no longer speaks its
+/// remembered HTTP transport. Auth and network failures say nothing about it.
+fn is_transport_mismatch(e: &AppError) -> bool {
+    matches!(e, AppError::Protocol(_) | AppError::Transport(_))
+}
+
+/// Drop a remembered HTTP transport mode after a failed connect, so the next
+/// attempt probes again (the server may have switched transports).
+fn forget_http_mode(app: &AppHandle, state: &SharedState, id: &str) {
+    let mut s = state.lock().unwrap();
+    if let Some(server) = s
+        .servers
+        .iter_mut()
+        .find(|s| s.id == id && s.http_mode.is_some())
+    {
+        server.http_mode = None;
+        save_servers(app, &s.servers);
     }
 }
 
@@ -361,43 +703,27 @@ async fn finalize_connection(
     id: &str,
     client: McpClient,
 ) -> Result<(), AppError> {
-    let server_name;
-
-    // Convert discovered tools to McpTool for storage in AppState
-    let tools: Vec<McpTool> = {
-        let s = state.lock().unwrap();
-        let srv = s.servers.iter().find(|s| s.id == id);
-        server_name = srv.map(|s| s.name.clone()).unwrap_or_default();
-
-        client
-            .tools
-            .iter()
-            .map(|t| McpTool {
-                name: t.name.clone(),
-                title: t.title.clone(),
-                description: t.description.clone(),
-                input_schema: t.input_schema.clone(),
-                server_id: id.to_string(),
-                server_name: server_name.clone(),
-            })
-            .collect()
-    };
-
+    let tools = store_tools(app, state, id, &client.tools);
     info!("Connected to server {id} with {} tools", tools.len());
 
+    let http_mode = client.http_mode().await;
+
     // Store connection state in AppState
     {
         let mut s = state.lock().unwrap();
+        let mut mode_changed = false;
         if let Some(server) = s.servers.iter_mut().find(|s| s.id == id) {
             server.status = Some(ServerStatus::Connected);
             server.last_connected = Some(chrono_now());
+            // Remember the detected HTTP transport so later connects skip the probe
+            if http_mode.is_some() && server.http_mode != http_mode {
+                server.http_mode = http_mode;
+                mode_changed = true;
+            }
+        }
+        if mode_changed {
+            save_servers(app, &s.servers);
         }
-        s.connections.insert(
-            id.to_string(),
-            ConnectionState {
-                tools: tools.clone(),
-            },
-        );
     }
 
     // Store the live client in the connections map
@@ -430,18 +756,74 @@ async fn finalize_connection(
     Ok(())
 }
 
+/// Convert a server's discovered tools to `McpTool`s and store them as its
+/// connection state and catalog entry.
+fn store_tools(
+    app: &AppHandle,
+    state: &SharedState,
+    id: &str,
+    defs: &[McpToolDef],
+) -> Vec<McpTool> {
+    let mut s = state.lock().unwrap();
+    let server_name = s
+        .servers
+        .iter()
+        .find(|s| s.id == id)
+        .map(|s| s.name.clone())
+        .unwrap_or_default();
+    let tools: Vec<McpTool> = defs
+        .iter()
+        .map(|t| McpTool {
+            name: t.name.clone(),
+            title: t.title.clone(),
+            description: t.description.clone(),
+            input_schema: t.input_schema.clone(),
+            server_id: id.to_string(),
+            server_name: server_name.clone(),
+        })
+        .collect();
+
+    s.connections.insert(
+        id.to_string(),
+        ConnectionState {
+            tools: tools.clone(),
+        },
+    );
+    s.tool_catalog.insert(id.to_string(), tools.clone());
+    save_tool_catalog(app, &s.tool_catalog);
+    tools
+}
+
+/// Re-list a connected server's tools, e.g. after its HTTP session was renewed,
+/// and publish them like a fresh connect does.
+pub(crate) async fn refresh_tools(app: AppHandle, id: String) {
+    let connections = app.state::<SharedConnections>();
+    let Some(client) = connections.lock().await.get(&id).cloned() else {
+        return;
+    };
+    let defs = match client.list_tools().await {
+        Ok(defs) => defs,
+        Err(e) => {
+            tracing::warn!("Failed to refresh tools of server {id}: {e}");
+            return;
+        }
+    };
+
+    let state = app.state::<SharedState>();
+    let tools = store_tools(&app, &state, &id, &defs);
+    info!("Refreshed server {id}: {} tools", tools.len());
+    let _ = app.emit(
+        "tools-updated",
+        serde_json::json!({ "serverId": id, "tools": tools }),
+    );
+    crate::mcp::proxy::notify_if_tools_changed(&app, &id, &tools).await;
+}
+
 /// Emit a `server-log` event and buffer it in AppState for the frontend to drain later.
 /// HTTP servers only get logs during connection, so if the frontend isn't mounted yet
 /// the events are lost. The buffer ensures they can be retrieved after mount.
 fn emit_server_log(app: &AppHandle, server_id: &str, level: &str, message: &str) {
-    let _ = app.emit(
-        "server-log",
-        serde_json::json!({
-            "serverId": server_id,
-            "level": level,
-            "message": message,
-        }),
-    );
+    server_logs::emit(app, server_id, level, LogSource::Lifecycle, message);
 
     // Also buffer for frontend that may not be listening yet
     let state = app.state::<SharedState>();
@@ -1,12 +1,14 @@
 use serde::Serialize;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 
 use crate::commands::integrations::update_all_integration_configs;
 use crate::commands::skills::{install_managed_skill, uninstall_managed_skill};
 use crate::error::AppError;
-use crate::mcp::proxy::ProxyState;
-use crate::persistence::save_tool_discovery;
-use crate::state::SharedState;
+use crate::mcp::discovery::DISCOVERY_ID;
+use crate::mcp::embeddings::{self, ToolEmbeddings};
+use crate::mcp::proxy::{NotifySender, ProxyState};
+use crate::persistence::{save_discovery_pinning, save_discovery_search_mode, save_tool_discovery};
+use crate::state::{AppState, SearchMode, SharedState, ToolPinning};
 
 pub(crate) const DISCOVERY_SKILL_ID: &str = "using-discovery";
 pub(crate) const DISCOVERY_SKILL_CONTENT: &str =
@@ -16,14 +18,30 @@ pub(crate) const DISCOVERY_SKILL_CONTENT: &str =
 #[serde(rename_all = "camelCase")]
 pub struct DiscoveryStatus {
     pub enabled: bool,
+    pub search_mode: SearchMode,
+    /// Tools with vectors from the current embedding model.
+    pub embedded_tools: usize,
+    pub pinning: ToolPinning,
+}
+
+fn discovery_status(app: &AppHandle, s: &AppState) -> DiscoveryStatus {
+    DiscoveryStatus {
+        enabled: s.tool_discovery_enabled,
+        search_mode: s.discovery_search_mode,
+        embedded_tools: app
+            .try_state::<ToolEmbeddings>()
+            .map_or(0, |e| e.embedded_tools(&s.embedding_config)),
+        pinning: s.discovery_pinning.clone(),
+    }
 }
 
 #[tauri::command]
-pub async fn get_discovery_mode(state: State<'_, SharedState>) -> Result<DiscoveryStatus, AppError> {
+pub async fn get_discovery_mode(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> Result<DiscoveryStatus, AppError> {
     let s = state.lock().unwrap();
-    Ok(DiscoveryStatus {
-        enabled: s.tool_discovery_enabled,
-    })
+    Ok(discovery_status(&app, &s))
 }
 
 #[tauri::command]
@@ -59,5 +77,50 @@ pub async fn set_discovery_mode(
         tracing::warn!("Failed to update integration configs after discovery toggle: {e}");
     }
 
-    Ok(DiscoveryStatus { enabled })
+    let s = state.lock().unwrap();
+    Ok(discovery_status(&app, &s))
+}
+
+#[tauri::command]
+pub async fn set_discovery_search_mode(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    mode: SearchMode,
+) -> Result<DiscoveryStatus, AppError> {
+    {
+        let mut s = state.lock().unwrap();
+        s.discovery_search_mode = mode;
+    }
+
+    save_discovery_search_mode(&app, mode);
+
+    // Embed tools that connected while ranking by keyword
+    if mode != SearchMode::Keyword {
+        embeddings::index_all(&app);
+    }
+
+    let s = state.lock().unwrap();
+    Ok(discovery_status(&app, &s))
+}
+
+#[tauri::command]
+pub async fn set_discovery_pinning(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    pinning: ToolPinning,
+) -> Result<DiscoveryStatus, AppError> {
+    {
+        let mut s = state.lock().unwrap();
+        s.discovery_pinning = pinning.clone();
+    }
+
+    save_discovery_pinning(&app, &pinning);
+
+    // Connected discovery clients re-fetch tools/list if their pinned set changed
+    if let Some(sender) = app.try_state::<NotifySender>() {
+        let _ = sender.0.send(DISCOVERY_ID.to_string());
+    }
+
+    let s = state.lock().unwrap();
+    Ok(discovery_status(&app, &s))
 }
@@ -2,14 +2,18 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use tauri::{AppHandle, Manager, State};
 use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::error::AppError;
 use crate::mcp::proxy::ProxyState;
-use crate::persistence::{save_enabled_integrations, save_servers};
-use crate::state::{ServerConfig, ServerStatus, ServerTransport, SharedState};
+use crate::persistence::{
+    migrate_server_credentials, save_enabled_integrations, save_servers, save_vscode_workspaces,
+};
+use crate::secrets::SecretStore;
+use crate::state::{HttpMode, ServerConfig, ServerStatus, ServerTransport, SharedState};
 
 /// How to parse a tool's config file.
 #[derive(Debug, Clone)]
@@ -22,6 +26,14 @@ enum ConfigFormat {
     Zed,
     /// TOML with [mcp_servers.name] — Codex
     CodexToml,
+    /// {"servers": {"name": {"type":"stdio","command":"...",...}}, "inputs": [...]} — VS Code
+    VsCode,
+    /// {"mcpServers": {"name": {"httpUrl"|"url":"...","excludeTools":[...]}}} — Gemini CLI
+    Gemini,
+    /// YAML with extensions.name: {type: stdio, cmd, args, envs} — Goose
+    GooseYaml,
+    /// YAML with mcpServers: [{name, command, args, env}] — Continue
+    ContinueYaml,
 }
 
 /// How to write/remove MCP entries for this tool.
@@ -38,11 +50,23 @@ struct ToolDef {
     id: String,
     name: String,
     config_path: PathBuf,
-    detection_paths: Vec<PathBuf>,
+    /// Places that show the tool is installed; any one is enough.
+    detection: Vec<Probe>,
     config_format: ConfigFormat,
     config_strategy: ConfigStrategy,
 }
 
+/// One place a tool's installation can show up.
+enum Probe {
+    /// A file or directory that exists once the tool is installed or has run.
+    Path(PathBuf),
+    /// An AppImage whose file name starts with this prefix, in one of the
+    /// folders AppImages are usually kept in.
+    AppImage(&'static str),
+    /// A `.desktop` launcher with this id in any XDG applications directory.
+    DesktopEntry(&'static str),
+}
+
 /// An existing MCP server found in a tool's config file.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -65,20 +89,65 @@ pub struct AiToolInfo {
     pub id: String,
     pub name: String,
     pub installed: bool,
+    /// Why the tool was or wasn't detected, e.g. "Found /usr/bin/code".
+    pub detection: String,
     pub enabled: bool,
     pub config_path: String,
     pub configured_port: u16,
     /// Existing MCP servers in this tool's config that could be imported.
     pub existing_servers: Vec<ExistingMcpServer>,
+    /// Secrets the servers just imported reference but that aren't set yet.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_secrets: Vec<MissingSecret>,
+}
+
+/// A secret an imported server needs, e.g. from a VS Code `${input:ID}`
+/// prompt. The server can't start until it's set.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingSecret {
+    pub name: String,
+    /// What the tool's config said to prompt for, if anything.
+    pub description: Option<String>,
 }
 
-fn get_tool_definitions(home: &Path) -> Vec<ToolDef> {
+/// Every AI tool Agent Hub knows how to configure. `workspaces` are the
+/// project folders of enabled VS Code workspace integrations.
+fn get_tool_definitions(home: &Path, workspaces: &[PathBuf]) -> Vec<ToolDef> {
+    let config_home = xdg_dir("XDG_CONFIG_HOME", home, ".config");
+
+    let mut cursor = vec![Probe::Path(home.join(".cursor"))];
+    cursor.extend(app_probes(
+        "Cursor.app",
+        vec![
+            Probe::Path(PathBuf::from("/opt/Cursor")),
+            Probe::Path(PathBuf::from("/usr/share/cursor")),
+            Probe::Path(PathBuf::from("/usr/bin/cursor")),
+            Probe::AppImage("Cursor"),
+            Probe::DesktopEntry("cursor"),
+        ],
+    ));
+
+    // Claude Desktop keeps its config next to Electron's app data: Application
+    // Support on macOS, the XDG config dir on Linux
+    let claude_dir = app_data_dir(home).join("Claude");
+    let claude_desktop = app_probes(
+        "Claude.app",
+        vec![
+            Probe::Path(claude_dir.clone()),
+            Probe::Path(PathBuf::from("/usr/bin/claude-desktop")),
+            Probe::Path(PathBuf::from("/usr/lib/claude-desktop")),
+            Probe::AppImage("Claude"),
+            Probe::DesktopEntry("claude-desktop"),
+        ],
+    );
+
     let mut tools = vec![
         ToolDef {
             id: "claude-code".into(),
             name: "Claude Code".into(),
             config_path: home.join(".claude.json"),
-            detection_paths: vec![home.join(".claude")],
+            detection: vec![Probe::Path(home.join(".claude"))],
             config_format: ConfigFormat::McpServers,
             config_strategy: ConfigStrategy::ClaudeCli,
         },
@@ -86,18 +155,15 @@ fn get_tool_definitions(home: &Path) -> Vec<ToolDef> {
             id: "cursor".into(),
             name: "Cursor".into(),
             config_path: home.join(".cursor").join("mcp.json"),
-            detection_paths: vec![
-                home.join(".cursor"),
-                PathBuf::from("/Applications/Cursor.app"),
-            ],
+            detection: cursor,
             config_format: ConfigFormat::McpServers,
             config_strategy: ConfigStrategy::ManagedFile,
         },
         ToolDef {
             id: "claude-desktop".into(),
             name: "Claude Desktop".into(),
-            config_path: home.join("Library/Application Support/Claude/claude_desktop_config.json"),
-            detection_paths: vec![PathBuf::from("/Applications/Claude.app")],
+            config_path: claude_dir.join("claude_desktop_config.json"),
+            detection: claude_desktop,
             config_format: ConfigFormat::McpServers,
             config_strategy: ConfigStrategy::ManagedFile,
         },
@@ -112,15 +178,25 @@ fn get_tool_definitions(home: &Path) -> Vec<ToolDef> {
         codeium_path
     };
 
+    let mut windsurf = vec![
+        Probe::Path(home.join(".codeium/windsurf")),
+        Probe::Path(home.join(".windsurf")),
+    ];
+    windsurf.extend(app_probes(
+        "Windsurf.app",
+        vec![
+            Probe::Path(PathBuf::from("/usr/share/windsurf")),
+            Probe::Path(PathBuf::from("/usr/bin/windsurf")),
+            Probe::AppImage("Windsurf"),
+            Probe::DesktopEntry("windsurf"),
+        ],
+    ));
+
     tools.push(ToolDef {
         id: "windsurf".into(),
         name: "Windsurf".into(),
         config_path,
-        detection_paths: vec![
-            home.join(".codeium/windsurf"),
-            home.join(".windsurf"),
-            PathBuf::from("/Applications/Windsurf.app"),
-        ],
+        detection: windsurf,
         config_format: ConfigFormat::McpServers,
         config_strategy: ConfigStrategy::ManagedFile,
     });
@@ -130,7 +206,7 @@ fn get_tool_definitions(home: &Path) -> Vec<ToolDef> {
         name: "MCP Config".into(),
         config_path: home.join(".mcp.json"),
         // Always "installed" — just check if the file exists
-        detection_paths: vec![home.join(".mcp.json")],
+        detection: vec![Probe::Path(home.join(".mcp.json"))],
         config_format: ConfigFormat::McpServers,
         config_strategy: ConfigStrategy::ManagedFile,
     });
@@ -138,8 +214,8 @@ fn get_tool_definitions(home: &Path) -> Vec<ToolDef> {
     tools.push(ToolDef {
         id: "opencode".into(),
         name: "OpenCode".into(),
-        config_path: home.join(".config/opencode/opencode.json"),
-        detection_paths: vec![home.join(".config/opencode")],
+        config_path: config_home.join("opencode/opencode.json"),
+        detection: vec![Probe::Path(config_home.join("opencode"))],
         config_format: ConfigFormat::OpenCode,
         config_strategy: ConfigStrategy::ManagedFile,
     });
@@ -148,28 +224,333 @@ fn get_tool_definitions(home: &Path) -> Vec<ToolDef> {
         id: "codex".into(),
         name: "Codex".into(),
         config_path: home.join(".codex/config.toml"),
-        detection_paths: vec![home.join(".codex")],
+        detection: vec![Probe::Path(home.join(".codex"))],
         config_format: ConfigFormat::CodexToml,
         config_strategy: ConfigStrategy::ManagedFile,
     });
 
+    // Zed reads ~/.config/zed on macOS whatever XDG_CONFIG_HOME says
+    let zed_dir = if cfg!(target_os = "macos") {
+        home.join(".config/zed")
+    } else {
+        config_home.join("zed")
+    };
+    let mut zed_linux = vec![
+        // Where zed.dev/install.sh puts it
+        Probe::Path(home.join(".local/zed.app")),
+        Probe::Path(PathBuf::from("/usr/bin/zeditor")),
+        Probe::DesktopEntry("dev.zed.Zed"),
+    ];
+    zed_linux.extend(flatpak_probes(home, "dev.zed.Zed"));
+    let mut zed = vec![Probe::Path(zed_dir.clone())];
+    zed.extend(app_probes("Zed.app", zed_linux));
+
     tools.push(ToolDef {
         id: "zed".into(),
         name: "Zed".into(),
-        config_path: home.join(".config/zed/settings.json"),
-        detection_paths: vec![
-            home.join(".config/zed"),
-            PathBuf::from("/Applications/Zed.app"),
-        ],
+        config_path: zed_dir.join("settings.json"),
+        detection: zed,
         config_format: ConfigFormat::Zed,
         config_strategy: ConfigStrategy::ManagedFile,
     });
 
+    tools.push(ToolDef {
+        id: "gemini-cli".into(),
+        name: "Gemini CLI".into(),
+        config_path: home.join(".gemini/settings.json"),
+        detection: vec![Probe::Path(home.join(".gemini"))],
+        config_format: ConfigFormat::Gemini,
+        config_strategy: ConfigStrategy::ManagedFile,
+    });
+
+    tools.push(ToolDef {
+        id: "goose".into(),
+        name: "Goose".into(),
+        config_path: config_home.join("goose/config.yaml"),
+        detection: vec![Probe::Path(config_home.join("goose"))],
+        config_format: ConfigFormat::GooseYaml,
+        config_strategy: ConfigStrategy::ManagedFile,
+    });
+
+    tools.push(ToolDef {
+        id: "continue".into(),
+        name: "Continue".into(),
+        config_path: home.join(".continue/config.yaml"),
+        detection: vec![Probe::Path(home.join(".continue"))],
+        config_format: ConfigFormat::ContinueYaml,
+        config_strategy: ConfigStrategy::ManagedFile,
+    });
+
+    let mut vscode_user_dirs = Vec::new();
+    for (id, name, product, bin, flatpak_id) in [
+        (
+            "vscode",
+            "VS Code",
+            "Code",
+            "code",
+            Some("com.visualstudio.code"),
+        ),
+        (
+            "vscode-insiders",
+            "VS Code Insiders",
+            "Code - Insiders",
+            "code-insiders",
+            None,
+        ),
+    ] {
+        let mut linux = vec![
+            Probe::Path(PathBuf::from("/usr/share").join(bin)),
+            Probe::Path(PathBuf::from("/usr/bin").join(bin)),
+            Probe::Path(PathBuf::from("/snap/bin").join(bin)),
+            Probe::DesktopEntry(bin),
+        ];
+        // A Flatpak install keeps its settings inside the sandbox's own
+        // config dir; use that when the host one was never created
+        let mut user_dir = app_data_dir(home).join(product).join("User");
+        if let Some(flatpak_id) = flatpak_id {
+            linux.extend(flatpak_probes(home, flatpak_id));
+            let sandboxed = home
+                .join(".var/app")
+                .join(flatpak_id)
+                .join("config")
+                .join(product)
+                .join("User");
+            if !cfg!(target_os = "macos") && !user_dir.exists() && sandboxed.exists() {
+                user_dir = sandboxed;
+            }
+        }
+        vscode_user_dirs.push(user_dir.clone());
+        let mut detection = vec![Probe::Path(user_dir.clone())];
+        detection.extend(app_probes(&format!("Visual Studio {product}.app"), linux));
+        tools.push(ToolDef {
+            id: id.into(),
+            name: name.into(),
+            config_path: user_dir.join("mcp.json"),
+            detection,
+            config_format: ConfigFormat::VsCode,
+            config_strategy: ConfigStrategy::ManagedFile,
+        });
+    }
+
+    // Workspace configs live in each project's .vscode folder; offer the
+    // folders VS Code has opened, and keep enabled ones it has since forgotten
+    let mut folders = recent_vscode_folders(&vscode_user_dirs, home);
+    folders.extend(workspaces.iter().cloned());
+    folders.sort();
+    folders.dedup();
+    for folder in folders {
+        let label = folder
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| folder.display().to_string());
+        tools.push(ToolDef {
+            id: vscode_workspace_id(&folder),
+            name: format!("VS Code Workspace ({label})"),
+            config_path: folder.join(".vscode/mcp.json"),
+            detection: vec![Probe::Path(folder)],
+            config_format: ConfigFormat::VsCode,
+            config_strategy: ConfigStrategy::ManagedFile,
+        });
+    }
+
     tools
 }
 
-fn find_tool_def(home: &Path, id: &str) -> Result<ToolDef, AppError> {
-    get_tool_definitions(home)
+// ---------------------------------------------------------------------------
+// Platform paths and install detection
+// ---------------------------------------------------------------------------
+
+/// An XDG base directory: the variable if it holds an absolute path,
+/// otherwise its default under the home directory.
+fn xdg_dir(var: &str, home: &Path, default: &str) -> PathBuf {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home.join(default))
+}
+
+/// Where Electron apps (Claude Desktop, VS Code) keep per-user data.
+fn app_data_dir(home: &Path) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        home.join("Library/Application Support")
+    } else {
+        xdg_dir("XDG_CONFIG_HOME", home, ".config")
+    }
+}
+
+/// Folders VS Code has opened, from the window state and per-folder profile
+/// associations in each user dir's `globalStorage/storage.json`. Folders
+/// that no longer exist, multi-root `.code-workspace` files and the home
+/// directory itself are left out.
+fn recent_vscode_folders(user_dirs: &[PathBuf], home: &Path) -> Vec<PathBuf> {
+    let mut uris = Vec::new();
+    for user_dir in user_dirs {
+        let Ok(content) = std::fs::read_to_string(user_dir.join("globalStorage/storage.json"))
+        else {
+            continue;
+        };
+        let Ok(storage) = serde_json::from_str::<serde_json::Value>(&content) else {
+            continue;
+        };
+        let windows = &storage["windowsState"];
+        uris.push(windows["lastActiveWindow"]["folder"].clone());
+        if let Some(opened) = windows["openedWindows"].as_array() {
+            uris.extend(opened.iter().map(|w| w["folder"].clone()));
+        }
+        if let Some(folders) = storage["backupWorkspaces"]["folders"].as_array() {
+            uris.extend(folders.iter().map(|f| f["folderUri"].clone()));
+        }
+        if let Some(workspaces) = storage["profileAssociations"]["workspaces"].as_object() {
+            uris.extend(workspaces.keys().map(|uri| serde_json::json!(uri)));
+        }
+    }
+
+    let mut folders: Vec<PathBuf> = uris
+        .iter()
+        .filter_map(|uri| url::Url::parse(uri.as_str()?).ok())
+        .filter(|uri| uri.scheme() == "file")
+        .filter_map(|uri| uri.to_file_path().ok())
+        .filter(|path| path.is_dir() && path != home)
+        .collect();
+    folders.sort();
+    folders.dedup();
+    folders
+}
+
+fn vscode_workspace_id(folder: &Path) -> String {
+    let digest = Sha256::digest(folder.to_string_lossy().as_bytes());
+    let hash: String = digest[..4].iter().map(|b| format!("{b:02x}")).collect();
+    format!("vscode-workspace-{hash}")
+}
+
+/// The project folder of a VS Code workspace integration.
+fn vscode_workspace_folder(tool: &ToolDef) -> Option<PathBuf> {
+    if !tool.id.starts_with("vscode-workspace-") {
+        return None;
+    }
+    // `<folder>/.vscode/mcp.json`
+    tool.config_path.parent()?.parent().map(Path::to_path_buf)
+}
+
+/// Install locations for a desktop app: its bundle in /Applications on macOS,
+/// the given package, AppImage, Flatpak, Snap and launcher probes elsewhere.
+fn app_probes(bundle: &str, linux: Vec<Probe>) -> Vec<Probe> {
+    if cfg!(target_os = "macos") {
+        vec![Probe::Path(PathBuf::from("/Applications").join(bundle))]
+    } else {
+        linux
+    }
+}
+
+/// System-wide and per-user Flatpak installs of an app.
+fn flatpak_probes(home: &Path, app_id: &str) -> Vec<Probe> {
+    vec![
+        Probe::Path(PathBuf::from("/var/lib/flatpak/app").join(app_id)),
+        Probe::Path(home.join(".local/share/flatpak/app").join(app_id)),
+    ]
+}
+
+/// Folders AppImages usually live in (AppImageLauncher uses ~/Applications).
+fn appimage_dirs(home: &Path) -> Vec<PathBuf> {
+    vec![
+        home.join("Applications"),
+        home.join(".local/bin"),
+        PathBuf::from("/opt"),
+    ]
+}
+
+/// XDG applications directories, plus the Flatpak and Snap export dirs that
+/// aren't always on XDG_DATA_DIRS.
+fn desktop_entry_dirs(home: &Path) -> Vec<PathBuf> {
+    let mut data_dirs = vec![xdg_dir("XDG_DATA_HOME", home, ".local/share")];
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    data_dirs.extend(
+        system
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+    data_dirs.push(home.join(".local/share/flatpak/exports/share"));
+    data_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    data_dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in data_dirs.into_iter().map(|d| d.join("applications")) {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Whether a tool is installed, and why (or why not) in a sentence for the UI.
+fn detect(probes: &[Probe], home: &Path) -> (bool, String) {
+    for probe in probes {
+        let found = match probe {
+            Probe::Path(path) => path.exists().then(|| path.clone()),
+            Probe::AppImage(prefix) => appimage_dirs(home)
+                .iter()
+                .find_map(|dir| find_appimage(dir, prefix)),
+            Probe::DesktopEntry(id) => desktop_entry_dirs(home)
+                .into_iter()
+                .map(|dir| dir.join(format!("{id}.desktop")))
+                .find(|path| path.exists()),
+        };
+        if let Some(path) = found {
+            let what = match probe {
+                Probe::Path(_) => "",
+                Probe::AppImage(_) => "AppImage ",
+                Probe::DesktopEntry(_) => "desktop entry ",
+            };
+            return (true, format!("Found {what}{}", tilde(&path, home)));
+        }
+    }
+
+    let checked: Vec<String> = probes
+        .iter()
+        .map(|probe| match probe {
+            Probe::Path(path) => tilde(path, home),
+            Probe::AppImage(prefix) => format!("{prefix}*.AppImage"),
+            Probe::DesktopEntry(id) => format!("{id}.desktop"),
+        })
+        .collect();
+    (false, format!("Not found: checked {}", checked.join(", ")))
+}
+
+/// An AppImage in `dir` whose name starts with `prefix`, ignoring case.
+fn find_appimage(dir: &Path, prefix: &str) -> Option<PathBuf> {
+    let prefix = prefix.to_lowercase();
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            name.starts_with(&prefix) && name.ends_with(".appimage")
+        })
+}
+
+/// Display a path with the home directory shortened to `~`.
+fn tilde(path: &Path, home: &Path) -> String {
+    match path.strip_prefix(home) {
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Project folders of the enabled VS Code workspace integrations.
+fn enabled_workspaces(state: &SharedState) -> Vec<PathBuf> {
+    state.lock().unwrap().vscode_workspaces.clone()
+}
+
+fn find_tool_def(home: &Path, workspaces: &[PathBuf], id: &str) -> Result<ToolDef, AppError> {
+    get_tool_definitions(home, workspaces)
         .into_iter()
         .find(|t| t.id == id)
         .ok_or_else(|| AppError::IntegrationNotFound(id.to_string()))
@@ -197,6 +578,10 @@ fn parse_config(path: &Path, format: &ConfigFormat) -> (bool, u16, Vec<ExistingM
         ConfigFormat::OpenCode => parse_opencode(path),
         ConfigFormat::Zed => parse_zed(path),
         ConfigFormat::CodexToml => parse_codex_toml(path),
+        ConfigFormat::VsCode => parse_vscode(path),
+        ConfigFormat::Gemini => parse_gemini(path),
+        ConfigFormat::GooseYaml => parse_goose(path),
+        ConfigFormat::ContinueYaml => parse_continue(path),
     }
 }
 
@@ -459,59 +844,47 @@ fn parse_codex_toml(path: &Path) -> (bool, u16, Vec<ExistingMcpServer>) {
     (enabled, port, existing)
 }
 
-// ---------------------------------------------------------------------------
-// Import — read full ServerConfig from each format (including env)
-// ---------------------------------------------------------------------------
-
-/// Read a tool's config and return ready-to-insert ServerConfig entries.
-fn read_importable_servers(tool: &ToolDef) -> Result<Vec<ServerConfig>, AppError> {
-    if !tool.config_path.exists() {
-        return Ok(Vec::new());
-    }
-    match &tool.config_format {
-        ConfigFormat::McpServers => import_mcp_servers(&tool.config_path),
-        ConfigFormat::OpenCode => import_opencode(&tool.config_path),
-        ConfigFormat::Zed => import_zed(&tool.config_path),
-        ConfigFormat::CodexToml => import_codex_toml(&tool.config_path),
-    }
-}
-
-fn json_obj_to_env(value: &serde_json::Value, key: &str) -> Option<HashMap<String, String>> {
-    value.get(key).and_then(|v| v.as_object()).map(|obj| {
-        obj.iter()
-            .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
-            .collect()
-    })
-}
+/// VS Code format: {"servers": {"name": {"type":"stdio"|"http"|"sse", ...}}, "inputs": [...]}
+fn parse_vscode(path: &Path) -> (bool, u16, Vec<ExistingMcpServer>) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return (false, 0, Vec::new()),
+    };
+    // mcp.json is JSONC, like VS Code's other settings files
+    let stripped = strip_json_comments(&content);
+    let config: serde_json::Value = match serde_json::from_str(&stripped) {
+        Ok(v) => v,
+        Err(_) => return (false, 0, Vec::new()),
+    };
 
-fn import_mcp_servers(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
-    let content = std::fs::read_to_string(path)?;
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| AppError::Protocol(format!("Invalid JSON: {e}")))?;
-    let servers_obj = match config.get("mcpServers").and_then(|v| v.as_object()) {
+    let servers_obj = match config.get("servers").and_then(|v| v.as_object()) {
         Some(obj) => obj,
-        None => return Ok(Vec::new()),
+        None => return (false, 0, Vec::new()),
     };
-    let mut result = Vec::new();
+
+    let mut enabled = false;
+    let mut port = 0u16;
+    let mut existing = Vec::new();
+
     for (key, value) in servers_obj {
-        // Skip discovery entry and proxy URLs
-        if key == DISCOVERY_SERVER_NAME {
-            continue;
-        }
         let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+
         if is_proxy_url(entry_url) {
+            enabled = true;
+            if port == 0 {
+                port = extract_port_from_url(entry_url);
+            }
             continue;
         }
 
-        let has_url = value.get("url").and_then(|v| v.as_str()).is_some();
-        result.push(ServerConfig {
-            id: Uuid::new_v4().to_string(),
+        let has_url = !entry_url.is_empty();
+
+        existing.push(ExistingMcpServer {
             name: key.clone(),
-            enabled: true,
             transport: if has_url {
-                ServerTransport::Http
+                "http".into()
             } else {
-                ServerTransport::Stdio
+                "stdio".into()
             },
             command: value
                 .get("command")
@@ -522,93 +895,378 @@ fn import_mcp_servers(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
                     .filter_map(|v| v.as_str().map(String::from))
                     .collect()
             }),
-            env: json_obj_to_env(value, "env"),
             url: if has_url {
-                value.get("url").and_then(|v| v.as_str()).map(String::from)
+                Some(entry_url.to_string())
             } else {
                 None
             },
-            headers: json_obj_to_env(value, "headers"),
-            tags: None,
-            status: Some(ServerStatus::Disconnected),
-            last_connected: None,
-            managed: None,
-            managed_by: None,
-            registry_name: None,
         });
     }
-    Ok(result)
+
+    (enabled, port, existing)
 }
 
-fn import_opencode(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
-    let content = std::fs::read_to_string(path)?;
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| AppError::Protocol(format!("Invalid JSON: {e}")))?;
-    let servers_obj = match config.get("mcp").and_then(|v| v.as_object()) {
+/// Gemini CLI's URL for a server: `httpUrl` for streamable HTTP, `url` for SSE.
+fn gemini_url(value: &serde_json::Value) -> Option<&str> {
+    value
+        .get("httpUrl")
+        .or_else(|| value.get("url"))
+        .and_then(|v| v.as_str())
+}
+
+/// Gemini CLI format: {"mcpServers": {"name": {"command":"..."|"httpUrl":"..."|"url":"...", ...}}}
+fn parse_gemini(path: &Path) -> (bool, u16, Vec<ExistingMcpServer>) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return (false, 0, Vec::new()),
+    };
+    // Gemini CLI allows comments in settings.json
+    let stripped = strip_json_comments(&content);
+    let config: serde_json::Value = match serde_json::from_str(&stripped) {
+        Ok(v) => v,
+        Err(_) => return (false, 0, Vec::new()),
+    };
+
+    let servers_obj = match config.get("mcpServers").and_then(|v| v.as_object()) {
         Some(obj) => obj,
-        None => return Ok(Vec::new()),
+        None => return (false, 0, Vec::new()),
     };
-    let mut result = Vec::new();
+
+    let mut enabled = false;
+    let mut port = 0u16;
+    let mut existing = Vec::new();
+
     for (key, value) in servers_obj {
-        let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        let entry_url = gemini_url(value).unwrap_or("");
+
         if is_proxy_url(entry_url) {
+            enabled = true;
+            if port == 0 {
+                port = extract_port_from_url(entry_url);
+            }
             continue;
         }
 
-        let server_type = value
-            .get("type")
-            .and_then(|v| v.as_str())
-            .unwrap_or("local");
-        let is_remote = server_type == "remote";
-
-        // OpenCode: "command" is an array, "environment" instead of "env"
-        let (command, args) = if let Some(cmd_arr) = value.get("command").and_then(|v| v.as_array())
-        {
-            let parts: Vec<String> = cmd_arr
-                .iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect();
-            if parts.is_empty() {
-                (None, None)
-            } else {
-                (
-                    Some(parts[0].clone()),
-                    if parts.len() > 1 {
-                        Some(parts[1..].to_vec())
-                    } else {
-                        None
-                    },
-                )
-            }
-        } else {
-            (None, None)
-        };
+        let has_url = !entry_url.is_empty();
 
-        result.push(ServerConfig {
-            id: Uuid::new_v4().to_string(),
+        existing.push(ExistingMcpServer {
             name: key.clone(),
-            enabled: true,
-            transport: if is_remote {
-                ServerTransport::Http
+            transport: if has_url {
+                "http".into()
             } else {
-                ServerTransport::Stdio
+                "stdio".into()
             },
-            command,
-            args,
-            env: json_obj_to_env(value, "environment"),
-            url: value.get("url").and_then(|v| v.as_str()).map(String::from),
-            headers: json_obj_to_env(value, "headers"),
-            tags: None,
-            status: Some(ServerStatus::Disconnected),
-            last_connected: None,
-            managed: None,
-            managed_by: None,
-            registry_name: None,
-        });
-    }
-    Ok(result)
-}
-
+            command: value
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            }),
+            url: if has_url {
+                Some(entry_url.to_string())
+            } else {
+                None
+            },
+        });
+    }
+
+    (enabled, port, existing)
+}
+
+/// Goose format: extensions.name: {type: stdio|sse|streamable_http, cmd, args, uri, ...}
+fn parse_goose(path: &Path) -> (bool, u16, Vec<ExistingMcpServer>) {
+    let config = match read_yaml(path) {
+        Ok((_, v)) => v,
+        Err(_) => return (false, 0, Vec::new()),
+    };
+
+    let extensions = match config.get("extensions").and_then(|v| v.as_mapping()) {
+        Some(m) => m,
+        None => return (false, 0, Vec::new()),
+    };
+
+    let mut enabled = false;
+    let mut port = 0u16;
+    let mut existing = Vec::new();
+
+    for (key, value) in extensions {
+        // Builtin and platform extensions aren't MCP servers
+        if !is_goose_mcp_extension(value) {
+            continue;
+        }
+        let entry_url = value.get("uri").and_then(|v| v.as_str()).unwrap_or("");
+
+        if is_proxy_url(entry_url) {
+            enabled = true;
+            if port == 0 {
+                port = extract_port_from_url(entry_url);
+            }
+            continue;
+        }
+
+        let has_url = !entry_url.is_empty();
+
+        existing.push(ExistingMcpServer {
+            name: goose_extension_name(key, value),
+            transport: if has_url {
+                "http".into()
+            } else {
+                "stdio".into()
+            },
+            command: value.get("cmd").and_then(|v| v.as_str()).map(String::from),
+            args: yaml_str_list(value, "args"),
+            url: if has_url {
+                Some(entry_url.to_string())
+            } else {
+                None
+            },
+        });
+    }
+
+    (enabled, port, existing)
+}
+
+/// Continue format: mcpServers: [{name, command, args, env} | {name, type, url}]
+fn parse_continue(path: &Path) -> (bool, u16, Vec<ExistingMcpServer>) {
+    let config = match read_yaml(path) {
+        Ok((_, v)) => v,
+        Err(_) => return (false, 0, Vec::new()),
+    };
+
+    let servers = match config.get("mcpServers").and_then(|v| v.as_sequence()) {
+        Some(s) => s,
+        None => return (false, 0, Vec::new()),
+    };
+
+    let mut enabled = false;
+    let mut port = 0u16;
+    let mut existing = Vec::new();
+
+    for value in servers {
+        // Hub blocks (`uses: owner/block`) have nothing to import
+        let Some(name) = value.get("name").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let entry_url = value.get("url").and_then(|v| v.as_str()).unwrap_or("");
+
+        if is_proxy_url(entry_url) {
+            enabled = true;
+            if port == 0 {
+                port = extract_port_from_url(entry_url);
+            }
+            continue;
+        }
+
+        let has_url = !entry_url.is_empty();
+
+        existing.push(ExistingMcpServer {
+            name: name.to_string(),
+            transport: if has_url {
+                "http".into()
+            } else {
+                "stdio".into()
+            },
+            command: value
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            args: yaml_str_list(value, "args"),
+            url: if has_url {
+                Some(entry_url.to_string())
+            } else {
+                None
+            },
+        });
+    }
+
+    (enabled, port, existing)
+}
+
+// ---------------------------------------------------------------------------
+// Import — read full ServerConfig from each format (including env)
+// ---------------------------------------------------------------------------
+
+/// Read a tool's config and return ready-to-insert ServerConfig entries,
+/// plus the secrets they reference.
+fn read_importable_servers(
+    tool: &ToolDef,
+    home: &Path,
+) -> Result<(Vec<ServerConfig>, Vec<MissingSecret>), AppError> {
+    if !tool.config_path.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let servers = match &tool.config_format {
+        ConfigFormat::McpServers => import_mcp_servers(&tool.config_path),
+        ConfigFormat::OpenCode => import_opencode(&tool.config_path),
+        ConfigFormat::Zed => import_zed(&tool.config_path),
+        ConfigFormat::CodexToml => import_codex_toml(&tool.config_path),
+        ConfigFormat::VsCode => {
+            let workspace = vscode_workspace_folder(tool);
+            return import_vscode(&tool.config_path, workspace.as_deref(), home);
+        }
+        ConfigFormat::Gemini => import_gemini(&tool.config_path),
+        ConfigFormat::GooseYaml => import_goose(&tool.config_path),
+        ConfigFormat::ContinueYaml => import_continue(&tool.config_path),
+    }?;
+    Ok((servers, Vec::new()))
+}
+
+fn json_obj_to_env(value: &serde_json::Value, key: &str) -> Option<HashMap<String, String>> {
+    value.get(key).and_then(|v| v.as_object()).map(|obj| {
+        obj.iter()
+            .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+            .collect()
+    })
+}
+
+fn import_mcp_servers(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::Protocol(format!("Invalid JSON: {e}")))?;
+    let servers_obj = match config.get("mcpServers").and_then(|v| v.as_object()) {
+        Some(obj) => obj,
+        None => return Ok(Vec::new()),
+    };
+    let mut result = Vec::new();
+    for (key, value) in servers_obj {
+        // Skip discovery entry and proxy URLs
+        if key == DISCOVERY_SERVER_NAME {
+            continue;
+        }
+        let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        if is_proxy_url(entry_url) {
+            continue;
+        }
+
+        let has_url = value.get("url").and_then(|v| v.as_str()).is_some();
+        result.push(ServerConfig {
+            id: Uuid::new_v4().to_string(),
+            name: key.clone(),
+            enabled: true,
+            transport: if has_url {
+                ServerTransport::Http
+            } else {
+                ServerTransport::Stdio
+            },
+            command: value
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            }),
+            env: json_obj_to_env(value, "env"),
+            url: if has_url {
+                value.get("url").and_then(|v| v.as_str()).map(String::from)
+            } else {
+                None
+            },
+            headers: json_obj_to_env(value, "headers"),
+            tags: None,
+            status: Some(ServerStatus::Disconnected),
+            last_connected: None,
+            managed: None,
+            managed_by: None,
+            registry_name: None,
+            sandbox: None,
+            http_mode: None,
+            cwd: None,
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
+        });
+    }
+    Ok(result)
+}
+
+fn import_opencode(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::Protocol(format!("Invalid JSON: {e}")))?;
+    let servers_obj = match config.get("mcp").and_then(|v| v.as_object()) {
+        Some(obj) => obj,
+        None => return Ok(Vec::new()),
+    };
+    let mut result = Vec::new();
+    for (key, value) in servers_obj {
+        let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        if is_proxy_url(entry_url) {
+            continue;
+        }
+
+        let server_type = value
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("local");
+        let is_remote = server_type == "remote";
+
+        // OpenCode: "command" is an array, "environment" instead of "env"
+        let (command, args) = if let Some(cmd_arr) = value.get("command").and_then(|v| v.as_array())
+        {
+            let parts: Vec<String> = cmd_arr
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect();
+            if parts.is_empty() {
+                (None, None)
+            } else {
+                (
+                    Some(parts[0].clone()),
+                    if parts.len() > 1 {
+                        Some(parts[1..].to_vec())
+                    } else {
+                        None
+                    },
+                )
+            }
+        } else {
+            (None, None)
+        };
+
+        result.push(ServerConfig {
+            id: Uuid::new_v4().to_string(),
+            name: key.clone(),
+            enabled: true,
+            transport: if is_remote {
+                ServerTransport::Http
+            } else {
+                ServerTransport::Stdio
+            },
+            command,
+            args,
+            env: json_obj_to_env(value, "environment"),
+            url: value.get("url").and_then(|v| v.as_str()).map(String::from),
+            headers: json_obj_to_env(value, "headers"),
+            tags: None,
+            status: Some(ServerStatus::Disconnected),
+            last_connected: None,
+            managed: None,
+            managed_by: None,
+            registry_name: None,
+            sandbox: None,
+            http_mode: None,
+            cwd: None,
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
+        });
+    }
+    Ok(result)
+}
+
 fn import_zed(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
     let content = std::fs::read_to_string(path)?;
     let stripped = strip_json_comments(&content);
@@ -619,76 +1277,411 @@ fn import_zed(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
         None => return Ok(Vec::new()),
     };
     let mut result = Vec::new();
-    for (key, value) in servers_obj {
-        let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+    for (key, value) in servers_obj {
+        let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        if is_proxy_url(entry_url) {
+            continue;
+        }
+
+        let has_url = !entry_url.is_empty();
+        result.push(ServerConfig {
+            id: Uuid::new_v4().to_string(),
+            name: key.clone(),
+            enabled: true,
+            transport: if has_url {
+                ServerTransport::Http
+            } else {
+                ServerTransport::Stdio
+            },
+            command: value
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            }),
+            env: json_obj_to_env(value, "env"),
+            url: if has_url {
+                Some(entry_url.to_string())
+            } else {
+                None
+            },
+            headers: json_obj_to_env(value, "headers"),
+            tags: None,
+            status: Some(ServerStatus::Disconnected),
+            last_connected: None,
+            managed: None,
+            managed_by: None,
+            registry_name: None,
+            sandbox: None,
+            http_mode: None,
+            cwd: None,
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
+        });
+    }
+    Ok(result)
+}
+
+fn import_codex_toml(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let config: toml::Value = content
+        .parse()
+        .map_err(|e| AppError::Protocol(format!("Invalid TOML: {e}")))?;
+    let servers_table = match config.get("mcp_servers").and_then(|v| v.as_table()) {
+        Some(t) => t,
+        None => return Ok(Vec::new()),
+    };
+    let mut result = Vec::new();
+    for (key, value) in servers_table {
+        let entry_url = value.get("url").and_then(|v| v.as_str()).unwrap_or("");
+        if is_proxy_url(entry_url) {
+            continue;
+        }
+
+        let has_url = !entry_url.is_empty();
+
+        let env = value.get("env").and_then(|v| v.as_table()).map(|t| {
+            t.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect::<HashMap<String, String>>()
+        });
+
+        result.push(ServerConfig {
+            id: Uuid::new_v4().to_string(),
+            name: key.clone(),
+            enabled: true,
+            transport: if has_url {
+                ServerTransport::Http
+            } else {
+                ServerTransport::Stdio
+            },
+            command: value
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            }),
+            env,
+            url: if has_url {
+                Some(entry_url.to_string())
+            } else {
+                None
+            },
+            headers: None,
+            tags: None,
+            status: Some(ServerStatus::Disconnected),
+            last_connected: None,
+            managed: None,
+            managed_by: None,
+            registry_name: None,
+            sandbox: None,
+            http_mode: None,
+            cwd: None,
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
+        });
+    }
+    Ok(result)
+}
+
+/// Import VS Code servers. `${input:ID}` prompts become `${secret:ID}`
+/// references, returned with the prompts' descriptions so they can be set.
+/// `${userHome}` and, for a workspace config, `${workspaceFolder}` are
+/// expanded, since nothing outside VS Code would.
+fn import_vscode(
+    path: &Path,
+    workspace: Option<&Path>,
+    home: &Path,
+) -> Result<(Vec<ServerConfig>, Vec<MissingSecret>), AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let stripped = strip_json_comments(&content);
+    let config: serde_json::Value = serde_json::from_str(&stripped)
+        .map_err(|e| AppError::Protocol(format!("Invalid JSON: {e}")))?;
+    let servers_obj = match config.get("servers").and_then(|v| v.as_object()) {
+        Some(obj) => obj,
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    let mut result = Vec::new();
+    let mut input_names = Vec::new();
+    for (key, value) in servers_obj {
+        let entry_url = value.get("url").and_then(|u| u.as_str()).unwrap_or("");
+        if is_proxy_url(entry_url) {
+            continue;
+        }
+        rewrite_references(
+            &value.to_string(),
+            "input",
+            str::to_string,
+            &mut input_names,
+        );
+
+        let has_url = !entry_url.is_empty();
+        let is_sse = value.get("type").and_then(|v| v.as_str()) == Some("sse");
+
+        // `${input:ID}` prompts have no value outside VS Code — turn them into secrets
+        let resolve =
+            |text: &str| expand_vscode_variables(&inputs_to_secrets(text), workspace, home);
+        let with_secrets = |map: HashMap<String, String>| -> HashMap<String, String> {
+            map.into_iter().map(|(k, v)| (k, resolve(&v))).collect()
+        };
+
+        result.push(ServerConfig {
+            id: Uuid::new_v4().to_string(),
+            name: key.clone(),
+            enabled: true,
+            transport: if has_url {
+                ServerTransport::Http
+            } else {
+                ServerTransport::Stdio
+            },
+            command: value.get("command").and_then(|v| v.as_str()).map(resolve),
+            args: value
+                .get("args")
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(resolve)).collect()),
+            env: json_obj_to_env(value, "env").map(with_secrets),
+            url: if has_url {
+                Some(resolve(entry_url))
+            } else {
+                None
+            },
+            headers: json_obj_to_env(value, "headers").map(with_secrets),
+            tags: None,
+            status: Some(ServerStatus::Disconnected),
+            last_connected: None,
+            managed: None,
+            managed_by: None,
+            registry_name: None,
+            sandbox: None,
+            http_mode: if is_sse {
+                Some(HttpMode::LegacySse)
+            } else {
+                None
+            },
+            cwd: value.get("cwd").and_then(|v| v.as_str()).map(resolve),
+            env_file: value
+                .get("envFile")
+                .and_then(|v| v.as_str())
+                .map(|f| vec![resolve(f)]),
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
+        });
+    }
+    let inputs = config.get("inputs").and_then(|v| v.as_array());
+    let missing = input_names
+        .into_iter()
+        .map(|name| {
+            let description = inputs
+                .into_iter()
+                .flatten()
+                .find(|input| input.get("id").and_then(|v| v.as_str()) == Some(&name))
+                .and_then(|input| input.get("description")?.as_str())
+                .map(String::from);
+            MissingSecret { name, description }
+        })
+        .collect();
+    Ok((result, missing))
+}
+
+fn import_gemini(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let stripped = strip_json_comments(&content);
+    let config: serde_json::Value = serde_json::from_str(&stripped)
+        .map_err(|e| AppError::Protocol(format!("Invalid JSON: {e}")))?;
+    let servers_obj = match config.get("mcpServers").and_then(|v| v.as_object()) {
+        Some(obj) => obj,
+        None => return Ok(Vec::new()),
+    };
+    let mut result = Vec::new();
+    for (key, value) in servers_obj {
+        let entry_url = gemini_url(value).unwrap_or("");
+        if is_proxy_url(entry_url) {
+            continue;
+        }
+
+        let has_url = !entry_url.is_empty();
+        // A plain `url` is Gemini's SSE transport
+        let is_sse = has_url && value.get("httpUrl").is_none();
+
+        // Gemini's tool filters become Agent Hub's own
+        let tool_list = |key: &str| -> Option<Vec<String>> {
+            value.get(key).and_then(|v| v.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+        };
+        let excluded = tool_list("excludeTools").unwrap_or_default();
+        // Settings like `trust` and `timeout` are kept for the restored config
+        let extra: serde_json::Map<String, serde_json::Value> = value
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| !GEMINI_SERVER_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        result.push(ServerConfig {
+            id: Uuid::new_v4().to_string(),
+            name: key.clone(),
+            enabled: true,
+            transport: if has_url {
+                ServerTransport::Http
+            } else {
+                ServerTransport::Stdio
+            },
+            command: value
+                .get("command")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            }),
+            env: json_obj_to_env(value, "env"),
+            url: if has_url {
+                Some(entry_url.to_string())
+            } else {
+                None
+            },
+            headers: json_obj_to_env(value, "headers"),
+            tags: None,
+            status: Some(ServerStatus::Disconnected),
+            last_connected: None,
+            managed: None,
+            managed_by: None,
+            registry_name: None,
+            sandbox: None,
+            http_mode: if is_sse {
+                Some(HttpMode::LegacySse)
+            } else {
+                None
+            },
+            cwd: value.get("cwd").and_then(|v| v.as_str()).map(String::from),
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: (!excluded.is_empty()).then_some(excluded),
+            enabled_tools: tool_list("includeTools"),
+            extra: (!extra.is_empty()).then_some(extra),
+        });
+    }
+    Ok(result)
+}
+
+fn import_goose(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
+    let (_, config) = read_yaml(path)?;
+    let extensions = match config.get("extensions").and_then(|v| v.as_mapping()) {
+        Some(m) => m,
+        None => return Ok(Vec::new()),
+    };
+    let mut result = Vec::new();
+    for (key, value) in extensions {
+        if !is_goose_mcp_extension(value) {
+            continue;
+        }
+        let entry_url = value.get("uri").and_then(|v| v.as_str()).unwrap_or("");
         if is_proxy_url(entry_url) {
             continue;
         }
 
         let has_url = !entry_url.is_empty();
+        let is_sse = value.get("type").and_then(|v| v.as_str()) == Some("sse");
+
         result.push(ServerConfig {
             id: Uuid::new_v4().to_string(),
-            name: key.clone(),
-            enabled: true,
+            name: goose_extension_name(key, value),
+            enabled: value
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
             transport: if has_url {
                 ServerTransport::Http
             } else {
                 ServerTransport::Stdio
             },
-            command: value
-                .get("command")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect()
-            }),
-            env: json_obj_to_env(value, "env"),
+            command: value.get("cmd").and_then(|v| v.as_str()).map(String::from),
+            args: yaml_str_list(value, "args"),
+            env: yaml_str_map(value, "envs"),
             url: if has_url {
                 Some(entry_url.to_string())
             } else {
                 None
             },
-            headers: json_obj_to_env(value, "headers"),
+            headers: yaml_str_map(value, "headers"),
             tags: None,
             status: Some(ServerStatus::Disconnected),
             last_connected: None,
             managed: None,
             managed_by: None,
             registry_name: None,
+            sandbox: None,
+            http_mode: if is_sse {
+                Some(HttpMode::LegacySse)
+            } else {
+                None
+            },
+            cwd: None,
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
         });
     }
     Ok(result)
 }
 
-fn import_codex_toml(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
-    let content = std::fs::read_to_string(path)?;
-    let config: toml::Value = content
-        .parse()
-        .map_err(|e| AppError::Protocol(format!("Invalid TOML: {e}")))?;
-    let servers_table = match config.get("mcp_servers").and_then(|v| v.as_table()) {
-        Some(t) => t,
+fn import_continue(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
+    let (_, config) = read_yaml(path)?;
+    let servers = match config.get("mcpServers").and_then(|v| v.as_sequence()) {
+        Some(s) => s,
         None => return Ok(Vec::new()),
     };
     let mut result = Vec::new();
-    for (key, value) in servers_table {
+    for value in servers {
+        let Some(name) = value.get("name").and_then(|v| v.as_str()) else {
+            continue;
+        };
         let entry_url = value.get("url").and_then(|v| v.as_str()).unwrap_or("");
         if is_proxy_url(entry_url) {
             continue;
         }
 
         let has_url = !entry_url.is_empty();
-
-        let env = value.get("env").and_then(|v| v.as_table()).map(|t| {
-            t.iter()
-                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
-                .collect::<HashMap<String, String>>()
-        });
+        let is_sse = value.get("type").and_then(|v| v.as_str()) == Some("sse");
+        // Continue nests HTTP headers under requestOptions
+        let headers = value
+            .get("requestOptions")
+            .and_then(|opts| yaml_str_map(opts, "headers"));
 
         result.push(ServerConfig {
             id: Uuid::new_v4().to_string(),
-            name: key.clone(),
+            name: name.to_string(),
             enabled: true,
             transport: if has_url {
                 ServerTransport::Http
@@ -699,33 +1692,283 @@ fn import_codex_toml(path: &Path) -> Result<Vec<ServerConfig>, AppError> {
                 .get("command")
                 .and_then(|v| v.as_str())
                 .map(String::from),
-            args: value.get("args").and_then(|v| v.as_array()).map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect()
-            }),
-            env,
+            args: yaml_str_list(value, "args"),
+            env: yaml_str_map(value, "env"),
             url: if has_url {
                 Some(entry_url.to_string())
             } else {
                 None
             },
-            headers: None,
+            headers,
             tags: None,
             status: Some(ServerStatus::Disconnected),
             last_connected: None,
             managed: None,
             managed_by: None,
             registry_name: None,
+            sandbox: None,
+            http_mode: if is_sse {
+                Some(HttpMode::LegacySse)
+            } else {
+                None
+            },
+            cwd: value.get("cwd").and_then(|v| v.as_str()).map(String::from),
+            env_file: None,
+            container: None,
+            oauth: None,
+            replay: None,
+            disabled_tools: None,
+            enabled_tools: None,
+            extra: None,
         });
     }
     Ok(result)
 }
 
+// ---------------------------------------------------------------------------
+// YAML helpers — Goose and Continue
+// ---------------------------------------------------------------------------
+
+/// Read a YAML config, returning its text (for in-place edits) and parsed value.
+/// A missing or empty file reads as null.
+fn read_yaml(path: &Path) -> Result<(String, serde_yaml::Value), AppError> {
+    let content = if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    if content.trim().is_empty() {
+        return Ok((content, serde_yaml::Value::Null));
+    }
+    let value = serde_yaml::from_str(&content)
+        .map_err(|e| AppError::Protocol(format!("Invalid YAML: {e}")))?;
+    Ok((content, value))
+}
+
+/// Replace the block of a top-level key, leaving the rest of the file —
+/// comments included — as written. A missing key is appended.
+fn replace_yaml_section(
+    content: &str,
+    key: &str,
+    value: serde_yaml::Value,
+) -> Result<String, AppError> {
+    let mut section = serde_yaml::Mapping::new();
+    section.insert(key.into(), value);
+    let section = serde_yaml::to_string(&section)
+        .map_err(|e| AppError::Protocol(format!("Failed to serialize YAML: {e}")))?;
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let header = format!("{key}:");
+    let Some(start) = lines.iter().position(|l| {
+        l.strip_prefix(&header)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t', '\r', '\n']))
+    }) else {
+        let mut out = content.to_string();
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&section);
+        return Ok(out);
+    };
+
+    // The block runs over indented lines, comments and sequence items at column 0
+    let mut end = start + 1;
+    while end < lines.len() {
+        let line = lines[end];
+        let continues = line.trim().is_empty()
+            || line.starts_with([' ', '\t', '#'])
+            || (line.starts_with('-') && !line.starts_with("---"));
+        if !continues {
+            break;
+        }
+        end += 1;
+    }
+    // Trailing blank lines and comments belong with what follows
+    while end > start + 1 {
+        let line = lines[end - 1];
+        if line.trim().is_empty() || line.starts_with('#') {
+            end -= 1;
+        } else {
+            break;
+        }
+    }
+
+    let mut out: String = lines[..start].concat();
+    out.push_str(&section);
+    out.push_str(&lines[end..].concat());
+    Ok(out)
+}
+
+/// Write a YAML config with one top-level key replaced.
+fn write_yaml_section(
+    path: &Path,
+    content: &str,
+    key: &str,
+    value: serde_yaml::Value,
+) -> Result<(), AppError> {
+    let content = replace_yaml_section(content, key, value)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn yaml_str_list(value: &serde_yaml::Value, key: &str) -> Option<Vec<String>> {
+    value.get(key).and_then(|v| v.as_sequence()).map(|seq| {
+        seq.iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect()
+    })
+}
+
+/// A string map, e.g. env vars. Unquoted numbers and booleans are kept as text.
+fn yaml_str_map(value: &serde_yaml::Value, key: &str) -> Option<HashMap<String, String>> {
+    value.get(key).and_then(|v| v.as_mapping()).map(|map| {
+        map.iter()
+            .filter_map(|(k, v)| {
+                let v = match v {
+                    serde_yaml::Value::String(s) => s.clone(),
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    serde_yaml::Value::Bool(b) => b.to_string(),
+                    _ => return None,
+                };
+                Some((k.as_str()?.to_string(), v))
+            })
+            .collect()
+    })
+}
+
+/// Whether a Goose extension is an MCP server, rather than a builtin,
+/// platform or frontend extension.
+fn is_goose_mcp_extension(value: &serde_yaml::Value) -> bool {
+    matches!(
+        value.get("type").and_then(|v| v.as_str()),
+        Some("stdio" | "sse" | "streamable_http")
+    )
+}
+
+fn goose_extension_name(key: &serde_yaml::Value, value: &serde_yaml::Value) -> String {
+    value
+        .get("name")
+        .or(Some(key))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// A Goose extension entry for one of our proxy endpoints.
+fn goose_proxy_extension(name: &str, url: &str) -> serde_yaml::Value {
+    let mut ext = serde_yaml::Mapping::new();
+    ext.insert("name".into(), name.into());
+    ext.insert("type".into(), "streamable_http".into());
+    ext.insert("uri".into(), url.into());
+    ext.insert("enabled".into(), true.into());
+    ext.insert("timeout".into(), 300.into());
+    ext.insert("description".into(), "Proxied by Agent Hub".into());
+    serde_yaml::Value::Mapping(ext)
+}
+
+/// A Continue MCP server entry for one of our proxy endpoints.
+fn continue_proxy_server(name: &str, url: &str) -> serde_yaml::Value {
+    let mut server = serde_yaml::Mapping::new();
+    server.insert("name".into(), name.into());
+    server.insert("type".into(), "streamable-http".into());
+    server.insert("url".into(), url.into());
+    serde_yaml::Value::Mapping(server)
+}
+
+fn yaml_string_map(map: &HashMap<String, String>) -> serde_yaml::Value {
+    let mut sorted: Vec<_> = map.iter().collect();
+    sorted.sort();
+    sorted
+        .into_iter()
+        .map(|(k, v)| (k.as_str().into(), v.as_str().into()))
+        .collect::<serde_yaml::Mapping>()
+        .into()
+}
+
+/// Goose extensions that aren't MCP servers, which we leave in place.
+fn goose_other_extensions(config: &serde_yaml::Value) -> serde_yaml::Mapping {
+    config
+        .get("extensions")
+        .and_then(|v| v.as_mapping())
+        .map(|m| {
+            m.iter()
+                .filter(|(_, v)| !is_goose_mcp_extension(v))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Continue hub blocks (`uses:` entries), which we leave in place.
+fn continue_hub_blocks(config: &serde_yaml::Value) -> Vec<serde_yaml::Value> {
+    config
+        .get("mcpServers")
+        .and_then(|v| v.as_sequence())
+        .map(|s| {
+            s.iter()
+                .filter(|v| v.get("name").is_none() && v.get("uses").is_some())
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Rewrite VS Code `${input:ID}` references as `${secret:ID}`, so the value is
+/// stored once in Agent Hub instead of being prompted for.
+fn inputs_to_secrets(text: &str) -> String {
+    rewrite_references(text, "input", crate::secrets::reference, &mut Vec::new())
+}
+
+/// Replace VS Code's `${userHome}` and `${workspaceFolder}` with the paths they
+/// stand for. Without a workspace, `${workspaceFolder}` is left as written.
+fn expand_vscode_variables(text: &str, workspace: Option<&Path>, home: &Path) -> String {
+    let text = text.replace("${userHome}", &home.to_string_lossy());
+    match workspace {
+        Some(folder) => text.replace("${workspaceFolder}", &folder.to_string_lossy()),
+        None => text,
+    }
+}
+
+/// Replace every `${<kind>:NAME}` in `text` with `to(NAME)`, collecting the names.
+/// References with names that aren't valid secret names are left as written.
+fn rewrite_references(
+    text: &str,
+    kind: &str,
+    to: impl Fn(&str) -> String,
+    names: &mut Vec<String>,
+) -> String {
+    let open = format!("${{{kind}:");
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(&open) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + open.len()..];
+        match after.find('}') {
+            Some(end) if crate::secrets::is_valid_name(&after[..end]) => {
+                let name = &after[..end];
+                out.push_str(&to(name));
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push_str(&open);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Extract port number from a URL like "http://localhost:12345/mcp".
 fn extract_port_from_url(url: &str) -> u16 {
     if let Ok(parsed) = url::Url::parse(url) {
@@ -808,6 +2051,7 @@ fn run_claude_mcp(args: &[&str]) -> Result<String, AppError> {
     let output = std::process::Command::new(&claude)
         .arg("mcp")
         .args(args)
+        .envs(crate::shell_env::get().vars())
         .env_remove("CLAUDECODE")
         .output()
         .map_err(|e| {
@@ -942,7 +2186,7 @@ pub async fn detect_integrations(
     proxy_state: State<'_, ProxyState>,
 ) -> Result<Vec<AiToolInfo>, AppError> {
     let home = home_dir()?;
-    let tools = get_tool_definitions(&home);
+    let tools = get_tool_definitions(&home, &enabled_workspaces(&state));
     let _port = proxy_state.port().await;
 
     let enabled_ids: Vec<String> = {
@@ -952,7 +2196,7 @@ pub async fn detect_integrations(
 
     let mut results = Vec::new();
     for tool in tools {
-        let installed = tool.detection_paths.iter().any(|p| p.exists());
+        let (installed, detection) = detect(&tool.detection, &home);
         let enabled = enabled_ids.contains(&tool.id);
 
         let (_, configured_port, existing_servers) = if installed {
@@ -965,10 +2209,12 @@ pub async fn detect_integrations(
             id: tool.id,
             name: tool.name,
             installed,
+            detection,
             enabled,
             config_path: tool.config_path.display().to_string(),
             configured_port,
             existing_servers,
+            missing_secrets: Vec::new(),
         });
     }
 
@@ -983,17 +2229,31 @@ pub async fn enable_integration(
     id: String,
 ) -> Result<AiToolInfo, AppError> {
     let home = home_dir()?;
-    let tool = find_tool_def(&home, &id)?;
+    let tool = find_tool_def(&home, &enabled_workspaces(&state), &id)?;
     let port = proxy_state.port().await;
 
     // Import existing servers from the config file (format-agnostic)
-    let candidates = read_importable_servers(&tool)?;
+    let (candidates, referenced_secrets) = read_importable_servers(&tool, &home)?;
+    let missing_secrets: Vec<MissingSecret> = {
+        let secrets = app.state::<SecretStore>();
+        referenced_secrets
+            .into_iter()
+            .filter(|secret| secrets.get(&secret.name).is_none())
+            .collect()
+    };
+    for secret in &missing_secrets {
+        warn!(
+            "Servers imported from {} need the secret '{}' to be set",
+            tool.name, secret.name
+        );
+    }
 
     let imported_count = {
         let mut s = state.lock().unwrap();
 
+        let secrets = app.state::<SecretStore>();
         let mut imported = 0;
-        for server in candidates {
+        for mut server in candidates {
             // Never overwrite a managed server (owned by Memory, plugins, etc.)
             if s.servers.iter().any(|srv| srv.name == server.name && srv.managed_by.is_some()) {
                 info!(
@@ -1002,6 +2262,7 @@ pub async fn enable_integration(
                 );
                 continue;
             }
+            migrate_server_credentials(&secrets, std::slice::from_mut(&mut server));
             if let Some(idx) = s.servers.iter().position(|srv| srv.name == server.name) {
                 info!(
                     "Replacing existing server '{}' with import from {}",
@@ -1019,6 +2280,12 @@ pub async fn enable_integration(
         if !s.enabled_integrations.contains(&id) {
             s.enabled_integrations.push(id.clone());
         }
+        if let Some(folder) = vscode_workspace_folder(&tool) {
+            if !s.vscode_workspaces.contains(&folder) {
+                s.vscode_workspaces.push(folder);
+                save_vscode_workspaces(&app, &s.vscode_workspaces);
+            }
+        }
 
         save_servers(&app, &s.servers);
         save_enabled_integrations(&app, &s.enabled_integrations);
@@ -1043,10 +2310,12 @@ pub async fn enable_integration(
         id: tool.id,
         name: tool.name,
         installed: true,
+        detection: detect(&tool.detection, &home).1,
         enabled: true,
         config_path: tool.config_path.display().to_string(),
         configured_port: port,
         existing_servers: Vec::new(),
+        missing_secrets,
     })
 }
 
@@ -1058,7 +2327,7 @@ pub async fn disable_integration(
     id: String,
 ) -> Result<AiToolInfo, AppError> {
     let home = home_dir()?;
-    let tool = find_tool_def(&home, &id)?;
+    let tool = find_tool_def(&home, &enabled_workspaces(&state), &id)?;
     let port = proxy_state.port().await;
 
     // Remove from enabled list
@@ -1066,6 +2335,10 @@ pub async fn disable_integration(
         let mut s = state.lock().unwrap();
         s.enabled_integrations.retain(|i| i != &id);
         save_enabled_integrations(&app, &s.enabled_integrations);
+        if let Some(folder) = vscode_workspace_folder(&tool) {
+            s.vscode_workspaces.retain(|f| f != &folder);
+            save_vscode_workspaces(&app, &s.vscode_workspaces);
+        }
     }
 
     if !tool.config_path.exists() && matches!(tool.config_strategy, ConfigStrategy::ManagedFile) {
@@ -1073,10 +2346,12 @@ pub async fn disable_integration(
             id: tool.id,
             name: tool.name,
             installed: true,
+            detection: detect(&tool.detection, &home).1,
             enabled: false,
             config_path: tool.config_path.display().to_string(),
             configured_port: 0,
             existing_servers: Vec::new(),
+            missing_secrets: Vec::new(),
         });
     }
 
@@ -1091,10 +2366,12 @@ pub async fn disable_integration(
         id: tool.id,
         name: tool.name,
         installed: true,
+        detection: detect(&tool.detection, &home).1,
         enabled: false,
         config_path: tool.config_path.display().to_string(),
         configured_port: 0,
         existing_servers,
+        missing_secrets: Vec::new(),
     })
 }
 
@@ -1118,6 +2395,10 @@ fn write_managed_config(
             ConfigFormat::OpenCode => write_opencode_config(app, path, port, tool_id),
             ConfigFormat::Zed => write_zed_config(app, path, port, tool_id),
             ConfigFormat::CodexToml => write_codex_config(app, path, port, tool_id),
+            ConfigFormat::VsCode => write_vscode_config(app, path, port, tool_id),
+            ConfigFormat::Gemini => write_gemini_config(app, path, port, tool_id),
+            ConfigFormat::GooseYaml => write_goose_config(app, path, port, tool_id),
+            ConfigFormat::ContinueYaml => write_continue_config(app, path, port, tool_id),
         },
     }
 }
@@ -1269,6 +2550,110 @@ fn write_codex_config(
     Ok(())
 }
 
+fn write_vscode_config(
+    app: &AppHandle,
+    path: &Path,
+    port: u16,
+    tool_id: &str,
+) -> Result<(), AppError> {
+    let entries = connected_proxy_urls(app, port, tool_id);
+
+    let mut servers = serde_json::Map::new();
+    for (name, url) in entries {
+        servers.insert(name, serde_json::json!({ "type": "http", "url": url }));
+    }
+
+    // Other keys, including "inputs", are kept as they are
+    let mut config = if path.exists() {
+        let content = std::fs::read_to_string(path)?;
+        let stripped = strip_json_comments(&content);
+        serde_json::from_str::<serde_json::Value>(&stripped).unwrap_or(serde_json::json!({}))
+    } else {
+        serde_json::json!({})
+    };
+
+    config["servers"] = serde_json::Value::Object(servers);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string_pretty(&config)?;
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn write_gemini_config(
+    app: &AppHandle,
+    path: &Path,
+    port: u16,
+    tool_id: &str,
+) -> Result<(), AppError> {
+    write_gemini_proxy_entries(path, connected_proxy_urls(app, port, tool_id))
+}
+
+fn write_gemini_proxy_entries(path: &Path, entries: Vec<(String, String)>) -> Result<(), AppError> {
+    // The proxy speaks streamable HTTP, which Gemini reads from `httpUrl`
+    let mut mcp_servers = serde_json::Map::new();
+    for (name, url) in entries {
+        mcp_servers.insert(name, serde_json::json!({ "httpUrl": url }));
+    }
+
+    let mut config = if path.exists() {
+        let content = std::fs::read_to_string(path)?;
+        let stripped = strip_json_comments(&content);
+        serde_json::from_str::<serde_json::Value>(&stripped).unwrap_or(serde_json::json!({}))
+    } else {
+        serde_json::json!({})
+    };
+
+    config["mcpServers"] = serde_json::Value::Object(mcp_servers);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string_pretty(&config)?;
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn write_goose_config(
+    app: &AppHandle,
+    path: &Path,
+    port: u16,
+    tool_id: &str,
+) -> Result<(), AppError> {
+    let entries = connected_proxy_urls(app, port, tool_id);
+    let (content, config) = read_yaml(path)?;
+
+    let mut extensions = goose_other_extensions(&config);
+    for (name, url) in entries {
+        extensions.insert(name.as_str().into(), goose_proxy_extension(&name, &url));
+    }
+
+    write_yaml_section(path, &content, "extensions", extensions.into())
+}
+
+fn write_continue_config(
+    app: &AppHandle,
+    path: &Path,
+    port: u16,
+    tool_id: &str,
+) -> Result<(), AppError> {
+    let entries = connected_proxy_urls(app, port, tool_id);
+    let (content, config) = read_yaml(path)?;
+
+    let mut servers = continue_hub_blocks(&config);
+    for (name, url) in entries {
+        servers.push(continue_proxy_server(&name, &url));
+    }
+
+    write_yaml_section(path, &content, "mcpServers", servers.into())
+}
+
 // ---------------------------------------------------------------------------
 // Format-aware config removers — remove proxy entries on disable
 // ---------------------------------------------------------------------------
@@ -1289,6 +2674,10 @@ fn remove_managed_entries(
             ConfigFormat::OpenCode => remove_opencode_entries(path),
             ConfigFormat::Zed => remove_zed_entries(path),
             ConfigFormat::CodexToml => remove_codex_entries(path),
+            ConfigFormat::VsCode => remove_vscode_entries(path),
+            ConfigFormat::Gemini => remove_gemini_entries(path),
+            ConfigFormat::GooseYaml => remove_goose_entries(path),
+            ConfigFormat::ContinueYaml => remove_continue_entries(path),
         },
     }
 }
@@ -1398,19 +2787,110 @@ fn remove_codex_entries(path: &Path) -> Result<(), AppError> {
                 .map(|(k, _)| k.clone())
                 .collect();
 
-            for key in proxy_keys {
-                servers.remove(&key);
-            }
+            for key in proxy_keys {
+                servers.remove(&key);
+            }
+        }
+    }
+
+    let content = toml::to_string_pretty(&config)
+        .map_err(|e| AppError::Protocol(format!("Failed to serialize TOML: {e}")))?;
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn remove_vscode_entries(path: &Path) -> Result<(), AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let stripped = strip_json_comments(&content);
+    let mut config: serde_json::Value = serde_json::from_str(&stripped)?;
+
+    if let Some(servers) = config.get_mut("servers").and_then(|v| v.as_object_mut()) {
+        let proxy_keys: Vec<String> = servers
+            .iter()
+            .filter(|(_, v)| {
+                v.get("url")
+                    .and_then(|u| u.as_str())
+                    .map(is_proxy_url)
+                    .unwrap_or(false)
+            })
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        for key in proxy_keys {
+            servers.remove(&key);
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&config)?;
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
+
+fn remove_gemini_entries(path: &Path) -> Result<(), AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let stripped = strip_json_comments(&content);
+    let mut config: serde_json::Value = serde_json::from_str(&stripped)?;
+
+    if let Some(servers) = config.get_mut("mcpServers").and_then(|v| v.as_object_mut()) {
+        let proxy_keys: Vec<String> = servers
+            .iter()
+            .filter(|(_, v)| gemini_url(v).map(is_proxy_url).unwrap_or(false))
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        for key in proxy_keys {
+            servers.remove(&key);
         }
     }
 
-    let content = toml::to_string_pretty(&config)
-        .map_err(|e| AppError::Protocol(format!("Failed to serialize TOML: {e}")))?;
+    let content = serde_json::to_string_pretty(&config)?;
     std::fs::write(path, content)?;
 
     Ok(())
 }
 
+fn remove_goose_entries(path: &Path) -> Result<(), AppError> {
+    let (content, mut config) = read_yaml(path)?;
+
+    let Some(extensions) = config
+        .get_mut("extensions")
+        .and_then(|v| v.as_mapping_mut())
+    else {
+        return Ok(());
+    };
+    extensions.retain(|_, v| {
+        !v.get("uri")
+            .and_then(|u| u.as_str())
+            .map(is_proxy_url)
+            .unwrap_or(false)
+    });
+    let extensions = extensions.clone();
+
+    write_yaml_section(path, &content, "extensions", extensions.into())
+}
+
+fn remove_continue_entries(path: &Path) -> Result<(), AppError> {
+    let (content, mut config) = read_yaml(path)?;
+
+    let Some(servers) = config
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_sequence_mut())
+    else {
+        return Ok(());
+    };
+    servers.retain(|v| {
+        !v.get("url")
+            .and_then(|u| u.as_str())
+            .map(is_proxy_url)
+            .unwrap_or(false)
+    });
+    let servers = servers.clone();
+
+    write_yaml_section(path, &content, "mcpServers", servers.into())
+}
+
 // ---------------------------------------------------------------------------
 // Format-aware native config writers — write original server configs on exit
 // ---------------------------------------------------------------------------
@@ -1418,16 +2898,81 @@ fn remove_codex_entries(path: &Path) -> Result<(), AppError> {
 /// Write original (non-proxy) server configs to a tool's config file.
 /// This is the inverse of `write_managed_config`: it replaces proxy entries
 /// with the actual server configurations so they work without Agent Hub.
+/// `secret` looks up the values of `${secret:NAME}` references.
 fn write_native_config(
     servers: &[ServerConfig],
     path: &Path,
     format: &ConfigFormat,
+    secret: impl Fn(&str) -> Option<String>,
 ) -> Result<(), AppError> {
+    let mut servers = native_servers(servers);
+    // VS Code prompts for them as inputs; other tools need the values themselves
+    if !matches!(format, ConfigFormat::VsCode) {
+        resolve_secrets(&mut servers, secret);
+    }
+    let servers = servers.as_slice();
     match format {
         ConfigFormat::McpServers => write_native_mcp_servers(servers, path),
         ConfigFormat::OpenCode => write_native_opencode(servers, path),
         ConfigFormat::Zed => write_native_zed(servers, path),
         ConfigFormat::CodexToml => write_native_codex(servers, path),
+        ConfigFormat::VsCode => write_native_vscode(servers, path),
+        ConfigFormat::Gemini => write_native_gemini(servers, path),
+        ConfigFormat::GooseYaml => write_native_goose(servers, path),
+        ConfigFormat::ContinueYaml => write_native_continue(servers, path),
+    }
+}
+
+/// Rewrite container servers as plain stdio `docker run -i` commands so they work
+/// without Agent Hub. HTTP-mode containers and replay servers are dropped —
+/// nothing would start them.
+fn native_servers(servers: &[ServerConfig]) -> Vec<ServerConfig> {
+    servers
+        .iter()
+        .filter_map(|srv| {
+            match srv.transport {
+                ServerTransport::Replay => return None,
+                ServerTransport::Container => {}
+                _ => return Some(srv.clone()),
+            }
+            let (command, args) = crate::mcp::container::native_command(
+                srv.container.as_ref()?,
+                srv.args.as_deref().unwrap_or_default(),
+                &srv.env.clone().unwrap_or_default(),
+            )?;
+            let mut native = srv.clone();
+            native.transport = ServerTransport::Stdio;
+            native.command = Some(command);
+            native.args = Some(args);
+            native.container = None;
+            Some(native)
+        })
+        .collect()
+}
+
+/// Replace `${secret:NAME}` references in args, env, URL and headers with the
+/// secrets' values. References to missing secrets are left as written.
+fn resolve_secrets(servers: &mut [ServerConfig], secret: impl Fn(&str) -> Option<String>) {
+    let resolve = |text: &mut String| {
+        let resolved = rewrite_references(
+            text,
+            "secret",
+            |name| secret(name).unwrap_or_else(|| crate::secrets::reference(name)),
+            &mut Vec::new(),
+        );
+        *text = resolved;
+    };
+    for srv in servers {
+        srv.args.iter_mut().flatten().for_each(resolve);
+        srv.env
+            .iter_mut()
+            .flat_map(|m| m.values_mut())
+            .for_each(resolve);
+        srv.headers
+            .iter_mut()
+            .flat_map(|m| m.values_mut())
+            .for_each(resolve);
+        srv.url.iter_mut().for_each(resolve);
     }
 }
 
@@ -1463,6 +3008,8 @@ fn write_native_mcp_servers(servers: &[ServerConfig], path: &Path) -> Result<(),
                 }
                 serde_json::Value::Object(obj)
             }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
         };
         mcp_servers.insert(srv.name.clone(), entry);
     }
@@ -1511,6 +3058,8 @@ fn write_native_opencode(servers: &[ServerConfig], path: &Path) -> Result<(), Ap
                 }
                 serde_json::Value::Object(obj)
             }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
         };
         mcp.insert(srv.name.clone(), entry);
     }
@@ -1555,6 +3104,8 @@ fn write_native_zed(servers: &[ServerConfig], path: &Path) -> Result<(), AppErro
                 }
                 serde_json::Value::Object(obj)
             }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
         };
         context_servers.insert(srv.name.clone(), entry);
     }
@@ -1605,6 +3156,8 @@ fn write_native_codex(servers: &[ServerConfig], path: &Path) -> Result<(), AppEr
                     entry.insert("url".into(), toml::Value::String(url.clone()));
                 }
             }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
         }
         mcp_servers.insert(srv.name.clone(), toml::Value::Table(entry));
     }
@@ -1628,6 +3181,279 @@ fn write_native_codex(servers: &[ServerConfig], path: &Path) -> Result<(), AppEr
     Ok(())
 }
 
+fn write_native_vscode(servers: &[ServerConfig], path: &Path) -> Result<(), AppError> {
+    // Secret references go back to being `${input:NAME}` prompts
+    let mut secret_names = Vec::new();
+    let mut to_input = |text: &str| {
+        rewrite_references(
+            text,
+            "secret",
+            |n| format!("${{input:{n}}}"),
+            &mut secret_names,
+        )
+    };
+
+    let mut vscode_servers = serde_json::Map::new();
+    for srv in servers {
+        let mut obj = serde_json::Map::new();
+        match srv.transport {
+            ServerTransport::Stdio => {
+                obj.insert("type".into(), serde_json::Value::String("stdio".into()));
+                if let Some(cmd) = &srv.command {
+                    obj.insert("command".into(), serde_json::Value::String(cmd.clone()));
+                }
+                if let Some(args) = &srv.args {
+                    let args: Vec<String> = args.iter().map(|a| to_input(a)).collect();
+                    obj.insert("args".into(), serde_json::json!(args));
+                }
+                if let Some(env) = &srv.env {
+                    if !env.is_empty() {
+                        let env: HashMap<&String, String> =
+                            env.iter().map(|(k, v)| (k, to_input(v))).collect();
+                        obj.insert("env".into(), serde_json::json!(env));
+                    }
+                }
+                if let Some(cwd) = &srv.cwd {
+                    obj.insert("cwd".into(), serde_json::Value::String(cwd.clone()));
+                }
+                // VS Code takes a single env file
+                if let Some(file) = srv.env_file.as_ref().and_then(|f| f.first()) {
+                    obj.insert("envFile".into(), serde_json::Value::String(file.clone()));
+                }
+            }
+            ServerTransport::Http => {
+                let kind = match srv.http_mode {
+                    Some(HttpMode::LegacySse) => "sse",
+                    _ => "http",
+                };
+                obj.insert("type".into(), serde_json::Value::String(kind.into()));
+                if let Some(url) = &srv.url {
+                    obj.insert("url".into(), serde_json::Value::String(url.clone()));
+                }
+                if let Some(headers) = &srv.headers {
+                    if !headers.is_empty() {
+                        let headers: HashMap<&String, String> =
+                            headers.iter().map(|(k, v)| (k, to_input(v))).collect();
+                        obj.insert("headers".into(), serde_json::json!(headers));
+                    }
+                }
+            }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
+        }
+        vscode_servers.insert(srv.name.clone(), serde_json::Value::Object(obj));
+    }
+
+    let mut config = if path.exists() {
+        let content = std::fs::read_to_string(path)?;
+        let stripped = strip_json_comments(&content);
+        serde_json::from_str::<serde_json::Value>(&stripped).unwrap_or(serde_json::json!({}))
+    } else {
+        serde_json::json!({})
+    };
+
+    config["servers"] = serde_json::Value::Object(vscode_servers);
+
+    // Every `${input:NAME}` needs a matching input, or VS Code can't resolve it
+    if !secret_names.is_empty() {
+        let mut inputs = config
+            .get("inputs")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for name in secret_names {
+            if !inputs
+                .iter()
+                .any(|i| i.get("id").and_then(|v| v.as_str()) == Some(&name))
+            {
+                inputs.push(serde_json::json!({
+                    "type": "promptString",
+                    "id": name,
+                    "description": name,
+                    "password": true
+                }));
+            }
+        }
+        config["inputs"] = serde_json::Value::Array(inputs);
+    }
+
+    let content = serde_json::to_string_pretty(&config)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Per-server Gemini CLI keys written from a ServerConfig. Others are kept.
+const GEMINI_SERVER_KEYS: &[&str] = &[
+    "command",
+    "args",
+    "env",
+    "cwd",
+    "url",
+    "httpUrl",
+    "headers",
+    "excludeTools",
+    "includeTools",
+];
+
+fn write_native_gemini(servers: &[ServerConfig], path: &Path) -> Result<(), AppError> {
+    let mut config = if path.exists() {
+        let content = std::fs::read_to_string(path)?;
+        let stripped = strip_json_comments(&content);
+        serde_json::from_str::<serde_json::Value>(&stripped).unwrap_or(serde_json::json!({}))
+    } else {
+        serde_json::json!({})
+    };
+
+    let mut mcp_servers = serde_json::Map::new();
+    for srv in servers {
+        let mut obj = serde_json::Map::new();
+        match srv.transport {
+            ServerTransport::Stdio => {
+                if let Some(cmd) = &srv.command {
+                    obj.insert("command".into(), serde_json::Value::String(cmd.clone()));
+                }
+                if let Some(args) = &srv.args {
+                    obj.insert("args".into(), serde_json::json!(args));
+                }
+                if let Some(env) = &srv.env {
+                    if !env.is_empty() {
+                        obj.insert("env".into(), serde_json::json!(env));
+                    }
+                }
+                if let Some(cwd) = &srv.cwd {
+                    obj.insert("cwd".into(), serde_json::Value::String(cwd.clone()));
+                }
+            }
+            ServerTransport::Http => {
+                let key = match srv.http_mode {
+                    Some(HttpMode::LegacySse) => "url",
+                    _ => "httpUrl",
+                };
+                if let Some(url) = &srv.url {
+                    obj.insert(key.into(), serde_json::Value::String(url.clone()));
+                }
+                if let Some(headers) = &srv.headers {
+                    if !headers.is_empty() {
+                        obj.insert("headers".into(), serde_json::json!(headers));
+                    }
+                }
+            }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
+        }
+        if let Some(disabled) = srv.disabled_tools.as_ref().filter(|d| !d.is_empty()) {
+            obj.insert("excludeTools".into(), serde_json::json!(disabled));
+        }
+        if let Some(enabled) = &srv.enabled_tools {
+            obj.insert("includeTools".into(), serde_json::json!(enabled));
+        }
+        // Restore settings Agent Hub doesn't model, like `trust` and `timeout`,
+        // whether kept on import or still in the file
+        let existing = config["mcpServers"][&srv.name].as_object();
+        for (key, value) in srv.extra.iter().chain(existing).flatten() {
+            if !GEMINI_SERVER_KEYS.contains(&key.as_str()) {
+                obj.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        mcp_servers.insert(srv.name.clone(), serde_json::Value::Object(obj));
+    }
+
+    config["mcpServers"] = serde_json::Value::Object(mcp_servers);
+
+    let content = serde_json::to_string_pretty(&config)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn write_native_goose(servers: &[ServerConfig], path: &Path) -> Result<(), AppError> {
+    let (content, config) = read_yaml(path)?;
+
+    let mut extensions = goose_other_extensions(&config);
+    for srv in servers {
+        let mut ext = serde_yaml::Mapping::new();
+        ext.insert("name".into(), srv.name.as_str().into());
+        match srv.transport {
+            ServerTransport::Stdio => {
+                ext.insert("type".into(), "stdio".into());
+                if let Some(cmd) = &srv.command {
+                    ext.insert("cmd".into(), cmd.as_str().into());
+                }
+                ext.insert("args".into(), srv.args.clone().unwrap_or_default().into());
+                if let Some(env) = srv.env.as_ref().filter(|e| !e.is_empty()) {
+                    ext.insert("envs".into(), yaml_string_map(env));
+                }
+            }
+            ServerTransport::Http => {
+                let kind = match srv.http_mode {
+                    Some(HttpMode::LegacySse) => "sse",
+                    _ => "streamable_http",
+                };
+                ext.insert("type".into(), kind.into());
+                if let Some(url) = &srv.url {
+                    ext.insert("uri".into(), url.as_str().into());
+                }
+                if let Some(headers) = srv.headers.as_ref().filter(|h| !h.is_empty()) {
+                    ext.insert("headers".into(), yaml_string_map(headers));
+                }
+            }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
+        }
+        ext.insert("enabled".into(), srv.enabled.into());
+        ext.insert("timeout".into(), 300.into());
+        extensions.insert(srv.name.as_str().into(), ext.into());
+    }
+
+    write_yaml_section(path, &content, "extensions", extensions.into())
+}
+
+fn write_native_continue(servers: &[ServerConfig], path: &Path) -> Result<(), AppError> {
+    let (content, config) = read_yaml(path)?;
+
+    let mut entries = continue_hub_blocks(&config);
+    for srv in servers {
+        let mut entry = serde_yaml::Mapping::new();
+        entry.insert("name".into(), srv.name.as_str().into());
+        match srv.transport {
+            ServerTransport::Stdio => {
+                entry.insert("type".into(), "stdio".into());
+                if let Some(cmd) = &srv.command {
+                    entry.insert("command".into(), cmd.as_str().into());
+                }
+                if let Some(args) = &srv.args {
+                    entry.insert("args".into(), args.clone().into());
+                }
+                if let Some(env) = srv.env.as_ref().filter(|e| !e.is_empty()) {
+                    entry.insert("env".into(), yaml_string_map(env));
+                }
+                if let Some(cwd) = &srv.cwd {
+                    entry.insert("cwd".into(), cwd.as_str().into());
+                }
+            }
+            ServerTransport::Http => {
+                let kind = match srv.http_mode {
+                    Some(HttpMode::LegacySse) => "sse",
+                    _ => "streamable-http",
+                };
+                entry.insert("type".into(), kind.into());
+                if let Some(url) = &srv.url {
+                    entry.insert("url".into(), url.as_str().into());
+                }
+                if let Some(headers) = srv.headers.as_ref().filter(|h| !h.is_empty()) {
+                    let mut opts = serde_yaml::Mapping::new();
+                    opts.insert("headers".into(), yaml_string_map(headers));
+                    entry.insert("requestOptions".into(), opts.into());
+                }
+            }
+            // Rewritten to stdio (or dropped) by `native_servers`.
+            ServerTransport::Container | ServerTransport::Replay => continue,
+        }
+        entries.push(entry.into());
+    }
+
+    write_yaml_section(path, &content, "mcpServers", entries.into())
+}
+
 // ---------------------------------------------------------------------------
 // Config previews — pure functions that generate the MCP section as a string
 // ---------------------------------------------------------------------------
@@ -1695,6 +3521,51 @@ fn preview_codex_config(entries: &[(String, String)]) -> String {
     toml::to_string_pretty(&wrapper).unwrap_or_default()
 }
 
+/// Generate a preview of the VS Code JSON section.
+fn preview_vscode_config(entries: &[(String, String)]) -> String {
+    let mut servers = serde_json::Map::new();
+    for (name, url) in entries {
+        servers.insert(
+            name.clone(),
+            serde_json::json!({ "type": "http", "url": url }),
+        );
+    }
+    let wrapper = serde_json::json!({ "servers": servers });
+    serde_json::to_string_pretty(&wrapper).unwrap_or_default()
+}
+
+/// Generate a preview of the Gemini CLI JSON section.
+fn preview_gemini_config(entries: &[(String, String)]) -> String {
+    let mut mcp_servers = serde_json::Map::new();
+    for (name, url) in entries {
+        mcp_servers.insert(name.clone(), serde_json::json!({ "httpUrl": url }));
+    }
+    let wrapper = serde_json::json!({ "mcpServers": mcp_servers });
+    serde_json::to_string_pretty(&wrapper).unwrap_or_default()
+}
+
+/// Generate a preview of the Goose YAML section.
+fn preview_goose_config(entries: &[(String, String)]) -> String {
+    let mut extensions = serde_yaml::Mapping::new();
+    for (name, url) in entries {
+        extensions.insert(name.as_str().into(), goose_proxy_extension(name, url));
+    }
+    let mut wrapper = serde_yaml::Mapping::new();
+    wrapper.insert("extensions".into(), extensions.into());
+    serde_yaml::to_string(&wrapper).unwrap_or_default()
+}
+
+/// Generate a preview of the Continue YAML section.
+fn preview_continue_config(entries: &[(String, String)]) -> String {
+    let servers: Vec<serde_yaml::Value> = entries
+        .iter()
+        .map(|(name, url)| continue_proxy_server(name, url))
+        .collect();
+    let mut wrapper = serde_yaml::Mapping::new();
+    wrapper.insert("mcpServers".into(), servers.into());
+    serde_yaml::to_string(&wrapper).unwrap_or_default()
+}
+
 /// Generate the preview string for a given tool's format.
 fn preview_for_format(
     entries: &[(String, String)],
@@ -1705,6 +3576,10 @@ fn preview_for_format(
         ConfigFormat::OpenCode => preview_opencode_config(entries),
         ConfigFormat::Zed => preview_zed_config(entries),
         ConfigFormat::CodexToml => preview_codex_config(entries),
+        ConfigFormat::VsCode => preview_vscode_config(entries),
+        ConfigFormat::Gemini => preview_gemini_config(entries),
+        ConfigFormat::GooseYaml => preview_goose_config(entries),
+        ConfigFormat::ContinueYaml => preview_continue_config(entries),
     }
 }
 
@@ -1715,7 +3590,7 @@ pub async fn get_managed_config_previews(
     proxy_state: State<'_, ProxyState>,
 ) -> Result<Vec<ManagedConfigPreview>, AppError> {
     let home = home_dir()?;
-    let tools = get_tool_definitions(&home);
+    let tools = get_tool_definitions(&home, &enabled_workspaces(&state));
     let port = proxy_state.port().await;
 
     let enabled_ids: Vec<String> = {
@@ -1756,13 +3631,14 @@ pub async fn get_managed_config_previews(
 /// Called on app exit so configs work without Agent Hub running.
 pub fn restore_all_integration_configs(app: &AppHandle, port: u16) -> Result<(), AppError> {
     let home = home_dir()?;
-    let tools = get_tool_definitions(&home);
+    let tools = get_tool_definitions(&home, &enabled_workspaces(&app.state::<SharedState>()));
 
     let (enabled_ids, servers) = {
         let state = app.state::<SharedState>();
         let s = state.lock().unwrap();
         (s.enabled_integrations.clone(), s.servers.clone())
     };
+    let secrets = app.state::<SecretStore>();
 
     for tool in tools {
         if !enabled_ids.contains(&tool.id) {
@@ -1779,7 +3655,9 @@ pub fn restore_all_integration_configs(app: &AppHandle, port: u16) -> Result<(),
                 if !tool.config_path.exists() {
                     continue;
                 }
-                write_native_config(&servers, &tool.config_path, &tool.config_format)
+                write_native_config(&servers, &tool.config_path, &tool.config_format, |name| {
+                    secrets.get(name)
+                })
             }
         };
 
@@ -1796,7 +3674,7 @@ pub fn restore_all_integration_configs(app: &AppHandle, port: u16) -> Result<(),
 /// Called on proxy startup, server connect/disconnect, and enable/disable.
 pub fn update_all_integration_configs(app: &AppHandle, port: u16) -> Result<(), AppError> {
     let home = home_dir()?;
-    let tools = get_tool_definitions(&home);
+    let tools = get_tool_definitions(&home, &enabled_workspaces(&app.state::<SharedState>()));
 
     let enabled_ids: Vec<String> = {
         let state = app.state::<SharedState>();
@@ -1820,3 +3698,492 @@ pub fn update_all_integration_configs(app: &AppHandle, port: u16) -> Result<(),
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detection_reports_what_was_found_or_checked() {
+        let home = std::env::temp_dir().join(format!("agent-hub-home-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(home.join("Applications")).unwrap();
+        std::fs::write(home.join("Applications/cursor-1.2.3-x86_64.AppImage"), "").unwrap();
+
+        let probes = [Probe::Path(home.join(".cursor")), Probe::AppImage("Cursor")];
+        let (installed, reason) = detect(&probes, &home);
+        assert!(installed);
+        assert_eq!(
+            reason,
+            "Found AppImage ~/Applications/cursor-1.2.3-x86_64.AppImage"
+        );
+
+        let probes = [
+            Probe::Path(home.join(".windsurf")),
+            Probe::AppImage("Windsurf"),
+            Probe::DesktopEntry("agent-hub-test-windsurf"),
+        ];
+        let (installed, reason) = detect(&probes, &home);
+        assert!(!installed);
+        assert_eq!(
+            reason,
+            "Not found: checked ~/.windsurf, Windsurf*.AppImage, agent-hub-test-windsurf.desktop"
+        );
+
+        let _ = std::fs::remove_dir_all(home);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn claude_desktop_uses_the_linux_config_dir() {
+        let home = PathBuf::from("/home/someone");
+        let tool = get_tool_definitions(&home, &[])
+            .into_iter()
+            .find(|t| t.id == "claude-desktop")
+            .unwrap();
+        assert!(tool
+            .config_path
+            .ends_with("Claude/claude_desktop_config.json"));
+        assert!(!tool.config_path.to_string_lossy().contains("Library"));
+        assert!(tool
+            .detection
+            .iter()
+            .any(|p| matches!(p, Probe::DesktopEntry(_))));
+    }
+
+    #[test]
+    fn vscode_workspaces_come_from_folders_it_opened() {
+        let home = std::env::temp_dir().join(format!("agent-hub-home-{}", Uuid::new_v4()));
+        let user_dir = home.join(".config/Code/User");
+        let project = home.join("src/my project");
+        std::fs::create_dir_all(user_dir.join("globalStorage")).unwrap();
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(home.join("multi.code-workspace"), "{}").unwrap();
+        let uri = |path: &Path| url::Url::from_file_path(path).unwrap().to_string();
+        let storage = serde_json::json!({
+            "windowsState": {
+                "lastActiveWindow": { "folder": uri(&project) },
+                "openedWindows": [{ "folder": uri(&home) }],
+            },
+            "backupWorkspaces": {
+                "folders": [{ "folderUri": uri(&home.join("deleted")) }],
+            },
+            "profileAssociations": {
+                "workspaces": {
+                    uri(&project): "__default__profile__",
+                    uri(&home.join("multi.code-workspace")): "__default__profile__",
+                    "vscode-remote://ssh-remote+box/srv/app": "__default__profile__",
+                },
+            },
+        });
+        std::fs::write(
+            user_dir.join("globalStorage/storage.json"),
+            storage.to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(recent_vscode_folders(&[user_dir], &home), [project]);
+
+        let _ = std::fs::remove_dir_all(home);
+    }
+
+    #[test]
+    fn enabled_vscode_workspaces_outlive_the_recent_list() {
+        let home = std::env::temp_dir().join(format!("agent-hub-home-{}", Uuid::new_v4()));
+        let forgotten = home.join("src/old-project");
+
+        let workspace = |workspaces: &[PathBuf]| {
+            get_tool_definitions(&home, workspaces)
+                .into_iter()
+                .find(|t| t.id == vscode_workspace_id(&forgotten))
+        };
+        assert!(workspace(&[]).is_none());
+        let tool = workspace(std::slice::from_ref(&forgotten)).unwrap();
+        assert_eq!(tool.name, "VS Code Workspace (old-project)");
+        assert_eq!(tool.config_path, forgotten.join(".vscode/mcp.json"));
+        assert_eq!(vscode_workspace_folder(&tool), Some(forgotten));
+        let user_config = find_tool_def(&home, &[], "vscode").unwrap();
+        assert!(vscode_workspace_folder(&user_config).is_none());
+    }
+
+    #[test]
+    fn migrated_secrets_are_resolved_in_native_configs() {
+        let dir = std::env::temp_dir().join(format!("agent-hub-native-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mcp.json");
+        std::fs::write(
+            &path,
+            r#"{
+  "mcpServers": {
+    "github": {
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-github"],
+      "env": { "GITHUB_TOKEN": "ghp_plaintext" }
+    },
+    "remote": {
+      "url": "https://example.com/mcp",
+      "headers": { "Authorization": "Bearer abc123" }
+    }
+  }
+}"#,
+        )
+        .unwrap();
+        let store = SecretStore::encrypted_file(&dir.join("secrets"));
+        let mut servers = import_mcp_servers(&path).unwrap();
+        assert_eq!(
+            crate::persistence::migrate_server_credentials(&store, &mut servers),
+            2
+        );
+
+        for format in [
+            ConfigFormat::McpServers,
+            ConfigFormat::OpenCode,
+            ConfigFormat::Zed,
+            ConfigFormat::CodexToml,
+            ConfigFormat::Gemini,
+            ConfigFormat::GooseYaml,
+            ConfigFormat::ContinueYaml,
+        ] {
+            let _ = std::fs::remove_file(&path);
+            write_native_config(&servers, &path, &format, |name| store.get(name)).unwrap();
+            let written = std::fs::read_to_string(&path).unwrap();
+            assert!(!written.contains("${secret:"), "{format:?}: {written}");
+            assert!(written.contains("ghp_plaintext"), "{format:?}: {written}");
+        }
+
+        // VS Code keeps prompting for them instead
+        write_native_config(&servers, &path, &ConfigFormat::VsCode, |name| {
+            store.get(name)
+        })
+        .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("ghp_plaintext"));
+        assert!(written.contains("${input:"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn vscode_inputs_round_trip_through_secrets() {
+        let path = std::env::temp_dir().join(format!("agent-hub-mcp-{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{
+  // Prompted for on first start
+  "inputs": [
+    { "type": "promptString", "id": "github-token", "description": "GitHub token", "password": true }
+  ],
+  "servers": {
+    "github": {
+      "type": "stdio",
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-github"],
+      "env": { "GITHUB_TOKEN": "${input:github-token}" }
+    },
+    "legacy": { "type": "sse", "url": "https://example.com/sse" },
+    "agent-hub-discovery-mode": {
+      "type": "http",
+      "url": "http://localhost:9000/mcp/discovery?client=vscode"
+    }
+  }
+}"#,
+        )
+        .unwrap();
+
+        let (enabled, port, existing) = parse_vscode(&path);
+        assert!(enabled);
+        assert_eq!(port, 9000);
+        assert_eq!(existing.len(), 2);
+
+        let (mut imported, missing) =
+            import_vscode(&path, None, Path::new("/home/someone")).unwrap();
+        let github = imported.iter().find(|s| s.name == "github").unwrap();
+        assert_eq!(
+            github.env.as_ref().unwrap()["GITHUB_TOKEN"],
+            "${secret:github-token}"
+        );
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].name, "github-token");
+        assert_eq!(missing[0].description.as_deref(), Some("GitHub token"));
+        let legacy = imported.iter_mut().find(|s| s.name == "legacy").unwrap();
+        assert!(matches!(legacy.http_mode, Some(HttpMode::LegacySse)));
+        legacy.headers = Some(HashMap::from([(
+            "Authorization".to_string(),
+            "Bearer ${secret:LEGACY_TOKEN}".to_string(),
+        )]));
+
+        write_native_vscode(&imported, &path).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let servers = &written["servers"];
+        assert_eq!(
+            servers["github"]["env"]["GITHUB_TOKEN"],
+            "${input:github-token}"
+        );
+        assert_eq!(servers["legacy"]["type"], "sse");
+        assert_eq!(
+            servers["legacy"]["headers"]["Authorization"],
+            "Bearer ${input:LEGACY_TOKEN}"
+        );
+
+        // The existing input is kept as written, and the new reference gets one
+        let inputs = written["inputs"].as_array().unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0]["description"], "GitHub token");
+        assert_eq!(inputs[1]["id"], "LEGACY_TOKEN");
+        assert_eq!(inputs[1]["password"], true);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn vscode_workspace_variables_and_inputs_are_resolved_on_import() {
+        let home = std::env::temp_dir().join(format!("agent-hub-home-{}", Uuid::new_v4()));
+        let project = home.join("src/app");
+        std::fs::create_dir_all(project.join(".vscode")).unwrap();
+        let path = project.join(".vscode/mcp.json");
+        std::fs::write(
+            &path,
+            r#"{
+  "servers": {
+    "files": {
+      "command": "${workspaceFolder}/node_modules/.bin/mcp-files",
+      "args": ["--cache", "${userHome}/.cache/files"],
+      "cwd": "${workspaceFolder}",
+      "envFile": "${workspaceFolder}/.env"
+    },
+    "tenant": { "type": "http", "url": "https://${input:tenant}.example.com/mcp" }
+  }
+}"#,
+        )
+        .unwrap();
+
+        let id = vscode_workspace_id(&project);
+        let tool = find_tool_def(&home, std::slice::from_ref(&project), &id).unwrap();
+        let (imported, missing) = read_importable_servers(&tool, &home).unwrap();
+        let by_name = |name: &str| imported.iter().find(|s| s.name == name).unwrap();
+        let folder = project.display().to_string();
+        let files = by_name("files");
+        assert_eq!(
+            files.command,
+            Some(format!("{folder}/node_modules/.bin/mcp-files"))
+        );
+        assert_eq!(
+            files.args,
+            Some(vec![
+                "--cache".to_string(),
+                format!("{}/.cache/files", home.display())
+            ])
+        );
+        assert_eq!(files.cwd.as_deref(), Some(folder.as_str()));
+        assert_eq!(files.env_file, Some(vec![format!("{folder}/.env")]));
+        assert_eq!(
+            by_name("tenant").url.as_deref(),
+            Some("https://${secret:tenant}.example.com/mcp")
+        );
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].name, "tenant");
+
+        // Without a workspace there's nothing to expand `${workspaceFolder}` to
+        let (user, _) = import_vscode(&path, None, &home).unwrap();
+        let files = user.iter().find(|s| s.name == "files").unwrap();
+        assert_eq!(files.cwd.as_deref(), Some("${workspaceFolder}"));
+        let _ = std::fs::remove_dir_all(home);
+    }
+
+    #[test]
+    fn gemini_servers_round_trip_with_tool_filters_and_trust() {
+        let path = std::env::temp_dir().join(format!("agent-hub-gemini-{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{
+  "theme": "GitHub",
+  "mcpServers": {
+    "github": {
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-github"],
+      "excludeTools": ["delete_repo"],
+      "trust": true
+    },
+    "remote": {
+      "httpUrl": "https://example.com/mcp",
+      "includeTools": ["search"],
+      "timeout": 30000
+    },
+    "legacy": { "url": "https://example.com/sse" },
+    "agent-hub-discovery-mode": {
+      "httpUrl": "http://localhost:9000/mcp/discovery?client=gemini-cli"
+    }
+  }
+}"#,
+        )
+        .unwrap();
+
+        let (enabled, port, existing) = parse_gemini(&path);
+        assert!(enabled);
+        assert_eq!(port, 9000);
+        assert_eq!(existing.len(), 3);
+
+        let imported = import_gemini(&path).unwrap();
+        let by_name = |name: &str| imported.iter().find(|s| s.name == name).unwrap();
+        let github = by_name("github");
+        assert_eq!(github.disabled_tools, Some(vec!["delete_repo".to_string()]));
+        assert!(!github.tool_enabled("delete_repo"));
+        assert!(github.tool_enabled("create_issue"));
+        let remote = by_name("remote");
+        assert!(remote.http_mode.is_none());
+        assert_eq!(remote.enabled_tools, Some(vec!["search".to_string()]));
+        assert!(remote.tool_enabled("search"));
+        assert!(!remote.tool_enabled("fetch"));
+        assert!(matches!(
+            by_name("legacy").http_mode,
+            Some(HttpMode::LegacySse)
+        ));
+
+        // Enabling the integration replaces every entry with a proxy URL, and
+        // only connected servers get one
+        write_gemini_proxy_entries(
+            &path,
+            vec![(
+                "github".into(),
+                "http://localhost:9000/mcp/servers/github".into(),
+            )],
+        )
+        .unwrap();
+        write_native_gemini(&imported, &path).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let servers = &written["mcpServers"];
+        assert_eq!(written["theme"], "GitHub");
+        assert_eq!(
+            servers["github"]["excludeTools"],
+            serde_json::json!(["delete_repo"])
+        );
+        assert_eq!(servers["github"]["trust"], true);
+        assert_eq!(servers["remote"]["httpUrl"], "https://example.com/mcp");
+        assert_eq!(
+            servers["remote"]["includeTools"],
+            serde_json::json!(["search"])
+        );
+        assert_eq!(servers["remote"]["timeout"], 30000);
+        assert_eq!(servers["legacy"]["url"], "https://example.com/sse");
+        assert!(servers.get("agent-hub-discovery-mode").is_none());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn yaml_section_replacement_keeps_surrounding_content() {
+        let content = "# Goose settings\nGOOSE_PROVIDER: openai\nextensions:\n  old:\n    type: stdio\n# model\nGOOSE_MODEL: gpt-4o\n";
+        let mut extensions = serde_yaml::Mapping::new();
+        extensions.insert("new".into(), goose_proxy_extension("new", "http://x"));
+
+        let out = replace_yaml_section(content, "extensions", extensions.into()).unwrap();
+        assert!(out.starts_with("# Goose settings\nGOOSE_PROVIDER: openai\nextensions:\n  new:\n"));
+        assert!(out.ends_with("# model\nGOOSE_MODEL: gpt-4o\n"));
+        assert!(!out.contains("old"));
+
+        // A missing key is appended; a sequence at column 0 is part of its key
+        let out = replace_yaml_section("name: cfg", "mcpServers", Vec::<String>::new().into());
+        assert_eq!(out.unwrap(), "name: cfg\nmcpServers: []\n");
+        let content = "mcpServers:\n- name: a\n  command: a\nmodels: []\n";
+        let out = replace_yaml_section(content, "mcpServers", vec!["b"].into()).unwrap();
+        assert_eq!(out, "mcpServers:\n- b\nmodels: []\n");
+    }
+
+    #[test]
+    fn goose_and_continue_round_trip() {
+        let dir = std::env::temp_dir().join(format!("agent-hub-yaml-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let goose = dir.join("config.yaml");
+        std::fs::write(
+            &goose,
+            r#"# provider settings
+GOOSE_PROVIDER: anthropic
+extensions:
+  developer:
+    type: builtin
+    name: developer
+    enabled: true
+  github:
+    type: stdio
+    name: github
+    cmd: npx
+    args: [-y, "@modelcontextprotocol/server-github"]
+    envs:
+      PORT: 8080
+    enabled: false
+  hub:
+    type: streamable_http
+    name: hub
+    uri: http://127.0.0.1:24680/mcp/abc
+"#,
+        )
+        .unwrap();
+
+        let imported = import_goose(&goose).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].name, "github");
+        assert!(!imported[0].enabled);
+        assert_eq!(imported[0].env.as_ref().unwrap()["PORT"], "8080");
+        let (enabled, port, existing) = parse_goose(&goose);
+        assert!(enabled);
+        assert_eq!(port, 24680);
+        assert_eq!(existing.len(), 1);
+
+        write_native_goose(&imported, &goose).unwrap();
+        let content = std::fs::read_to_string(&goose).unwrap();
+        assert!(content.starts_with("# provider settings\nGOOSE_PROVIDER: anthropic\n"));
+        let (_, config) = read_yaml(&goose).unwrap();
+        let extensions = config["extensions"].as_mapping().unwrap();
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(config["extensions"]["developer"]["type"], "builtin");
+        assert_eq!(config["extensions"]["github"]["cmd"], "npx");
+
+        let cont = dir.join("continue.yaml");
+        std::fs::write(
+            &cont,
+            r#"name: My Config
+version: 0.0.1
+mcpServers:
+  - uses: anthropic/memory-mcp
+  - name: search
+    type: sse
+    url: https://example.com/sse
+    requestOptions:
+      headers:
+        Authorization: Bearer x
+  - name: hub
+    type: streamable-http
+    url: http://localhost:24680/mcp/abc
+models: []
+"#,
+        )
+        .unwrap();
+
+        let imported = import_continue(&cont).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].http_mode, Some(HttpMode::LegacySse));
+        assert_eq!(
+            imported[0].headers.as_ref().unwrap()["Authorization"],
+            "Bearer x"
+        );
+
+        remove_continue_entries(&cont).unwrap();
+        let (_, config) = read_yaml(&cont).unwrap();
+        assert_eq!(config["mcpServers"].as_sequence().unwrap().len(), 2);
+
+        write_native_continue(&imported, &cont).unwrap();
+        let content = std::fs::read_to_string(&cont).unwrap();
+        assert!(content.starts_with("name: My Config\nversion: 0.0.1\nmcpServers:\n"));
+        assert!(content.ends_with("models: []\n"));
+        let (_, config) = read_yaml(&cont).unwrap();
+        let servers = config["mcpServers"].as_sequence().unwrap();
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0]["uses"], "anthropic/memory-mcp");
+        assert_eq!(servers[1]["type"], "sse");
+        assert_eq!(
+            servers[1]["requestOptions"]["headers"]["Authorization"],
+            "Bearer x"
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
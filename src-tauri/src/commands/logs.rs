@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use tauri::State;
+use tracing::info;
+
+use crate::error::AppError;
+use crate::server_logs::{LogEntry, LogQuery, ServerLogs};
+
+/// Default number of entries returned by `tail_server_log`.
+const DEFAULT_TAIL: usize = 200;
+
+#[tauri::command]
+pub async fn tail_server_log(
+    logs: State<'_, ServerLogs>,
+    id: String,
+    lines: Option<usize>,
+) -> Result<Vec<LogEntry>, AppError> {
+    Ok(logs.tail(&id, lines.unwrap_or(DEFAULT_TAIL)))
+}
+
+#[tauri::command]
+pub async fn search_server_logs(
+    logs: State<'_, ServerLogs>,
+    id: String,
+    query: LogQuery,
+) -> Result<Vec<LogEntry>, AppError> {
+    logs.search(&id, &query)
+}
+
+/// Write all of a server's retained logs to `path` as JSON lines.
+#[tauri::command]
+pub async fn export_server_logs(
+    logs: State<'_, ServerLogs>,
+    id: String,
+    path: String,
+) -> Result<usize, AppError> {
+    let count = logs.export(&id, &PathBuf::from(&path))?;
+    info!("Exported {count} log entries for {id} to {path}");
+    Ok(count)
+}
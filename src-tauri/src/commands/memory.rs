@@ -711,6 +711,9 @@ pub async fn save_embedding_config_cmd(
         "Saved embedding config: provider={:?}, model={}, dimensions={}",
         input.config.provider, input.config.model, input.config.dimensions
     );
+
+    // Tool discovery vectors from the old model can't be compared to new queries
+    crate::mcp::embeddings::index_all(&app);
     Ok(())
 }
 
@@ -906,6 +909,16 @@ pub async fn enable_memory(
         managed: None,
         managed_by: Some("memory".into()),
         registry_name: None,
+        sandbox: None,
+        http_mode: None,
+        cwd: None,
+        env_file: None,
+        container: None,
+        oauth: None,
+        replay: None,
+        disabled_tools: None,
+        enabled_tools: None,
+        extra: None,
     };
 
     {
@@ -1104,7 +1117,7 @@ pub async fn restart_memory(
     };
 
     use crate::mcp::client::McpClient;
-    match McpClient::connect_http(&url, HashMap::new(), None).await {
+    match McpClient::connect_http(&app, &server_id, &url, HashMap::new(), None, None).await {
         Ok(client) => {
             let tools: Vec<McpTool> = client
                 .tools
@@ -1,11 +1,15 @@
 use tauri::{AppHandle, Emitter, State};
 use tauri_plugin_opener::OpenerExt;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::commands::connections::resolve_http_endpoint;
 use crate::error::AppError;
 use crate::mcp::client::SharedConnections;
 use crate::mcp::{oauth, oauth_callback};
-use crate::state::{OAuthState, ServerTransport, SharedOAuthStore, SharedState};
+use crate::state::{
+    AuthMode, AuthServerMetadata, DeviceCodePrompt, OAuthConfig, OAuthState, OAuthTokens,
+    ServerTransport, SharedOAuthStore, SharedState, TokenStatus,
+};
 
 #[tauri::command]
 pub async fn start_oauth_flow(
@@ -15,8 +19,8 @@ pub async fn start_oauth_flow(
     connections: State<'_, SharedConnections>,
     id: String,
 ) -> Result<(), AppError> {
-    // 1. Read server URL from AppState
-    let server_url = {
+    // 1. Read server URL and OAuth settings from AppState
+    let mut oauth_config = {
         let s = state.lock().unwrap();
         let server = s
             .servers
@@ -28,11 +32,21 @@ pub async fn start_oauth_flow(
                 "OAuth is only supported for HTTP servers".into(),
             ));
         }
-        server
-            .url
-            .clone()
-            .ok_or_else(|| AppError::OAuth("No URL configured for server".into()))?
+        if server.url.is_none() {
+            return Err(AppError::OAuth("No URL configured for server".into()));
+        }
+        server.oauth.clone().unwrap_or_default()
     };
+    let (server_url, _) = resolve_http_endpoint(&app, &id)?;
+    if oauth_config.mode != AuthMode::Interactive {
+        return Err(AppError::OAuth(
+            "This server uses configured credentials; reconnect instead of signing in".into(),
+        ));
+    }
+    // The MCP auth spec requires a resource indicator naming the server
+    if oauth_config.resource.is_none() {
+        oauth_config.resource = Some(oauth::canonical_resource_uri(&server_url));
+    }
 
     let _ = app.emit(
         "oauth-status-changed",
@@ -42,81 +56,15 @@ pub async fn start_oauth_flow(
     // 2-3. Discover auth server metadata (tries RFC 9728 first, falls back to direct)
     let metadata = oauth::discover_metadata(&server_url).await?;
 
-    // 4. Start callback server to get the redirect URI
-    let (port, callback_rx) = oauth_callback::start_callback_server().await?;
-    let redirect_uri = format!("http://127.0.0.1:{port}/oauth/callback");
-
-    // 5. Dynamic client registration if available and no stored client_id
-    let (client_id, client_secret) = {
-        let store = oauth_store.lock().await;
-        let existing = store.get(&id);
-        match existing {
-            Some(os) if os.client_id.is_some() => {
-                (os.client_id.clone().unwrap(), os.client_secret.clone())
-            }
-            _ => {
-                drop(store);
-                if let Some(ref reg_endpoint) = metadata.registration_endpoint {
-                    let (cid, csec) = oauth::dynamic_register(reg_endpoint, &redirect_uri).await?;
-                    (cid, csec)
-                } else {
-                    return Err(AppError::OAuth(
-                        "Server has no registration_endpoint and no client_id is stored. \
-                         Cannot authenticate without a client_id."
-                            .into(),
-                    ));
-                }
-            }
-        }
+    // 4-10. Sign in: device flow where there's probably no local browser, else a browser redirect
+    let use_device_flow = metadata.device_authorization_endpoint.is_some()
+        && oauth::prefers_device_flow(&oauth_config);
+    let (tokens, client_id, client_secret) = if use_device_flow {
+        device_flow(&app, &state, &oauth_store, &id, &metadata, &oauth_config).await?
+    } else {
+        browser_flow(&app, &oauth_store, &id, &metadata, &oauth_config).await?
     };
 
-    // 6. Generate PKCE + state nonce
-    let pkce = oauth::generate_pkce();
-    let state_nonce = oauth::generate_state_nonce();
-
-    // 7. Build authorization URL
-    let auth_url =
-        oauth::build_authorization_url(&metadata, &client_id, &redirect_uri, &pkce, &state_nonce)?;
-
-    // 8. Open browser
-    info!("Opening browser for OAuth authorization");
-    app.opener()
-        .open_url(&auth_url, None::<&str>)
-        .map_err(|e| AppError::OAuth(format!("Failed to open browser: {e}")))?;
-
-    let _ = app.emit(
-        "oauth-status-changed",
-        serde_json::json!({ "serverId": id, "status": "awaiting_browser" }),
-    );
-
-    // 9. Await callback (2-min timeout is built into the callback server)
-    let callback_result = callback_rx
-        .await
-        .map_err(|_| AppError::OAuth("OAuth callback channel closed unexpectedly".into()))??;
-
-    // Verify state nonce
-    if callback_result.state != state_nonce {
-        return Err(AppError::OAuth(
-            "OAuth state mismatch — possible CSRF attack".into(),
-        ));
-    }
-
-    let _ = app.emit(
-        "oauth-status-changed",
-        serde_json::json!({ "serverId": id, "status": "exchanging_code" }),
-    );
-
-    // 10. Exchange code for tokens
-    let tokens = oauth::exchange_code(
-        &metadata,
-        &client_id,
-        client_secret.as_deref(),
-        &callback_result.code,
-        &redirect_uri,
-        &pkce.code_verifier,
-    )
-    .await?;
-
     // 11. Store in OAuthStore and persist to disk
     {
         let mut store = oauth_store.lock().await;
@@ -127,6 +75,9 @@ pub async fn start_oauth_flow(
                 client_id: Some(client_id),
                 client_secret,
                 tokens: Some(tokens.clone()),
+                resource: oauth_config.resource,
+                mode: AuthMode::Interactive,
+                last_refreshed_at: None,
             },
         );
         crate::persistence::save_oauth_store(&app, store.entries());
@@ -141,7 +92,7 @@ pub async fn start_oauth_flow(
 
     // 12. Auto-retry connection with token
     //     Re-read config and connect with the new access token.
-    let server_config = {
+    {
         let mut s = state.lock().unwrap();
         let server = s
             .servers
@@ -149,22 +100,25 @@ pub async fn start_oauth_flow(
             .find(|s| s.id == id)
             .ok_or_else(|| AppError::ServerNotFound(id.clone()))?;
         server.status = Some(crate::state::ServerStatus::Connecting);
-        (
-            server.url.clone().unwrap_or_default(),
-            server.headers.clone().unwrap_or_default(),
-        )
-    };
+    }
 
     let _ = app.emit(
         "server-status-changed",
         serde_json::json!({ "serverId": id, "status": "connecting" }),
     );
 
-    let client = crate::mcp::client::McpClient::connect_http(
-        &server_config.0,
-        server_config.1,
-        Some(tokens.access_token),
-    )
+    let client = async {
+        let (url, headers) = resolve_http_endpoint(&app, &id)?;
+        crate::mcp::client::McpClient::connect_http(
+            &app,
+            &id,
+            &url,
+            headers,
+            Some(tokens.access_token),
+            None,
+        )
+        .await
+    }
     .await;
 
     match client {
@@ -202,6 +156,8 @@ pub async fn start_oauth_flow(
                         tools: tools.clone(),
                     },
                 );
+                s.tool_catalog.insert(id.clone(), tools.clone());
+                crate::persistence::save_tool_catalog(&app, &s.tool_catalog);
             }
 
             {
@@ -237,15 +193,244 @@ pub async fn start_oauth_flow(
     }
 }
 
+/// Authorization code flow with PKCE: open the browser and wait for the
+/// redirect on a loopback callback server.
+async fn browser_flow(
+    app: &AppHandle,
+    oauth_store: &SharedOAuthStore,
+    id: &str,
+    metadata: &AuthServerMetadata,
+    config: &OAuthConfig,
+) -> Result<(OAuthTokens, String, Option<String>), AppError> {
+    // 4. Start callback server to get the redirect URI
+    let (port, callback_rx) = oauth_callback::start_callback_server().await?;
+    let redirect_uri = format!("http://127.0.0.1:{port}/oauth/callback");
+
+    // 5. Use the configured client, else a stored one, else register dynamically
+    let (client_id, client_secret) =
+        resolve_client(oauth_store, id, config, metadata, &redirect_uri, false).await?;
+
+    // 6. Generate PKCE + state nonce
+    let pkce = oauth::generate_pkce();
+    let state_nonce = oauth::generate_state_nonce();
+
+    // 7. Build authorization URL
+    let auth_url = oauth::build_authorization_url(
+        metadata,
+        &client_id,
+        &redirect_uri,
+        &pkce,
+        &state_nonce,
+        config,
+    )?;
+
+    // 8. Open browser
+    info!("Opening browser for OAuth authorization");
+    app.opener()
+        .open_url(&auth_url, None::<&str>)
+        .map_err(|e| AppError::OAuth(format!("Failed to open browser: {e}")))?;
+
+    let _ = app.emit(
+        "oauth-status-changed",
+        serde_json::json!({ "serverId": id, "status": "awaiting_browser" }),
+    );
+
+    // 9. Await callback (2-min timeout is built into the callback server)
+    let callback_result = callback_rx
+        .await
+        .map_err(|_| AppError::OAuth("OAuth callback channel closed unexpectedly".into()))??;
+
+    // Verify state nonce
+    if callback_result.state != state_nonce {
+        return Err(AppError::OAuth(
+            "OAuth state mismatch — possible CSRF attack".into(),
+        ));
+    }
+
+    let _ = app.emit(
+        "oauth-status-changed",
+        serde_json::json!({ "serverId": id, "status": "exchanging_code" }),
+    );
+
+    // 10. Exchange code for tokens
+    let tokens = oauth::exchange_code(
+        metadata,
+        &client_id,
+        client_secret.as_deref(),
+        &callback_result.code,
+        &redirect_uri,
+        &pkce.code_verifier,
+        config.resource.as_deref(),
+    )
+    .await?;
+
+    Ok((tokens, client_id, client_secret))
+}
+
+/// RFC 8628 device flow: show a code the user enters on any device with a
+/// browser, then poll until they have signed in.
+async fn device_flow(
+    app: &AppHandle,
+    state: &SharedState,
+    oauth_store: &SharedOAuthStore,
+    id: &str,
+    metadata: &AuthServerMetadata,
+    config: &OAuthConfig,
+) -> Result<(OAuthTokens, String, Option<String>), AppError> {
+    // Registration insists on a redirect URI even though the device flow never uses it
+    let (client_id, client_secret) = resolve_client(
+        oauth_store,
+        id,
+        config,
+        metadata,
+        "http://127.0.0.1/oauth/callback",
+        true,
+    )
+    .await?;
+
+    let device =
+        oauth::request_device_authorization(metadata, &client_id, client_secret.as_deref(), config)
+            .await?;
+
+    let prompt = DeviceCodePrompt {
+        user_code: device.user_code.clone(),
+        verification_uri: device.verification_uri.clone(),
+        verification_uri_complete: device.verification_uri_complete.clone(),
+    };
+    info!(
+        "Device sign-in for server {id}: enter {} at {}",
+        prompt.user_code, prompt.verification_uri
+    );
+    state
+        .lock()
+        .unwrap()
+        .device_codes
+        .insert(id.to_string(), prompt.clone());
+    crate::tray::rebuild_tray_menu(app);
+    let _ = app.emit(
+        "oauth-status-changed",
+        serde_json::json!({ "serverId": id, "status": "awaiting_device", "device": prompt }),
+    );
+
+    let result = oauth::poll_device_token(
+        metadata,
+        &client_id,
+        client_secret.as_deref(),
+        &device,
+        config.resource.as_deref(),
+    )
+    .await;
+
+    state.lock().unwrap().device_codes.remove(id);
+    crate::tray::rebuild_tray_menu(app);
+
+    Ok((result?, client_id, client_secret))
+}
+
+/// Pick the OAuth client: the one configured on the server, else one stored
+/// from an earlier sign-in, else a new dynamic registration.
+async fn resolve_client(
+    oauth_store: &SharedOAuthStore,
+    id: &str,
+    config: &OAuthConfig,
+    metadata: &AuthServerMetadata,
+    redirect_uri: &str,
+    device_flow: bool,
+) -> Result<(String, Option<String>), AppError> {
+    if let Some(ref cid) = config.client_id {
+        return Ok((cid.clone(), config.client_secret.clone()));
+    }
+
+    {
+        let store = oauth_store.lock().await;
+        if let Some(os) = store.get(id) {
+            if let Some(ref cid) = os.client_id {
+                return Ok((cid.clone(), os.client_secret.clone()));
+            }
+        }
+    }
+
+    match metadata.registration_endpoint {
+        Some(ref reg_endpoint) => {
+            oauth::dynamic_register(reg_endpoint, redirect_uri, device_flow).await
+        }
+        None => Err(AppError::OAuth(
+            "Server has no registration_endpoint and no client_id is stored. \
+             Set a client ID in the server's OAuth settings."
+                .into(),
+        )),
+    }
+}
+
 #[tauri::command]
 pub async fn clear_oauth_tokens(
     app: AppHandle,
     oauth_store: State<'_, SharedOAuthStore>,
     id: String,
 ) -> Result<(), AppError> {
-    let mut store = oauth_store.lock().await;
-    store.remove(&id);
-    crate::persistence::save_oauth_store(&app, store.entries());
+    let removed = {
+        let mut store = oauth_store.lock().await;
+        let removed = store.remove(&id);
+        crate::persistence::save_oauth_store(&app, store.entries());
+        removed
+    };
     info!("Cleared OAuth tokens for server {id}");
+
+    // Local tokens are gone either way; revocation makes sure the provider forgets them too
+    if let Some(oauth_state) = removed {
+        match oauth::revoke_tokens(&oauth_state).await {
+            Ok(true) => info!("Revoked OAuth tokens for server {id}"),
+            Ok(false) => {}
+            Err(e) => warn!("Failed to revoke OAuth tokens for server {id}: {e}"),
+        }
+    }
     Ok(())
 }
+
+/// Report the grant each HTTP server currently holds.
+#[tauri::command]
+pub async fn get_token_status(
+    state: State<'_, SharedState>,
+    oauth_store: State<'_, SharedOAuthStore>,
+) -> Result<Vec<TokenStatus>, AppError> {
+    let servers: Vec<(String, String, AuthMode)> = {
+        let s = state.lock().unwrap();
+        s.servers
+            .iter()
+            .filter(|srv| matches!(srv.transport, ServerTransport::Http))
+            .map(|srv| {
+                let mode = srv.oauth.as_ref().map(|o| o.mode).unwrap_or_default();
+                (srv.id.clone(), srv.name.clone(), mode)
+            })
+            .collect()
+    };
+
+    let store = oauth_store.lock().await;
+    let statuses = servers
+        .into_iter()
+        .filter_map(|(server_id, server_name, mode)| {
+            let entry = store.get(&server_id).filter(|e| e.mode == mode);
+            let tokens = entry.and_then(|e| e.tokens.as_ref());
+            // Static tokens never reach the OAuth store; they're reported without a grant
+            if tokens.is_none() && mode != AuthMode::StaticToken {
+                return None;
+            }
+            Some(TokenStatus {
+                server_id,
+                server_name,
+                mode,
+                scopes: tokens
+                    .and_then(|t| t.scope.as_deref())
+                    .map(|s| s.split_whitespace().map(String::from).collect())
+                    .unwrap_or_default(),
+                expires_at: tokens.and_then(|t| t.expires_in.map(|e| t.obtained_at + e)),
+                obtained_at: tokens.map(|t| t.obtained_at),
+                has_refresh_token: tokens.is_some_and(|t| t.refresh_token.is_some()),
+                last_refreshed_at: entry.and_then(|e| e.last_refreshed_at),
+                revocable: entry
+                    .is_some_and(|e| e.auth_server_metadata.revocation_endpoint.is_some()),
+            })
+        })
+        .collect();
+    Ok(statuses)
+}
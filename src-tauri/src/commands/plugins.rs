@@ -11,6 +11,7 @@ async fn run_claude_plugin(args: &[&str]) -> Result<String, AppError> {
     let output = tokio::process::Command::new(&claude)
         .arg("plugin")
         .args(args)
+        .envs(crate::shell_env::get().vars())
         .env_remove("CLAUDECODE")
         .output()
         .await
@@ -95,9 +95,15 @@ pub async fn install_registry_server(
         command: Some(config.command),
         args: Some(config.args),
         env: if env.is_empty() { None } else { Some(env) },
+        cwd: None,
+        env_file: None,
         url: None,
         headers: None,
         tags: None,
+        container: None,
+        oauth: None,
+        replay: None,
+        sandbox: None,
     };
 
     crate::commands::servers::add_server_inner(&app, &state, input, Some(id), None)
@@ -122,9 +128,16 @@ pub async fn check_runtime_deps() -> Result<RuntimeDeps, AppError> {
     Ok(RuntimeDeps { npx, uvx, docker })
 }
 
+/// Run `cmd` the way a server would be spawned: found on the login-shell PATH
+/// and with the login-shell environment.
 async fn check_command(cmd: &str, args: &[&str]) -> bool {
-    tokio::process::Command::new(cmd)
+    let shell_env = crate::shell_env::get();
+    let Some(program) = shell_env.which(cmd) else {
+        return false;
+    };
+    tokio::process::Command::new(program)
         .args(args)
+        .envs(shell_env.vars())
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .status()
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use tauri::State;
+use tracing::info;
+
+use crate::error::AppError;
+use crate::mcp::client::SharedConnections;
+use crate::mcp::replay::{Recorder, RecordingStatus};
+use crate::state::SharedState;
+
+/// Start recording a connected server's tool calls to a fixture at `path`.
+/// Calls made through the proxy or the app are appended as they happen.
+#[tauri::command]
+pub async fn start_recording(
+    state: State<'_, SharedState>,
+    connections: State<'_, SharedConnections>,
+    recorder: State<'_, Recorder>,
+    id: String,
+    path: String,
+) -> Result<(), AppError> {
+    let name = {
+        let s = state.lock().unwrap();
+        s.servers
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.name.clone())
+            .ok_or_else(|| AppError::ServerNotFound(id.clone()))?
+    };
+    let tools = {
+        let conns = connections.lock().await;
+        conns
+            .get(&id)
+            .map(|client| client.tools.clone())
+            .ok_or_else(|| AppError::ConnectionFailed(format!("{name} is not connected")))?
+    };
+
+    recorder.start(&id, &name, PathBuf::from(&path), tools)?;
+    info!("Recording {name} to {path}");
+    Ok(())
+}
+
+/// Stop recording a server. Returns the finished recording, if there was one.
+#[tauri::command]
+pub async fn stop_recording(
+    recorder: State<'_, Recorder>,
+    id: String,
+) -> Result<Option<RecordingStatus>, AppError> {
+    let status = recorder.stop(&id);
+    if let Some(status) = &status {
+        info!(
+            "Saved {} recorded calls to {}",
+            status.exchanges, status.path
+        );
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn list_recordings(
+    recorder: State<'_, Recorder>,
+) -> Result<Vec<RecordingStatus>, AppError> {
+    Ok(recorder.list())
+}
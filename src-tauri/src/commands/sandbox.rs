@@ -0,0 +1,61 @@
+use tauri::{AppHandle, State};
+
+use crate::error::AppError;
+use crate::mcp::sandbox::{self, SandboxProfile, SandboxTools};
+use crate::persistence::save_servers;
+use crate::state::{SandboxConfig, ServerConfig, ServerTransport, SharedState};
+
+#[tauri::command]
+pub async fn get_sandbox_profile(
+    state: State<'_, SharedState>,
+    id: String,
+) -> Result<SandboxProfile, AppError> {
+    let (command, args, config) = {
+        let s = state.lock().unwrap();
+        let server = s
+            .servers
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AppError::ServerNotFound(id.clone()))?;
+        (
+            server.command.clone().unwrap_or_default(),
+            server.args.clone().unwrap_or_default(),
+            server.sandbox.clone().unwrap_or_default(),
+        )
+    };
+
+    Ok(sandbox::describe(
+        &config,
+        &SandboxTools::detect(),
+        &command,
+        &args,
+    ))
+}
+
+/// Set (or clear, with `None`) the sandbox settings for a stdio server.
+/// Takes effect on the next connect.
+#[tauri::command]
+pub async fn set_server_sandbox(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    id: String,
+    sandbox: Option<SandboxConfig>,
+) -> Result<ServerConfig, AppError> {
+    let mut s = state.lock().unwrap();
+    let server = s
+        .servers
+        .iter_mut()
+        .find(|s| s.id == id)
+        .ok_or_else(|| AppError::ServerNotFound(id.clone()))?;
+
+    if sandbox.is_some() && !matches!(server.transport, ServerTransport::Stdio) {
+        return Err(AppError::Validation(
+            "Sandboxing is only supported for stdio servers".into(),
+        ));
+    }
+
+    server.sandbox = sandbox;
+    let updated = server.clone();
+    save_servers(&app, &s.servers);
+    Ok(updated)
+}
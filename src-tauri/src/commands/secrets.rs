@@ -0,0 +1,52 @@
+use serde::Serialize;
+use tauri::State;
+use tracing::info;
+
+use crate::error::AppError;
+use crate::secrets::{self, SecretStore};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretList {
+    /// Where secrets are kept, e.g. "Secret Service" or "Encrypted file".
+    pub backend: String,
+    /// Secret names, sorted. Values are never sent to the frontend.
+    pub names: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn list_secrets(secret_store: State<'_, SecretStore>) -> Result<SecretList, AppError> {
+    Ok(SecretList {
+        backend: secret_store.backend_name().into(),
+        names: secret_store.names(),
+    })
+}
+
+#[tauri::command]
+pub async fn set_secret(
+    secret_store: State<'_, SecretStore>,
+    name: String,
+    value: String,
+) -> Result<(), AppError> {
+    let name = name.trim();
+    if !secrets::is_valid_name(name) {
+        return Err(AppError::Validation(
+            "Secret names may only contain letters, digits, '_', '-' and '.'".into(),
+        ));
+    }
+    secret_store.set(name, &value)?;
+    info!("Stored secret {name}");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_secret(
+    secret_store: State<'_, SecretStore>,
+    name: String,
+) -> Result<(), AppError> {
+    if !secrets::is_valid_name(&name) || !secret_store.delete(&name)? {
+        return Err(AppError::Validation(format!("Secret not found: {name}")));
+    }
+    info!("Deleted secret {name}");
+    Ok(())
+}
@@ -1,10 +1,16 @@
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use uuid::Uuid;
 
 use crate::error::AppError;
-use crate::persistence::save_servers;
+use crate::mcp::env;
+use crate::mcp::search::ToolIndex;
+use crate::persistence::{migrate_server_credentials, save_servers, save_tool_catalog};
+use crate::secrets::SecretStore;
+use crate::server_logs::ServerLogs;
 use crate::state::registry::detect_http_proxy;
-use crate::state::{ServerConfig, ServerConfigInput, ServerStatus, ServerTransport, SharedState};
+use crate::state::{
+    SandboxConfig, ServerConfig, ServerConfigInput, ServerStatus, ServerTransport, SharedState,
+};
 
 /// If the input is a stdio config wrapping an HTTP proxy (e.g. `npx mcp-remote`),
 /// rewrite it to use HTTP transport directly.
@@ -28,6 +34,40 @@ fn maybe_rewrite_proxy(mut input: ServerConfigInput) -> ServerConfigInput {
     input
 }
 
+/// Container servers need an image to run.
+fn validate_container(input: &ServerConfigInput) -> Result<(), AppError> {
+    if !matches!(input.transport, ServerTransport::Container) {
+        return Ok(());
+    }
+    match &input.container {
+        Some(c) if !c.image.trim().is_empty() => Ok(()),
+        _ => Err(AppError::Validation(
+            "Container servers require an image".into(),
+        )),
+    }
+}
+
+/// Replay servers need a fixture to serve.
+fn validate_replay(input: &ServerConfigInput) -> Result<(), AppError> {
+    if !matches!(input.transport, ServerTransport::Replay) {
+        return Ok(());
+    }
+    match &input.replay {
+        Some(r) if !r.fixture.trim().is_empty() => Ok(()),
+        _ => Err(AppError::Validation(
+            "Replay servers require a fixture file".into(),
+        )),
+    }
+}
+
+/// Sandboxing only applies to stdio servers; drop it for anything else.
+fn stdio_sandbox(input: &ServerConfigInput) -> Option<SandboxConfig> {
+    match input.transport {
+        ServerTransport::Stdio => input.sandbox.clone(),
+        _ => None,
+    }
+}
+
 /// Core server-creation logic, reusable by both the `add_server` command and registry install.
 pub fn add_server_inner(
     app: &AppHandle,
@@ -37,7 +77,10 @@ pub fn add_server_inner(
     managed_by: Option<String>,
 ) -> Result<ServerConfig, AppError> {
     let input = maybe_rewrite_proxy(input);
-    let server = ServerConfig {
+    validate_container(&input)?;
+    validate_replay(&input)?;
+    let sandbox = stdio_sandbox(&input);
+    let mut server = ServerConfig {
         id: Uuid::new_v4().to_string(),
         name: input.name,
         enabled: input.enabled,
@@ -45,6 +88,8 @@ pub fn add_server_inner(
         command: input.command,
         args: input.args,
         env: input.env,
+        cwd: input.cwd,
+        env_file: input.env_file,
         url: input.url,
         headers: input.headers,
         tags: input.tags,
@@ -53,7 +98,19 @@ pub fn add_server_inner(
         managed: None,
         managed_by,
         registry_name,
+        sandbox,
+        http_mode: None,
+        container: input.container,
+        oauth: input.oauth,
+        replay: input.replay,
+        disabled_tools: None,
+        enabled_tools: None,
+        extra: None,
     };
+    migrate_server_credentials(
+        &app.state::<SecretStore>(),
+        std::slice::from_mut(&mut server),
+    );
 
     {
         let mut state = state.lock().unwrap();
@@ -105,7 +162,13 @@ pub async fn remove_server(
         }
         state.connections.remove(&id);
         save_servers(&app, &state.servers);
+        if state.tool_catalog.remove(&id).is_some() {
+            save_tool_catalog(&app, &state.tool_catalog);
+        }
     }
+    app.state::<ServerLogs>().remove(&id);
+    app.state::<ToolIndex>().update(&id, &[]);
+    crate::mcp::embeddings::index_server(&app, &id, &[]);
     crate::tray::rebuild_tray_menu(&app);
     Ok(())
 }
@@ -118,6 +181,8 @@ pub async fn update_server(
     input: ServerConfigInput,
 ) -> Result<ServerConfig, AppError> {
     let input = maybe_rewrite_proxy(input);
+    validate_container(&input)?;
+    validate_replay(&input)?;
     let updated = {
         let mut s = state.lock().unwrap();
         let server = s
@@ -126,16 +191,27 @@ pub async fn update_server(
             .find(|s| s.id == id)
             .ok_or_else(|| AppError::ServerNotFound(id.clone()))?;
 
+        server.sandbox = stdio_sandbox(&input);
         server.name = input.name;
         server.transport = input.transport;
         server.command = input.command;
         server.args = input.args;
         server.env = input.env;
+        server.cwd = input.cwd;
+        server.env_file = input.env_file;
+        // A different endpoint may speak a different HTTP transport — re-probe it.
+        if server.url != input.url {
+            server.http_mode = None;
+        }
         server.url = input.url;
         server.headers = input.headers;
         server.enabled = input.enabled;
         server.tags = input.tags;
+        server.container = input.container;
+        server.oauth = input.oauth;
+        server.replay = input.replay;
         // Preserve registry_name — don't overwrite from input
+        migrate_server_credentials(&app.state::<SecretStore>(), std::slice::from_mut(server));
 
         let updated = server.clone();
         save_servers(&app, &s.servers);
@@ -144,3 +220,39 @@ pub async fn update_server(
     crate::tray::rebuild_tray_menu(&app);
     Ok(updated)
 }
+
+/// The binary a stdio server's command resolves to on the login-shell `PATH`,
+/// or `None` if it can't be found (or the server isn't stdio).
+#[tauri::command]
+pub async fn resolve_server_command(
+    state: State<'_, SharedState>,
+    id: String,
+) -> Result<Option<String>, AppError> {
+    let (command, cwd) = {
+        let s = state.lock().unwrap();
+        let server = s
+            .servers
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AppError::ServerNotFound(id.clone()))?;
+        match (&server.transport, &server.command) {
+            (ServerTransport::Stdio, Some(command)) => (command.clone(), server.cwd.clone()),
+            _ => return Ok(None),
+        }
+    };
+
+    let shell_env = crate::shell_env::get();
+    let lookup = match cwd {
+        Some(dir) if command.contains(std::path::MAIN_SEPARATOR) => {
+            let dir = env::Interpolator::new(shell_env.vars()).expand(&dir);
+            env::resolve_path(&dir, None)
+                .join(&command)
+                .to_string_lossy()
+                .into_owned()
+        }
+        _ => command,
+    };
+    Ok(shell_env
+        .which(&lookup)
+        .map(|p| p.to_string_lossy().into_owned()))
+}
@@ -52,6 +52,12 @@ pub fn get_skill_tool_definitions() -> Result<Vec<SkillToolDef>, AppError> {
             name: "Codex",
             skills_dir: home.join(".codex/skills"),
         },
+        SkillToolDef {
+            id: "vscode",
+            name: "VS Code",
+            // Copilot's personal skills, shared by stable and Insiders
+            skills_dir: home.join(".copilot/skills"),
+        },
     ])
 }
 
@@ -59,7 +65,7 @@ pub fn get_skill_tool_definitions() -> Result<Vec<SkillToolDef>, AppError> {
 pub fn supports_skills(integration_id: &str) -> bool {
     matches!(
         integration_id,
-        "claude-code" | "cursor" | "windsurf" | "opencode" | "codex"
+        "claude-code" | "cursor" | "windsurf" | "opencode" | "codex" | "vscode"
     )
 }
 
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 use crate::error::AppError;
 use crate::mcp::client::{CallToolResult, McpClient, SharedConnections};
-use crate::state::{McpTool, SharedState};
+use crate::mcp::discovery::DISCOVERY_ID;
+use crate::mcp::proxy::NotifySender;
+use crate::persistence::save_servers;
+use crate::state::{McpTool, ServerConfig, SharedState};
 
 #[tauri::command]
 pub async fn list_tools(
@@ -35,6 +38,7 @@ pub async fn list_all_tools(state: State<'_, SharedState>) -> Result<Vec<McpTool
 
 #[tauri::command]
 pub async fn call_tool(
+    app: AppHandle,
     connections: State<'_, SharedConnections>,
     server_id: String,
     tool_name: String,
@@ -48,5 +52,39 @@ pub async fn call_tool(
             .cloned()
             .ok_or_else(|| AppError::ServerNotFound(server_id.clone()))?
     };
-    client.call_tool(&tool_name, arguments).await
+    crate::commands::connections::call_tool_prompting_sign_in(
+        &app, &server_id, client, &tool_name, arguments,
+    )
+    .await
+}
+
+/// Hide a server's tools from AI clients, or show them again with an empty list.
+/// This replaces any imported allowlist. Connected clients are told their tool
+/// list changed.
+#[tauri::command]
+pub async fn set_disabled_tools(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    id: String,
+    tools: Vec<String>,
+) -> Result<ServerConfig, AppError> {
+    let updated = {
+        let mut s = state.lock().unwrap();
+        let server = s
+            .servers
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AppError::ServerNotFound(id.clone()))?;
+        server.disabled_tools = (!tools.is_empty()).then_some(tools);
+        server.enabled_tools = None;
+        let updated = server.clone();
+        save_servers(&app, &s.servers);
+        updated
+    };
+
+    if let Some(sender) = app.try_state::<NotifySender>() {
+        let _ = sender.0.send(id);
+        let _ = sender.0.send(DISCOVERY_ID.to_string());
+    }
+    Ok(updated)
 }
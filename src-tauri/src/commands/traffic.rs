@@ -0,0 +1,52 @@
+use serde::Serialize;
+use tauri::State;
+use tracing::info;
+
+use crate::error::AppError;
+use crate::mcp::traffic::{TrafficEntry, TrafficTap};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficSnapshot {
+    pub enabled: bool,
+    /// Buffered messages, oldest first. New ones arrive as `traffic-captured` events.
+    pub entries: Vec<TrafficEntry>,
+}
+
+#[tauri::command]
+pub async fn get_traffic(tap: State<'_, TrafficTap>) -> Result<TrafficSnapshot, AppError> {
+    Ok(TrafficSnapshot {
+        enabled: tap.enabled(),
+        entries: tap.entries(),
+    })
+}
+
+#[tauri::command]
+pub async fn set_traffic_capture(
+    tap: State<'_, TrafficTap>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    tap.set_enabled(enabled);
+    info!(
+        "Traffic capture {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_traffic(tap: State<'_, TrafficTap>) -> Result<(), AppError> {
+    tap.clear();
+    Ok(())
+}
+
+/// Write the buffered traffic to `path` as a HAR-like JSON document.
+/// Returns the number of exchanges written.
+#[tauri::command]
+pub async fn export_traffic(tap: State<'_, TrafficTap>, path: String) -> Result<usize, AppError> {
+    let har = tap.export();
+    let count = har["log"]["entries"].as_array().map_or(0, Vec::len);
+    std::fs::write(&path, serde_json::to_string_pretty(&har)?)?;
+    info!("Exported {count} traffic entries to {path}");
+    Ok(count)
+}
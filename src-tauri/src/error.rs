@@ -20,6 +20,12 @@ pub enum AppError {
     #[error("Authentication required: {0}")]
     AuthRequired(String),
 
+    #[error("Access token expired: {0}")]
+    AuthExpired(String),
+
+    #[error("Session expired: {0}")]
+    SessionExpired(String),
+
     #[error("OAuth error: {0}")]
     OAuth(String),
 
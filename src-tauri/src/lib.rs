@@ -3,6 +3,9 @@ mod error;
 mod mcp;
 mod memory_client;
 mod persistence;
+mod secrets;
+mod server_logs;
+mod shell_env;
 mod state;
 pub mod stats;
 mod tray;
@@ -29,6 +32,9 @@ pub fn run() {
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
+            // Capture the login-shell environment before any server is spawned
+            shell_env::init();
+
             // TODO(2026-03-12): Remove migration after 2 weeks
             // Migrate store from old bundle ID (com.mcpmanager.app → com.agenthub.app)
             let old_store_dir = dirs::home_dir()
@@ -45,9 +51,23 @@ pub fn run() {
                 }
             }
 
+            // Secrets come first: OAuth tokens and API keys are loaded from there
+            app.manage(secrets::SecretStore::open(&app.path().app_data_dir()?));
+            app.manage(mcp::traffic::TrafficTap::new());
+            app.manage(mcp::replay::Recorder::new());
+            app.manage(mcp::search::ToolIndex::new());
+            app.manage(mcp::embeddings::ToolEmbeddings::new(
+                app.path().app_data_dir()?.join("tool-embeddings.json"),
+            ));
+            app.manage(server_logs::ServerLogs::new(
+                app.path().app_data_dir()?.join("logs"),
+            ));
+
             // Load persisted server configs, enabled integrations, and stats
-            let servers = persistence::load_servers(app.handle());
+            let mut servers = persistence::load_servers(app.handle());
+            persistence::migrate_plaintext_secrets(app.handle(), &mut servers);
             let enabled_integrations = persistence::load_enabled_integrations(app.handle());
+            let vscode_workspaces = persistence::load_vscode_workspaces(app.handle());
             let stats = persistence::load_stats(app.handle());
             let embedding_config = persistence::load_embedding_config(app.handle());
             let oauth_entries = persistence::load_oauth_store(app.handle());
@@ -60,6 +80,14 @@ pub fn run() {
             );
 
             let tool_discovery_enabled = persistence::load_tool_discovery(app.handle());
+            let discovery_search_mode = persistence::load_discovery_search_mode(app.handle());
+            let discovery_pinning = persistence::load_discovery_pinning(app.handle());
+            let tool_catalog = persistence::load_tool_catalog(app.handle());
+            // Servers stay searchable through discovery before they (re)connect
+            let tool_index = app.state::<mcp::search::ToolIndex>();
+            for (id, tools) in &tool_catalog {
+                tool_index.update(id, tools);
+            }
             let installed_skills = persistence::load_installed_skills(app.handle());
             let enabled_skill_integrations =
                 persistence::load_enabled_skill_integrations(app.handle());
@@ -72,8 +100,12 @@ pub fn run() {
             let mut app_state = AppState::new();
             app_state.servers = servers;
             app_state.enabled_integrations = enabled_integrations;
+            app_state.vscode_workspaces = vscode_workspaces;
             app_state.embedding_config = embedding_config;
             app_state.tool_discovery_enabled = tool_discovery_enabled;
+            app_state.discovery_search_mode = discovery_search_mode;
+            app_state.discovery_pinning = discovery_pinning;
+            app_state.tool_catalog = tool_catalog;
             app_state.installed_skills = installed_skills;
             app_state.enabled_skill_integrations = enabled_skill_integrations;
             let app_state = Mutex::new(app_state);
@@ -82,6 +114,7 @@ pub fn run() {
             commands::skills::reconcile_managed_skills(app.handle(), &app_state);
 
             app.manage(app_state);
+            mcp::embeddings::index_all(app.handle());
             app.manage(tokio::sync::Mutex::new(McpConnections::new()));
             app.manage(tokio::sync::Mutex::new(OAuthStore::from_entries(oauth_entries)));
             app.manage(Mutex::new(sysinfo::System::new()) as SharedSystem);
@@ -109,6 +142,12 @@ pub fn run() {
                 commands::connections::reconnect_on_startup(reconnect_handle).await;
             });
 
+            // Refresh OAuth tokens of connected servers before they expire
+            let refresh_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                mcp::oauth::run_refresh_scheduler(refresh_handle).await;
+            });
+
             tray::setup_tray(app)?;
 
             Ok(())
@@ -118,18 +157,31 @@ pub fn run() {
             commands::servers::add_server,
             commands::servers::remove_server,
             commands::servers::update_server,
+            commands::servers::resolve_server_command,
             commands::connections::connect_server,
             commands::connections::disconnect_server,
             commands::connections::drain_log_buffer,
+            commands::logs::tail_server_log,
+            commands::logs::search_server_logs,
+            commands::logs::export_server_logs,
+            commands::traffic::get_traffic,
+            commands::traffic::set_traffic_capture,
+            commands::traffic::clear_traffic,
+            commands::traffic::export_traffic,
+            commands::replay::start_recording,
+            commands::replay::stop_recording,
+            commands::replay::list_recordings,
             commands::tools::list_tools,
             commands::tools::list_all_tools,
             commands::tools::call_tool,
+            commands::tools::set_disabled_tools,
             commands::proxy::get_proxy_status,
             commands::integrations::detect_integrations,
             commands::integrations::enable_integration,
             commands::integrations::disable_integration,
             commands::oauth::start_oauth_flow,
             commands::oauth::clear_oauth_tokens,
+            commands::oauth::get_token_status,
             commands::skills::search_skills_marketplace,
             commands::skills::get_skills_marketplace_detail,
             commands::skills::list_installed_skills,
@@ -164,12 +216,19 @@ pub fn run() {
             commands::integrations::get_managed_config_previews,
             commands::discovery::get_discovery_mode,
             commands::discovery::set_discovery_mode,
+            commands::discovery::set_discovery_search_mode,
+            commands::discovery::set_discovery_pinning,
             commands::plugins::list_available_plugins,
             commands::plugins::install_plugin,
             commands::plugins::uninstall_plugin,
             commands::plugins::toggle_plugin,
             commands::plugins::list_installed_plugins,
             commands::plugins::update_marketplace,
+            commands::sandbox::get_sandbox_profile,
+            commands::sandbox::set_server_sandbox,
+            commands::secrets::list_secrets,
+            commands::secrets::set_secret,
+            commands::secrets::delete_secret,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
@@ -183,6 +242,15 @@ pub fn run() {
             } else {
                 info!("Restored integration configs to native mode on exit");
             }
+
+            // Container servers outlive the runtime client process, so remove them explicitly,
+            // and let HTTP servers know our sessions are over
+            let connections = app_handle.state::<mcp::client::SharedConnections>();
+            tauri::async_runtime::block_on(async {
+                let conns = connections.lock().await;
+                conns.close_sessions().await;
+                conns.remove_containers().await;
+            });
         }
     });
 }
@@ -1,30 +1,62 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
-use tauri::AppHandle;
+use futures::FutureExt;
+use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 use tracing::info;
 
 use crate::error::AppError;
-use crate::mcp::http_transport::HttpTransport;
+use crate::mcp::container::{self, ContainerHandle};
+use crate::mcp::http_transport::{HttpTransport, ServerMessageTap, SessionRenewed, TokenRefresher};
+use crate::mcp::oauth;
+use crate::mcp::proxy::make_error_response;
+use crate::mcp::replay::{Recorder, ReplayTransport};
+use crate::mcp::traffic::{self, Direction};
 use crate::mcp::transport::StdioTransport;
 use crate::mcp::types::*;
-
-/// Transport abstraction — either stdio (local process) or HTTP (remote server).
+use crate::mcp::ws_transport::{self, WsTransport};
+use crate::state::{
+    ContainerConfig, ContainerMode, HttpMode, ReplayConfig, SandboxConfig, SharedOAuthStore,
+};
+
+/// Transport abstraction — stdio (local process), HTTP or WebSocket (remote server).
+/// Container servers wrap stdio or HTTP and own the container's lifetime.
+/// Replay servers answer from a recorded fixture.
 enum Transport {
     Stdio(StdioTransport),
     Http(HttpTransport),
+    WebSocket(WsTransport),
+    Container(Box<Transport>, ContainerHandle),
+    Replay(ReplayTransport),
 }
 
 impl Transport {
+    fn next_id(&self) -> u64 {
+        match self {
+            Transport::Stdio(t) => t.next_id(),
+            Transport::Http(t) => t.next_id(),
+            Transport::WebSocket(t) => t.next_id(),
+            Transport::Container(inner, _) => inner.next_id(),
+            Transport::Replay(t) => t.next_id(),
+        }
+    }
+
     async fn send_request(
         &self,
+        id: u64,
         method: &str,
         params: Option<serde_json::Value>,
     ) -> Result<JsonRpcResponse, AppError> {
         match self {
-            Transport::Stdio(t) => t.send_request(method, params).await,
-            Transport::Http(t) => t.send_request(method, params).await,
+            Transport::Stdio(t) => t.send_request_with_id(id, method, params).await,
+            Transport::Http(t) => t.send_request_with_id(id, method, params).await,
+            Transport::WebSocket(t) => t.send_request_with_id(id, method, params).await,
+            Transport::Container(inner, _) => {
+                Box::pin(inner.send_request(id, method, params)).await
+            }
+            Transport::Replay(t) => t.send_request_with_id(id, method, params).await,
         }
     }
 
@@ -36,6 +68,30 @@ impl Transport {
         match self {
             Transport::Stdio(t) => t.send_notification(method, params).await,
             Transport::Http(t) => t.send_notification(method, params).await,
+            Transport::WebSocket(t) => t.send_notification(method, params).await,
+            Transport::Container(inner, _) => {
+                Box::pin(inner.send_notification(method, params)).await
+            }
+            Transport::Replay(_) => Ok(()),
+        }
+    }
+
+    /// Swap in a refreshed OAuth access token for remote transports.
+    async fn set_access_token(&self, token: String) {
+        match self {
+            Transport::Http(t) => t.set_access_token(token).await,
+            Transport::WebSocket(t) => t.set_access_token(token).await,
+            Transport::Container(inner, _) => Box::pin(inner.set_access_token(token)).await,
+            Transport::Stdio(_) | Transport::Replay(_) => {}
+        }
+    }
+
+    /// End a streamable HTTP session, if there is one.
+    async fn close_session(&self) {
+        match self {
+            Transport::Http(t) => t.close_session().await,
+            Transport::Container(inner, _) => Box::pin(inner.close_session()).await,
+            Transport::Stdio(_) | Transport::WebSocket(_) | Transport::Replay(_) => {}
         }
     }
 
@@ -43,11 +99,18 @@ impl Transport {
         match self {
             Transport::Stdio(t) => t.shutdown(),
             Transport::Http(_) => {
-                // HTTP transport has no persistent process to kill.
-                // Session cleanup (DELETE) would require async; dropping the
-                // transport is sufficient — the server will expire the session.
+                // HTTP transport has no persistent process to kill; the session
+                // is ended separately via `close_session`.
                 tracing::debug!("HTTP transport shutdown");
             }
+            Transport::WebSocket(t) => t.shutdown(),
+            Transport::Container(inner, handle) => {
+                inner.shutdown();
+                // Killing the runtime client doesn't stop the container, so remove it explicitly.
+                let handle = handle.clone();
+                tauri::async_runtime::spawn(async move { handle.remove().await });
+            }
+            Transport::Replay(_) => {}
         }
     }
 }
@@ -55,6 +118,8 @@ impl Transport {
 /// MCP client wrapping either a stdio or HTTP transport.
 pub struct McpClient {
     transport: Transport,
+    app: AppHandle,
+    server_id: String,
     pub server_capabilities: Option<ServerCapabilities>,
     pub server_info: Option<ServerInfo>,
     pub tools: Vec<McpToolDef>,
@@ -68,11 +133,15 @@ impl McpClient {
         command: &str,
         args: &[String],
         env: &HashMap<String, String>,
+        cwd: Option<&Path>,
+        sandbox: Option<&SandboxConfig>,
     ) -> Result<Self, AppError> {
-        let transport = StdioTransport::spawn(app, server_id, command, args, env)?;
+        let transport = StdioTransport::spawn(app, server_id, command, args, env, cwd, sandbox)?;
 
         let mut client = Self {
             transport: Transport::Stdio(transport),
+            app: app.clone(),
+            server_id: server_id.to_string(),
             server_capabilities: None,
             server_info: None,
             tools: Vec::new(),
@@ -84,16 +153,138 @@ impl McpClient {
         Ok(client)
     }
 
-    /// Connect to a remote MCP server via HTTP, perform initialization, and discover tools.
+    /// Connect to a remote MCP server, perform initialization, and discover tools.
+    /// `ws://` and `wss://` URLs use the WebSocket transport, everything else HTTP.
+    /// `http_mode` is the HTTP transport mode remembered from an earlier connect;
+    /// `None` probes for it.
     pub async fn connect_http(
+        app: &AppHandle,
+        server_id: &str,
         url: &str,
         headers: HashMap<String, String>,
         access_token: Option<String>,
+        http_mode: Option<HttpMode>,
     ) -> Result<Self, AppError> {
-        let transport = HttpTransport::connect(url, headers, access_token).await?;
+        let transport = if ws_transport::is_websocket_url(url) {
+            Transport::WebSocket(
+                WsTransport::connect(app, server_id, url, headers, access_token).await?,
+            )
+        } else {
+            let has_token = access_token.is_some();
+            let mut transport =
+                HttpTransport::connect(url, headers, access_token, http_mode).await?;
+            if has_token {
+                transport.set_token_refresher(oauth_refresher(app, server_id));
+            }
+            transport.set_session_renewed_hook(tools_refresher(app, server_id));
+            transport.set_server_message_tap(traffic_tap(app, server_id));
+            Transport::Http(transport)
+        };
+
+        let mut client = Self {
+            transport,
+            app: app.clone(),
+            server_id: server_id.to_string(),
+            server_capabilities: None,
+            server_info: None,
+            tools: Vec::new(),
+        };
+
+        client.initialize().await?;
+        client.discover_tools().await?;
 
+        Ok(client)
+    }
+
+    /// Start an MCP server in a Docker/Podman container, perform initialization,
+    /// and discover tools. The container is removed again if any step fails.
+    pub async fn connect_container(
+        app: &AppHandle,
+        server_id: &str,
+        config: &ContainerConfig,
+        args: &[String],
+        env: &HashMap<String, String>,
+        headers: HashMap<String, String>,
+    ) -> Result<Self, AppError> {
+        let handle = ContainerHandle::new(container::resolve_runtime(config.runtime)?, server_id);
+        container::ensure_image(
+            app,
+            server_id,
+            &handle.runtime,
+            &container::image_ref(config),
+        )
+        .await?;
+
+        // A previous session may have left the container behind (e.g. after a crash).
+        handle.remove().await;
+
+        let inner = match config.mode {
+            ContainerMode::Stdio => {
+                let argv = container::run_args(config, Some(server_id), args, env, None)?;
+                info!(
+                    "Starting container {}: {} {}",
+                    handle.name,
+                    handle.runtime,
+                    argv.join(" ")
+                );
+                Transport::Stdio(StdioTransport::spawn(
+                    app,
+                    server_id,
+                    &handle.runtime,
+                    &argv,
+                    env,
+                    None,
+                    None,
+                )?)
+            }
+            ContainerMode::Http => {
+                let url = container::start_http(&handle, config, server_id, args, env).await?;
+                match HttpTransport::connect(&url, headers, None, None).await {
+                    Ok(mut t) => {
+                        t.set_session_renewed_hook(tools_refresher(app, server_id));
+                        t.set_server_message_tap(traffic_tap(app, server_id));
+                        Transport::Http(t)
+                    }
+                    Err(e) => {
+                        handle.remove().await;
+                        return Err(e);
+                    }
+                }
+            }
+        };
+
+        let mut client = Self {
+            transport: Transport::Container(Box::new(inner), handle.clone()),
+            app: app.clone(),
+            server_id: server_id.to_string(),
+            server_capabilities: None,
+            server_info: None,
+            tools: Vec::new(),
+        };
+
+        let handshake = async {
+            client.initialize().await?;
+            client.discover_tools().await
+        }
+        .await;
+        if let Err(e) = handshake {
+            handle.remove().await;
+            return Err(e);
+        }
+
+        Ok(client)
+    }
+
+    /// Serve a recorded fixture as a server. Fails if the fixture can't be read.
+    pub async fn connect_replay(
+        app: &AppHandle,
+        server_id: &str,
+        config: &ReplayConfig,
+    ) -> Result<Self, AppError> {
         let mut client = Self {
-            transport: Transport::Http(transport),
+            transport: Transport::Replay(ReplayTransport::open(config)?),
+            app: app.clone(),
+            server_id: server_id.to_string(),
             server_capabilities: None,
             server_info: None,
             tools: Vec::new(),
@@ -105,6 +296,54 @@ impl McpClient {
         Ok(client)
     }
 
+    /// Send a request to the backend, capturing it for the traffic inspector.
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        let id = self.transport.next_id();
+        let exchange = traffic::request(
+            &self.app,
+            &self.server_id,
+            Direction::ProxyToBackend,
+            None,
+            &serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        );
+        let result = self.transport.send_request(id, method, params).await;
+        if exchange.is_some() {
+            // Failures never reach us as a response, so record one in their place
+            let message = match &result {
+                Ok(response) => serde_json::to_value(response).unwrap_or_default(),
+                Err(e) => make_error_response(Some(id.into()), -32603, &e.to_string()),
+            };
+            traffic::response(
+                &self.app,
+                &self.server_id,
+                Direction::BackendToProxy,
+                None,
+                &message,
+                exchange,
+            );
+        }
+        result
+    }
+
+    async fn notify(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), AppError> {
+        traffic::notification(
+            &self.app,
+            &self.server_id,
+            Direction::ProxyToBackend,
+            None,
+            &serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        );
+        self.transport.send_notification(method, params).await
+    }
+
     /// Send the MCP initialize request and notifications/initialized.
     async fn initialize(&mut self) -> Result<(), AppError> {
         let params = InitializeParams {
@@ -122,10 +361,7 @@ impl McpClient {
         let params_json = serde_json::to_value(&params)
             .map_err(|e| AppError::Protocol(format!("Failed to serialize init params: {e}")))?;
 
-        let response = self
-            .transport
-            .send_request("initialize", Some(params_json))
-            .await?;
+        let response = self.request("initialize", Some(params_json)).await?;
 
         let result: InitializeResult = serde_json::from_value(
             response
@@ -143,18 +379,24 @@ impl McpClient {
         self.server_info = Some(result.server_info);
 
         // Send initialized notification
-        self.transport
-            .send_notification("notifications/initialized", None)
-            .await?;
+        self.notify("notifications/initialized", None).await?;
 
         Ok(())
     }
 
     /// Send tools/list and store the results.
     async fn discover_tools(&mut self) -> Result<(), AppError> {
+        let tools = self.list_tools().await?;
+        info!("Discovered {} tools", tools.len());
+        self.tools = tools;
+
+        Ok(())
+    }
+
+    /// Send tools/list and return the server's current tools.
+    pub async fn list_tools(&self) -> Result<Vec<McpToolDef>, AppError> {
         let response = self
-            .transport
-            .send_request("tools/list", Some(serde_json::json!({})))
+            .request("tools/list", Some(serde_json::json!({})))
             .await?;
 
         let result = response
@@ -169,10 +411,7 @@ impl McpClient {
         let tools_result: ToolsListResult = serde_json::from_value(result)
             .map_err(|e| AppError::Protocol(format!("Failed to parse tools list: {e}")))?;
 
-        info!("Discovered {} tools", tools_result.tools.len());
-        self.tools = tools_result.tools;
-
-        Ok(())
+        Ok(tools_result.tools)
     }
 
     /// Call a tool by name with the given arguments.
@@ -186,10 +425,7 @@ impl McpClient {
             "arguments": arguments,
         });
 
-        let response = self
-            .transport
-            .send_request("tools/call", Some(params))
-            .await?;
+        let response = self.request("tools/call", Some(params)).await?;
 
         let result = response
             .result
@@ -198,14 +434,43 @@ impl McpClient {
         let call_result: CallToolResult = serde_json::from_value(result)
             .map_err(|e| AppError::Protocol(format!("Failed to parse tool call result: {e}")))?;
 
+        if let Some(recorder) = self.app.try_state::<Recorder>() {
+            recorder.record(&self.server_id, name, &arguments, &call_result);
+        }
+
         Ok(call_result)
     }
 
     /// Return the PID of the underlying process, if using stdio transport.
     pub fn pid(&self) -> Option<u32> {
-        match &self.transport {
+        let transport = match &self.transport {
+            Transport::Container(inner, _) => inner.as_ref(),
+            t => t,
+        };
+        match transport {
             Transport::Stdio(t) => Some(t.pid()),
-            Transport::Http(_) => None,
+            _ => None,
+        }
+    }
+
+    /// The detected HTTP transport mode, for HTTP servers.
+    pub async fn http_mode(&self) -> Option<HttpMode> {
+        match &self.transport {
+            Transport::Http(t) => t.mode().await,
+            _ => None,
+        }
+    }
+
+    /// Hand a freshly refreshed OAuth access token to a remote connection.
+    pub async fn set_access_token(&self, token: String) {
+        self.transport.set_access_token(token).await;
+    }
+
+    /// The container backing this client, for container servers.
+    pub fn container(&self) -> Option<&ContainerHandle> {
+        match &self.transport {
+            Transport::Container(_, handle) => Some(handle),
+            _ => None,
         }
     }
 
@@ -213,6 +478,55 @@ impl McpClient {
     pub fn shutdown(&self) {
         self.transport.shutdown();
     }
+
+    /// Tell an HTTP server we're done with our session (`DELETE`). No-op for
+    /// other transports. Call before `shutdown`.
+    pub async fn close_session(&self) {
+        self.transport.close_session().await;
+    }
+}
+
+/// Refresh hook for HTTP transports: refreshes the server's stored OAuth
+/// token (persisting it) and returns the new access token.
+fn oauth_refresher(app: &AppHandle, server_id: &str) -> TokenRefresher {
+    let app = app.clone();
+    let server_id = server_id.to_string();
+    Arc::new(move || {
+        let app = app.clone();
+        let server_id = server_id.clone();
+        async move {
+            let store = app.state::<SharedOAuthStore>();
+            oauth::try_refresh_token(&store, &server_id, &app).await
+        }
+        .boxed()
+    })
+}
+
+/// Renewal hook for HTTP transports: a new session may serve different
+/// tools, so re-list them in the background once it's initialized.
+fn tools_refresher(app: &AppHandle, server_id: &str) -> SessionRenewed {
+    let app = app.clone();
+    let server_id = server_id.to_string();
+    Arc::new(move || {
+        tauri::async_runtime::spawn(crate::commands::connections::refresh_tools(
+            app.clone(),
+            server_id.clone(),
+        ));
+    })
+}
+
+/// Message hook for HTTP transports: records what the server sends on its
+/// streams besides responses in the traffic inspector.
+fn traffic_tap(app: &AppHandle, server_id: &str) -> ServerMessageTap {
+    let app = app.clone();
+    let server_id = server_id.to_string();
+    Arc::new(move |message| {
+        if message.get("id").is_some() {
+            traffic::request(&app, &server_id, Direction::BackendToProxy, None, message);
+        } else {
+            traffic::notification(&app, &server_id, Direction::BackendToProxy, None, message);
+        }
+    })
 }
 
 /// Result from calling a tool.
@@ -250,6 +564,11 @@ impl McpConnections {
         self.clients.get(id)
     }
 
+    /// IDs of all connected servers.
+    pub fn ids(&self) -> Vec<String> {
+        self.clients.keys().cloned().collect()
+    }
+
     /// Return (server_id, pid) for all connected stdio clients.
     pub fn pids(&self) -> Vec<(String, u32)> {
         self.clients
@@ -257,6 +576,23 @@ impl McpConnections {
             .filter_map(|(id, client)| client.pid().map(|pid| (id.clone(), pid)))
             .collect()
     }
+
+    /// Remove every container owned by a live connection. Called on app exit,
+    /// where the normal disconnect path doesn't run.
+    pub async fn remove_containers(&self) {
+        let handles: Vec<ContainerHandle> = self
+            .clients
+            .values()
+            .filter_map(|client| client.container().cloned())
+            .collect();
+        futures::future::join_all(handles.iter().map(|h| h.remove())).await;
+    }
+
+    /// End every live HTTP session. Called on app exit alongside
+    /// `remove_containers`.
+    pub async fn close_sessions(&self) {
+        futures::future::join_all(self.clients.values().map(|c| c.close_session())).await;
+    }
 }
 
 pub type SharedConnections = Mutex<McpConnections>;
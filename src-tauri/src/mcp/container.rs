@@ -0,0 +1,512 @@
+//! Run MCP servers inside Docker or Podman containers.
+//!
+//! Stdio-mode containers are started with `run -i` and driven through the
+//! regular [`StdioTransport`](crate::mcp::transport::StdioTransport). HTTP-mode
+//! containers are started detached with their port published on localhost.
+//! Every container gets a deterministic name derived from the server ID so it
+//! can be removed on disconnect, on app exit, and before the next start.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+use crate::mcp::sandbox::{expand_home, find_in_path};
+use crate::state::{ContainerConfig, ContainerMode, ContainerRuntime};
+
+const DEFAULT_TAG: &str = "latest";
+const DEFAULT_HTTP_PATH: &str = "/mcp";
+const HTTP_READY_TIMEOUT_SECS: u64 = 60;
+/// Number of container log lines to include when a container dies during startup.
+const LOG_TAIL_LINES: &str = "20";
+
+/// A container owned by Agent Hub, identified by runtime binary and name.
+#[derive(Debug, Clone)]
+pub struct ContainerHandle {
+    pub runtime: String,
+    pub name: String,
+}
+
+impl ContainerHandle {
+    pub fn new(runtime: String, server_id: &str) -> Self {
+        Self {
+            runtime,
+            name: container_name(server_id),
+        }
+    }
+
+    /// Force-remove the container (best-effort). `rm -f` also stops it.
+    pub async fn remove(&self) {
+        match tokio::process::Command::new(&self.runtime)
+            .args(["rm", "-f", &self.name])
+            .output()
+            .await
+        {
+            Ok(output) if output.status.success() => {
+                debug!("Removed container {}", self.name);
+            }
+            // Non-zero exit means there was no such container — nothing to clean up.
+            Ok(_) => {}
+            Err(e) => warn!("Failed to remove container {}: {e}", self.name),
+        }
+    }
+
+    async fn is_running(&self) -> bool {
+        tokio::process::Command::new(&self.runtime)
+            .args(["inspect", "-f", "{{.State.Running}}", &self.name])
+            .output()
+            .await
+            .map(|o| o.status.success() && String::from_utf8_lossy(&o.stdout).trim() == "true")
+            .unwrap_or(false)
+    }
+
+    async fn tail_logs(&self) -> String {
+        match tokio::process::Command::new(&self.runtime)
+            .args(["logs", "--tail", LOG_TAIL_LINES, &self.name])
+            .output()
+            .await
+        {
+            Ok(output) => {
+                let mut logs = String::from_utf8_lossy(&output.stdout).into_owned();
+                logs.push_str(&String::from_utf8_lossy(&output.stderr));
+                logs.trim().to_string()
+            }
+            Err(_) => String::new(),
+        }
+    }
+}
+
+/// Find the container runtime binary. `Auto` prefers docker and falls back to podman.
+pub fn resolve_runtime(preference: ContainerRuntime) -> Result<String, AppError> {
+    let candidates: &[&str] = match preference {
+        ContainerRuntime::Auto => &["docker", "podman"],
+        ContainerRuntime::Docker => &["docker"],
+        ContainerRuntime::Podman => &["podman"],
+    };
+    candidates
+        .iter()
+        .find_map(|name| find_in_path(name))
+        .map(|p| p.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            AppError::DependencyNotFound(format!(
+                "{} (required for container servers)",
+                candidates.join(" or ")
+            ))
+        })
+}
+
+/// Deterministic container name for a server.
+pub fn container_name(server_id: &str) -> String {
+    let safe: String = server_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("agent-hub-{safe}")
+}
+
+/// Full image reference (`image:tag`). An image that already carries a tag or
+/// digest is used as-is.
+pub fn image_ref(config: &ContainerConfig) -> String {
+    let image = config.image.trim();
+    let last_segment = image.rsplit('/').next().unwrap_or(image);
+    if image.contains('@') || last_segment.contains(':') {
+        return image.to_string();
+    }
+    let tag = config
+        .tag
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or(DEFAULT_TAG);
+    format!("{image}:{tag}")
+}
+
+/// Build the `run` arguments (everything after the runtime binary).
+///
+/// `server_id` names and labels the container so Agent Hub can clean it up;
+/// pass `None` for commands handed to other tools. `host_port` is the localhost
+/// port to publish in HTTP mode. Env values are not put on the command line:
+/// `-e KEY` makes the runtime copy the value from its own environment, which
+/// keeps secrets out of the process list.
+pub fn run_args(
+    config: &ContainerConfig,
+    server_id: Option<&str>,
+    args: &[String],
+    env: &HashMap<String, String>,
+    host_port: Option<u16>,
+) -> Result<Vec<String>, AppError> {
+    let mut argv: Vec<String> = vec!["run".into(), "--rm".into()];
+    if let Some(id) = server_id {
+        argv.push("--name".into());
+        argv.push(container_name(id));
+        argv.push("--label".into());
+        argv.push(format!("agent-hub.server={id}"));
+    }
+
+    match config.mode {
+        ContainerMode::Stdio => argv.push("-i".into()),
+        ContainerMode::Http => {
+            let container_port = config.port.ok_or_else(|| {
+                AppError::Validation(
+                    "HTTP container servers need the port the server listens on".into(),
+                )
+            })?;
+            let host_port = host_port.unwrap_or(container_port);
+            argv.push("-d".into());
+            argv.push("-p".into());
+            argv.push(format!("127.0.0.1:{host_port}:{container_port}"));
+        }
+    }
+
+    for volume in &config.volumes {
+        let mut spec = format!("{}:{}", expand_home(&volume.host), volume.container);
+        if volume.read_only {
+            spec.push_str(":ro");
+        }
+        argv.push("-v".into());
+        argv.push(spec);
+    }
+
+    if let Some(network) = config.network.as_deref().filter(|n| !n.is_empty()) {
+        argv.push("--network".into());
+        argv.push(network.to_string());
+    }
+
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort();
+    for key in keys {
+        argv.push("-e".into());
+        argv.push(key.clone());
+    }
+
+    argv.push(image_ref(config));
+    argv.extend(args.iter().cloned());
+    Ok(argv)
+}
+
+/// Command line for running a stdio container outside Agent Hub (native
+/// integration configs). HTTP-mode containers need Agent Hub to start them,
+/// so they have no standalone equivalent.
+pub fn native_command(
+    config: &ContainerConfig,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Option<(String, Vec<String>)> {
+    if config.mode != ContainerMode::Stdio {
+        return None;
+    }
+    let runtime = match config.runtime {
+        ContainerRuntime::Docker => "docker",
+        ContainerRuntime::Podman => "podman",
+        ContainerRuntime::Auto => {
+            if find_in_path("docker").is_none() && find_in_path("podman").is_some() {
+                "podman"
+            } else {
+                "docker"
+            }
+        }
+    };
+    let argv = run_args(config, None, args, env, None).ok()?;
+    Some((runtime.to_string(), argv))
+}
+
+fn emit_pull_progress(app: &AppHandle, server_id: &str, image: &str, message: &str) {
+    let _ = app.emit(
+        "container-pull-progress",
+        serde_json::json!({ "serverId": server_id, "image": image, "message": message }),
+    );
+}
+
+/// Forward each output line of a pull as a progress event, returning the last few
+/// lines for error context.
+async fn forward_pull_output<R: AsyncRead + Unpin>(
+    reader: R,
+    app: &AppHandle,
+    server_id: &str,
+    image: &str,
+) -> Vec<String> {
+    let mut tail = Vec::new();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        emit_pull_progress(app, server_id, image, line);
+        tail.push(line.to_string());
+        if tail.len() > 5 {
+            tail.remove(0);
+        }
+    }
+    tail
+}
+
+/// Pull the image unless it is already present locally, emitting
+/// `container-pull-progress` events as the runtime reports progress.
+pub async fn ensure_image(
+    app: &AppHandle,
+    server_id: &str,
+    runtime: &str,
+    image: &str,
+) -> Result<(), AppError> {
+    let present = tokio::process::Command::new(runtime)
+        .args(["image", "inspect", image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map(|s| s.success())
+        .unwrap_or(false);
+    if present {
+        return Ok(());
+    }
+
+    info!("Pulling image {image}");
+    emit_pull_progress(app, server_id, image, &format!("Pulling {image}..."));
+
+    let mut child = tokio::process::Command::new(runtime)
+        .args(["pull", image])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::ConnectionFailed(format!("Failed to run {runtime} pull: {e}")))?;
+
+    // Docker reports progress on stdout, podman on stderr.
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let (_, stderr_tail) = tokio::join!(
+        forward_pull_output(stdout, app, server_id, image),
+        forward_pull_output(stderr, app, server_id, image),
+    );
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| AppError::ConnectionFailed(format!("Failed to pull {image}: {e}")))?;
+    if !status.success() {
+        return Err(AppError::ConnectionFailed(format!(
+            "Failed to pull {image}: {}",
+            stderr_tail.join("\n")
+        )));
+    }
+
+    emit_pull_progress(app, server_id, image, &format!("Pulled {image}"));
+    Ok(())
+}
+
+/// Ask the OS for a free localhost port to publish the container's HTTP port on.
+fn free_local_port() -> Result<u16, AppError> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// Start an HTTP-mode container detached and wait until its endpoint answers.
+/// Returns the endpoint URL. The container is removed if it fails to come up.
+pub async fn start_http(
+    handle: &ContainerHandle,
+    config: &ContainerConfig,
+    server_id: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+) -> Result<String, AppError> {
+    let host_port = free_local_port()?;
+    let argv = run_args(config, Some(server_id), args, env, Some(host_port))?;
+    info!(
+        "Starting container {}: {} {}",
+        handle.name,
+        handle.runtime,
+        argv.join(" ")
+    );
+
+    let output = tokio::process::Command::new(&handle.runtime)
+        .args(&argv)
+        .envs(env)
+        .output()
+        .await
+        .map_err(|e| AppError::ConnectionFailed(format!("Failed to start container: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::ConnectionFailed(format!(
+            "Failed to start container: {}",
+            stderr.trim()
+        )));
+    }
+
+    let path = config.path.as_deref().unwrap_or(DEFAULT_HTTP_PATH);
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    };
+    let url = format!("http://127.0.0.1:{host_port}{path}");
+
+    if let Err(e) = wait_for_http(handle, &url).await {
+        handle.remove().await;
+        return Err(e);
+    }
+    Ok(url)
+}
+
+/// Poll the endpoint until it answers any HTTP request. A TCP check isn't enough:
+/// the runtime's port proxy accepts connections before the server is listening.
+async fn wait_for_http(handle: &ContainerHandle, url: &str) -> Result<(), AppError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|e| AppError::Transport(format!("Failed to build HTTP client: {e}")))?;
+    let deadline = Instant::now() + Duration::from_secs(HTTP_READY_TIMEOUT_SECS);
+
+    while Instant::now() < deadline {
+        if client.get(url).send().await.is_ok() {
+            return Ok(());
+        }
+        if !handle.is_running().await {
+            let logs = handle.tail_logs().await;
+            return Err(AppError::ConnectionFailed(if logs.is_empty() {
+                format!("Container {} exited during startup", handle.name)
+            } else {
+                format!("Container {} exited during startup:\n{logs}", handle.name)
+            }));
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    Err(AppError::ConnectionFailed(format!(
+        "Container {} did not serve {url} within {HTTP_READY_TIMEOUT_SECS}s",
+        handle.name
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::VolumeMount;
+
+    fn config(image: &str) -> ContainerConfig {
+        ContainerConfig {
+            runtime: ContainerRuntime::Auto,
+            image: image.into(),
+            tag: None,
+            volumes: Vec::new(),
+            network: None,
+            mode: ContainerMode::Stdio,
+            port: None,
+            path: None,
+        }
+    }
+
+    #[test]
+    fn image_ref_defaults_to_latest() {
+        assert_eq!(image_ref(&config("mcp/fetch")), "mcp/fetch:latest");
+    }
+
+    #[test]
+    fn image_ref_uses_tag() {
+        let mut c = config("ghcr.io/org/server");
+        c.tag = Some("1.2".into());
+        assert_eq!(image_ref(&c), "ghcr.io/org/server:1.2");
+    }
+
+    #[test]
+    fn image_ref_keeps_explicit_tag_and_registry_port() {
+        let mut c = config("localhost:5000/server:dev");
+        c.tag = Some("ignored".into());
+        assert_eq!(image_ref(&c), "localhost:5000/server:dev");
+        assert_eq!(
+            image_ref(&config("localhost:5000/server")),
+            "localhost:5000/server:latest"
+        );
+        assert_eq!(
+            image_ref(&config("mcp/fetch@sha256:abc")),
+            "mcp/fetch@sha256:abc"
+        );
+    }
+
+    #[test]
+    fn container_name_is_sanitized() {
+        assert_eq!(container_name("abc-123"), "agent-hub-abc-123");
+        assert_eq!(container_name("a/b c"), "agent-hub-a-b-c");
+    }
+
+    #[test]
+    fn stdio_run_args() {
+        let mut c = config("mcp/fetch");
+        c.network = Some("none".into());
+        c.volumes.push(VolumeMount {
+            host: "/data".into(),
+            container: "/mnt/data".into(),
+            read_only: true,
+        });
+        let env = HashMap::from([
+            ("TOKEN".to_string(), "secret".to_string()),
+            ("A".to_string(), "1".to_string()),
+        ]);
+        let argv = run_args(&c, Some("srv"), &["--verbose".into()], &env, None).unwrap();
+        assert_eq!(
+            argv,
+            [
+                "run",
+                "--rm",
+                "--name",
+                "agent-hub-srv",
+                "--label",
+                "agent-hub.server=srv",
+                "-i",
+                "-v",
+                "/data:/mnt/data:ro",
+                "--network",
+                "none",
+                "-e",
+                "A",
+                "-e",
+                "TOKEN",
+                "mcp/fetch:latest",
+                "--verbose",
+            ]
+        );
+        assert!(!argv.iter().any(|a| a.contains("secret")));
+    }
+
+    #[test]
+    fn http_run_args_publish_on_localhost() {
+        let mut c = config("mcp/fetch");
+        c.mode = ContainerMode::Http;
+        c.port = Some(8000);
+        let argv = run_args(&c, Some("srv"), &[], &HashMap::new(), Some(41234)).unwrap();
+        assert!(argv.windows(2).any(|w| w == ["-p", "127.0.0.1:41234:8000"]));
+        assert!(argv.contains(&"-d".to_string()));
+        assert!(!argv.contains(&"-i".to_string()));
+    }
+
+    #[test]
+    fn native_command_is_unnamed_and_stdio_only() {
+        let c = config("mcp/fetch");
+        let (_, argv) = native_command(&c, &[], &HashMap::new()).unwrap();
+        assert_eq!(argv, ["run", "--rm", "-i", "mcp/fetch:latest"]);
+
+        let mut http = config("mcp/fetch");
+        http.mode = ContainerMode::Http;
+        http.port = Some(8000);
+        assert!(native_command(&http, &[], &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn http_mode_requires_port() {
+        let mut c = config("mcp/fetch");
+        c.mode = ContainerMode::Http;
+        assert!(matches!(
+            run_args(&c, Some("srv"), &[], &HashMap::new(), None),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
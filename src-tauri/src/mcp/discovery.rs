@@ -1,21 +1,41 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 
 use axum::extract::{Query, State as AxumState};
 use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
 use axum::Json;
+use futures::stream::Stream;
 use serde_json::Value;
-use tauri::Manager;
+use sha2::{Digest, Sha256};
+use tokio::sync::broadcast;
 use tokio::time::Instant;
 use tracing::{error, info};
 
-use crate::mcp::client::SharedConnections;
+use crate::error::AppError;
 use crate::mcp::http_common::{
     accepted_response, client_accepts_sse, json_response, mcp_response, negotiate_version,
     new_session_id, validate_origin,
 };
-use crate::mcp::proxy::{make_error_response, record_tool_stats, ProxyAppState};
-use crate::state::SharedState;
+use crate::mcp::proxy::{make_error_response, ProxyAppState};
+use crate::mcp::search::fuse;
+use crate::state::{AppState, McpTool, PinnedTool, SearchMode, ServerConfig, ServerStatus};
+
+/// Pseudo server ID under which discovery endpoint traffic is captured, and
+/// sent on the notify channel when the pinned tools may have changed.
+pub(crate) const DISCOVERY_ID: &str = "discovery";
+
+/// Joins server ID and tool name in a pinned tool's listed name.
+const PINNED_SEPARATOR: &str = "__";
+/// Server IDs longer than this (e.g. UUIDs) are listed as a short hash.
+const MAX_PINNED_PREFIX_LEN: usize = 16;
+/// Clients reject tool names outside `^[a-zA-Z0-9_-]{1,64}$`.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Results returned by `discover_tools` when the caller doesn't pass a limit.
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 50;
 
 /// Handle POST requests to `/mcp/discovery` — the single discovery endpoint.
 pub(crate) async fn handle_discovery_post(
@@ -31,8 +51,7 @@ pub(crate) async fn handle_discovery_post(
 
     // Check if discovery mode is enabled
     {
-        let app_state = state.app_handle.state::<SharedState>();
-        let s = app_state.lock().unwrap();
+        let s = state.backend.state().lock().unwrap();
         if !s.tool_discovery_enabled {
             let resp = make_error_response(
                 body.get("id").cloned(),
@@ -57,6 +76,10 @@ pub(crate) async fn handle_discovery_post(
         .and_then(|v| v.to_str().ok())
         .map(String::from);
 
+    let exchange = state
+        .backend
+        .capture_request(DISCOVERY_ID, req_session.as_deref(), &body);
+
     // Notifications (no id) get 202 Accepted with no body
     if id.is_none() {
         return accepted_response(req_session.as_deref());
@@ -64,7 +87,7 @@ pub(crate) async fn handle_discovery_post(
 
     info!("Discovery endpoint: {method}");
 
-    match method {
+    let (response, session) = match method {
         "initialize" => {
             // Negotiate protocol version from client's requested version
             let client_version = params
@@ -84,7 +107,7 @@ pub(crate) async fn handle_discovery_post(
                     "protocolVersion": negotiated,
                     "capabilities": {
                         "tools": {
-                            "listChanged": false
+                            "listChanged": true
                         }
                     },
                     "serverInfo": {
@@ -93,35 +116,212 @@ pub(crate) async fn handle_discovery_post(
                     }
                 }
             });
-            mcp_response(&response, Some(&session_id), use_sse)
-        }
-        "tools/list" => {
-            let response = handle_tools_list(id);
-            mcp_response(&response, req_session.as_deref(), use_sse)
-        }
-        "tools/call" => {
-            let response = handle_tools_call(id, params, &client_id, &state).await;
-            mcp_response(&response, req_session.as_deref(), use_sse)
+            (response, Some(session_id))
         }
-        _ => {
-            let response =
-                make_error_response(id, -32601, &format!("Method not found: {method}"));
-            mcp_response(&response, req_session.as_deref(), use_sse)
+        "tools/list" => (handle_tools_list(id, &client_id, &state).await, req_session),
+        "tools/call" => (
+            handle_tools_call(id, params, &client_id, &state).await,
+            req_session,
+        ),
+        _ => (
+            make_error_response(id, -32601, &format!("Method not found: {method}")),
+            req_session,
+        ),
+    };
+
+    state
+        .backend
+        .capture_response(DISCOVERY_ID, session.as_deref(), &response, exchange);
+    mcp_response(&response, session.as_deref(), use_sse)
+}
+
+/// Handle GET requests to `/mcp/discovery` — an SSE stream that tells the
+/// client when its pinned tools change.
+pub(crate) async fn handle_discovery_get(
+    AxumState(state): AxumState<ProxyAppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let client_id = query.get("client").cloned().unwrap_or_default();
+    let mut rx = state.notify_tx.subscribe();
+    let mut listed = pinned_tools(&state, &client_id).await;
+    let stream = async_stream::stream! {
+        loop {
+            let changed_id = match rx.recv().await {
+                Ok(changed_id) => Some(changed_id),
+                Err(broadcast::error::RecvError::Closed) => break,
+                Err(broadcast::error::RecvError::Lagged(_)) => None,
+            };
+            let pinned = pinned_tools(&state, &client_id).await;
+            // A pinned tool's server reconnecting may have changed its schema
+            let schema_changed = changed_id
+                .is_none_or(|changed| listed.iter().any(|p| p.server_id == changed));
+            if pinned != listed || (schema_changed && !pinned.is_empty()) {
+                listed = pinned;
+                let notification = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/tools/list_changed"
+                });
+                yield Ok(Event::default().data(notification.to_string()));
+            }
         }
+    };
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Tell discovery clients a tool call was recorded, which can change the
+/// most-called tools they have pinned.
+pub(crate) fn usage_changed(state: &ProxyAppState) {
+    let _ = state.notify_tx.send(DISCOVERY_ID.to_string());
+}
+
+/// The tools listed directly for a client: explicit pins first, then the
+/// client's most-called tools.
+async fn pinned_tools(state: &ProxyAppState, client_id: &str) -> Vec<PinnedTool> {
+    let top_n = state
+        .backend
+        .state()
+        .lock()
+        .unwrap()
+        .discovery_pinning
+        .top_n;
+    let counts = if top_n > 0 {
+        state.backend.tool_call_counts(client_id).await
+    } else {
+        HashMap::new()
+    };
+    let s = state.backend.state().lock().unwrap();
+    select_pinned(&s, &counts)
+}
+
+/// Pick pinned tools from what's connected. `counts` are calls per
+/// `(server ID, tool name)`.
+fn select_pinned(s: &AppState, counts: &HashMap<(String, String), u64>) -> Vec<PinnedTool> {
+    let available = |server_id: &str, tool_name: &str| {
+        s.servers
+            .iter()
+            .any(|srv| srv.id == server_id && discoverable(srv))
+            && known_tools(s, server_id)
+                .is_some_and(|tools| tools.iter().any(|t| t.name == tool_name))
+    };
+
+    let mut pinned: Vec<PinnedTool> = s
+        .discovery_pinning
+        .pinned
+        .iter()
+        .filter(|p| available(&p.server_id, &p.tool_name))
+        .cloned()
+        .collect();
+
+    let mut used: Vec<(&(String, String), &u64)> = counts
+        .iter()
+        .filter(|((server_id, tool_name), _)| available(server_id, tool_name))
+        .collect();
+    // Most calls first, then by name so the list is stable
+    used.sort_by(|(a, a_calls), (b, b_calls)| b_calls.cmp(a_calls).then_with(|| a.cmp(b)));
+    let most_used = used
+        .into_iter()
+        .map(|((server_id, tool_name), _)| PinnedTool {
+            server_id: server_id.clone(),
+            tool_name: tool_name.clone(),
+        })
+        .filter(|tool| !s.discovery_pinning.pinned.contains(tool))
+        .take(s.discovery_pinning.top_n)
+        .collect::<Vec<_>>();
+    pinned.extend(most_used);
+    pinned
+}
+
+/// The name a pinned tool is listed under, e.g. `github__create_issue`.
+/// Long server IDs become a short hash, other characters clients reject
+/// become `_`, and names over the length limit are cut short and end in a
+/// hash of the full name.
+fn pinned_name(server_id: &str, tool_name: &str) -> String {
+    let prefix = if server_id.len() <= MAX_PINNED_PREFIX_LEN {
+        server_id.to_string()
+    } else {
+        short_hash(server_id)
+    };
+    let name: String = format!("{prefix}{PINNED_SEPARATOR}{tool_name}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.len() <= MAX_TOOL_NAME_LEN {
+        return name;
     }
+    let hash = short_hash(&format!("{server_id}\n{tool_name}"));
+    format!("{}_{hash}", &name[..MAX_TOOL_NAME_LEN - hash.len() - 1])
+}
+
+fn short_hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The server and tool behind a pinned tool's listed name. Any known tool
+/// resolves, so calls still route after it drops out of the pinned set.
+fn resolve_pinned(state: &ProxyAppState, name: &str) -> Option<(String, String)> {
+    let s = state.backend.state().lock().unwrap();
+    s.servers
+        .iter()
+        .filter(|srv| discoverable(srv))
+        .find_map(|srv| {
+            known_tools(&s, &srv.id)?
+                .iter()
+                .find(|t| pinned_name(&srv.id, &t.name) == name)
+                .map(|t| (srv.id.clone(), t.name.clone()))
+        })
+}
+
+/// Whether discovery covers a server: unmanaged, and enabled or connected.
+fn discoverable(srv: &ServerConfig) -> bool {
+    srv.managed_by.is_none() && (srv.enabled || srv.status == Some(ServerStatus::Connected))
 }
 
-fn handle_tools_list(id: Option<Value>) -> Value {
-    let tools = vec![
+/// A server's tools: the live list if it's connected, else the last-known
+/// one from the tool catalog. Disabled tools are left out.
+fn known_tools<'a>(s: &'a AppState, server_id: &str) -> Option<Vec<&'a McpTool>> {
+    let tools = match s.connections.get(server_id) {
+        Some(conn) => &conn.tools,
+        None => s.tool_catalog.get(server_id)?,
+    };
+    let server = s.servers.iter().find(|srv| srv.id == server_id);
+    Some(
+        tools
+            .iter()
+            .filter(|t| server.is_none_or(|srv| srv.tool_enabled(&t.name)))
+            .collect(),
+    )
+}
+
+async fn handle_tools_list(id: Option<Value>, client_id: &str, state: &ProxyAppState) -> Value {
+    let pinned = pinned_tools(state, client_id).await;
+
+    let mut tools = vec![
         serde_json::json!({
             "name": "discover_tools",
-            "description": "Search for available tools across all connected MCP servers. Returns matching tools with their full input schemas so you can call them immediately via call_tool. Use this before calling a tool you haven't used yet.",
+            "description": "Search for tools across all MCP servers, including ones that aren't running (marked \"available\": false; call_tool starts them). Returns matching tools with their full input schemas so you can call them immediately via call_tool. Use this before calling a tool you haven't used yet.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "query": {
                         "type": "string",
-                        "description": "Search query — matches against tool names and descriptions. All terms must match (case-insensitive). Example: 'slack message' finds tools with both 'slack' and 'message' in their name or description."
+                        "description": "Search query in plain words — matched against tool names, titles, descriptions and parameter names. Results are ranked by relevance and not every term has to match; small typos are tolerated. Example: 'create github issue'."
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of results (default 20, max 50)."
+                    },
+                    "server": {
+                        "type": "string",
+                        "description": "Only search this server's tools, by server ID or name (from list_servers)."
                     }
                 },
                 "required": ["query"]
@@ -151,7 +351,7 @@ fn handle_tools_list(id: Option<Value>) -> Value {
         }),
         serde_json::json!({
             "name": "list_servers",
-            "description": "List all connected MCP servers and their available tool names. Use this to get an overview of what's available, then use discover_tools for details on specific tools.",
+            "description": "List all MCP servers, whether each is running, and their tool names. Use this to get an overview of what's available, then use discover_tools for details on specific tools.",
             "inputSchema": {
                 "type": "object",
                 "properties": {},
@@ -160,6 +360,26 @@ fn handle_tools_list(id: Option<Value>) -> Value {
         }),
     ];
 
+    let s = state.backend.state().lock().unwrap();
+    for pin in &pinned {
+        let Some(tool) = known_tools(&s, &pin.server_id)
+            .and_then(|tools| tools.into_iter().find(|t| t.name == pin.tool_name))
+        else {
+            continue;
+        };
+        let mut entry = serde_json::json!({
+            "name": pinned_name(&pin.server_id, &pin.tool_name),
+            "inputSchema": tool.input_schema,
+        });
+        if let Some(ref desc) = tool.description {
+            entry["description"] = Value::String(desc.clone());
+        }
+        if let Some(ref title) = tool.title {
+            entry["title"] = Value::String(title.clone());
+        }
+        tools.push(entry);
+    }
+
     serde_json::json!({
         "jsonrpc": "2.0",
         "id": id,
@@ -191,14 +411,25 @@ async fn handle_tools_call(
         .unwrap_or(serde_json::json!({}));
 
     match tool_name {
-        "discover_tools" => handle_discover_tools(id, &arguments, state),
+        "discover_tools" => handle_discover_tools(id, &arguments, state).await,
         "list_servers" => handle_list_servers(id, state),
         "call_tool" => handle_call_tool(id, &arguments, client_id, state).await,
-        _ => make_error_response(
-            id,
-            -32602,
-            &format!("Unknown discovery tool: {tool_name}. Available: discover_tools, call_tool, list_servers"),
-        ),
+        _ => match resolve_pinned(state, tool_name) {
+            // Pinned tools route straight to their server
+            Some((server_id, tool_name)) => {
+                let arguments = serde_json::json!({
+                    "server_id": server_id,
+                    "tool_name": tool_name,
+                    "arguments": arguments,
+                });
+                handle_call_tool(id, &arguments, client_id, state).await
+            }
+            None => make_error_response(
+                id,
+                -32602,
+                &format!("Unknown discovery tool: {tool_name}. Available: discover_tools, call_tool, list_servers"),
+            ),
+        },
     }
 }
 
@@ -233,8 +464,12 @@ fn summarize_params(schema: &Option<Value>) -> String {
     parts.join(", ")
 }
 
-/// Search across all connected servers' tools by keyword.
-fn handle_discover_tools(id: Option<Value>, arguments: &Value, state: &ProxyAppState) -> Value {
+/// Search across all connected servers' tools, best matches first.
+async fn handle_discover_tools(
+    id: Option<Value>,
+    arguments: &Value,
+    state: &ProxyAppState,
+) -> Value {
     let query = arguments
         .get("query")
         .and_then(|q| q.as_str())
@@ -244,47 +479,83 @@ fn handle_discover_tools(id: Option<Value>, arguments: &Value, state: &ProxyAppS
         return make_error_response(id, -32602, "Missing required argument: query");
     }
 
-    let terms: Vec<String> = query.to_lowercase().split_whitespace().map(String::from).collect();
-
-    let app_state = state.app_handle.state::<SharedState>();
-    let s = app_state.lock().unwrap();
+    let limit = arguments
+        .get("limit")
+        .and_then(|l| l.as_u64())
+        .map_or(DEFAULT_SEARCH_LIMIT, |l| {
+            (l as usize).clamp(1, MAX_SEARCH_LIMIT)
+        });
+    let server_filter = arguments
+        .get("server")
+        .and_then(|s| s.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_lowercase);
 
-    let mut matches = Vec::new();
+    // Servers with known tools, optionally narrowed to one by ID or name
+    let (mode, searchable): (SearchMode, HashSet<String>) = {
+        let s = state.backend.state().lock().unwrap();
+        let searchable = s
+            .servers
+            .iter()
+            .filter(|srv| discoverable(srv) && known_tools(&s, &srv.id).is_some())
+            .filter(|srv| {
+                server_filter
+                    .as_deref()
+                    .is_none_or(|f| srv.id.to_lowercase() == f || srv.name.to_lowercase() == f)
+            })
+            .map(|srv| srv.id.clone())
+            .collect();
+        (s.discovery_search_mode, searchable)
+    };
 
-    for srv in &s.servers {
-        if srv.status != Some(crate::state::ServerStatus::Connected) {
-            continue;
+    let keyword = |limit| {
+        state
+            .backend
+            .tool_index()
+            .search(query, limit, |server_id| searchable.contains(server_id))
+    };
+    // Semantic modes fall back to keyword ranking when embeddings are unavailable
+    let hits = match mode {
+        SearchMode::Keyword => keyword(limit),
+        SearchMode::Semantic => {
+            let semantic = state.backend.semantic_search(query, limit, &searchable);
+            semantic.await.unwrap_or_else(|| keyword(limit))
         }
-        if srv.managed_by.is_some() {
-            continue;
+        SearchMode::Hybrid => {
+            match state
+                .backend
+                .semantic_search(query, MAX_SEARCH_LIMIT, &searchable)
+                .await
+            {
+                Some(semantic) => fuse(&keyword(MAX_SEARCH_LIMIT), &semantic, limit),
+                None => keyword(limit),
+            }
         }
+    };
 
-        let conn = match s.connections.get(&srv.id) {
-            Some(c) => c,
-            None => continue,
-        };
+    let s = state.backend.state().lock().unwrap();
+    let servers: HashMap<&str, &ServerConfig> = s
+        .servers
+        .iter()
+        .filter(|srv| searchable.contains(&srv.id))
+        .map(|srv| (srv.id.as_str(), srv))
+        .collect();
 
-        for tool in &conn.tools {
-            let name_lower = tool.name.to_lowercase();
-            let desc_lower = tool
-                .description
-                .as_deref()
-                .unwrap_or("")
-                .to_lowercase();
-            let haystack = format!("{name_lower} {desc_lower}");
-
-            let all_match = terms.iter().all(|term| haystack.contains(term.as_str()));
-            if !all_match {
-                continue;
-            }
+    let matches: Vec<Value> = hits
+        .iter()
+        .filter_map(|hit| {
+            let srv = servers.get(hit.server_id.as_str())?;
+            let tool = known_tools(&s, &srv.id)?
+                .into_iter()
+                .find(|t| t.name == hit.tool_name)?;
 
-            let param_summary = summarize_params(&tool.input_schema);
             let mut entry = serde_json::json!({
                 "server_id": srv.id,
                 "server_name": srv.name,
                 "name": tool.name,
-                "parameters": param_summary,
+                "parameters": summarize_params(&tool.input_schema),
                 "inputSchema": tool.input_schema,
+                "available": srv.status == Some(ServerStatus::Connected),
             });
             if let Some(ref desc) = tool.description {
                 entry["description"] = Value::String(desc.clone());
@@ -292,17 +563,9 @@ fn handle_discover_tools(id: Option<Value>, arguments: &Value, state: &ProxyAppS
             if let Some(ref title) = tool.title {
                 entry["title"] = Value::String(title.clone());
             }
-            matches.push(entry);
-
-            if matches.len() >= 20 {
-                break;
-            }
-        }
-
-        if matches.len() >= 20 {
-            break;
-        }
-    }
+            Some(entry)
+        })
+        .collect();
 
     let result_text = if matches.is_empty() {
         format!("No tools found matching '{query}'. Try broader terms or use list_servers to see available servers.")
@@ -322,37 +585,33 @@ fn handle_discover_tools(id: Option<Value>, arguments: &Value, state: &ProxyAppS
     })
 }
 
-/// List all connected servers and their tool names.
+/// List all servers with known tools, connected or not, and their tool names.
 fn handle_list_servers(id: Option<Value>, state: &ProxyAppState) -> Value {
-    let app_state = state.app_handle.state::<SharedState>();
-    let s = app_state.lock().unwrap();
+    let s = state.backend.state().lock().unwrap();
 
     let mut servers = Vec::new();
 
     for srv in &s.servers {
-        if srv.status != Some(crate::state::ServerStatus::Connected) {
+        if !discoverable(srv) {
             continue;
         }
-        if srv.managed_by.is_some() {
+        let Some(tools) = known_tools(&s, &srv.id) else {
             continue;
-        }
+        };
 
-        let tool_names: Vec<String> = s
-            .connections
-            .get(&srv.id)
-            .map(|c| c.tools.iter().map(|t| t.name.clone()).collect())
-            .unwrap_or_default();
+        let tool_names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
 
         servers.push(serde_json::json!({
             "server_id": srv.id,
             "server_name": srv.name,
+            "available": srv.status == Some(ServerStatus::Connected),
             "tool_count": tool_names.len(),
             "tools": tool_names,
         }));
     }
 
     let result_text = if servers.is_empty() {
-        "No servers are currently connected.".to_string()
+        "No servers have connected yet.".to_string()
     } else {
         serde_json::to_string_pretty(&servers).unwrap_or_default()
     };
@@ -371,13 +630,42 @@ fn handle_list_servers(id: Option<Value>, state: &ProxyAppState) -> Value {
 
 /// Look up a tool's inputSchema from connection state.
 fn lookup_tool_schema(state: &ProxyAppState, server_id: &str, tool_name: &str) -> Option<Value> {
-    let app_state = state.app_handle.state::<SharedState>();
-    let s = app_state.lock().unwrap();
-    let conn = s.connections.get(server_id)?;
-    let tool = conn.tools.iter().find(|t| t.name == tool_name)?;
+    let s = state.backend.state().lock().unwrap();
+    let tool = known_tools(&s, server_id)?
+        .into_iter()
+        .find(|t| t.name == tool_name)?;
     tool.input_schema.clone()
 }
 
+/// Build an error result the LLM reads as the tool's output.
+fn tool_error(id: Option<Value>, text: &str) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": {
+            "content": [{
+                "type": "text",
+                "text": text
+            }],
+            "isError": true
+        }
+    })
+}
+
+/// Why a server couldn't be started for a tool call, phrased for the LLM to
+/// relay to the user.
+fn connect_failure(server_name: &str, error: &AppError) -> String {
+    match error {
+        AppError::AuthRequired(_) | AppError::AuthExpired(_) => format!(
+            "Server '{server_name}' requires authentication. Ask the user to click Authorize on it in Agent Hub."
+        ),
+        AppError::DependencyNotFound(dependency) => format!(
+            "Server '{server_name}' can't start because '{dependency}' isn't installed. Ask the user to install it."
+        ),
+        _ => format!("Server '{server_name}' failed to start: {error}"),
+    }
+}
+
 /// Build an error result that includes the tool's schema so the LLM can self-correct.
 fn tool_error_with_schema(
     id: Option<Value>,
@@ -430,12 +718,17 @@ async fn handle_call_tool(
         .cloned()
         .unwrap_or(serde_json::json!({}));
 
-    // Look up server name and managed status
-    let (server_name, is_managed) = {
-        let app_state = state.app_handle.state::<SharedState>();
-        let s = app_state.lock().unwrap();
+    // Look up server name, managed status, whether it's running and the tool is allowed
+    let (server_name, is_managed, enabled, connected, tool_enabled) = {
+        let s = state.backend.state().lock().unwrap();
         match s.servers.iter().find(|srv| srv.id == server_id) {
-            Some(srv) => (srv.name.clone(), srv.managed_by.is_some()),
+            Some(srv) => (
+                srv.name.clone(),
+                srv.managed_by.is_some(),
+                srv.enabled,
+                srv.status == Some(ServerStatus::Connected),
+                srv.tool_enabled(&tool_name),
+            ),
             None => {
                 return make_error_response(
                     id,
@@ -447,42 +740,55 @@ async fn handle_call_tool(
     };
 
     if is_managed {
-        return serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "result": {
-                "content": [{
-                    "type": "text",
-                    "text": format!(
-                        "Server '{}' is managed externally and its tools should be called directly through its own MCP connection, not via discovery.",
-                        server_name
-                    )
-                }],
-                "isError": true
-            }
-        });
+        return tool_error(
+            id,
+            &format!(
+                "Server '{}' is managed externally and its tools should be called directly through its own MCP connection, not via discovery.",
+                server_name
+            ),
+        );
     }
 
-    // Get the MCP client
-    let connections = state.app_handle.state::<SharedConnections>();
-    let client = {
-        let conns = connections.lock().await;
-        match conns.get(&server_id).cloned() {
-            Some(c) => c,
-            None => {
-                return make_error_response(
-                    id,
-                    -32602,
-                    &format!("Server '{server_name}' is not connected"),
-                );
-            }
+    if !tool_enabled {
+        return tool_error(
+            id,
+            &format!(
+                "Tool '{tool_name}' is disabled on server '{server_name}'. Ask the user to enable it in Agent Hub."
+            ),
+        );
+    }
+
+    // Discovery lists last-known tools of servers that aren't running; start them on demand
+    if !connected {
+        if !enabled {
+            return tool_error(
+                id,
+                &format!(
+                    "Server '{server_name}' is disabled. Ask the user to enable it in Agent Hub."
+                ),
+            );
         }
-    };
+        info!("Discovery: connecting {server_name} on demand");
+        match state.backend.connect(&server_id).await {
+            // Another request is already connecting it
+            Ok(()) | Err(AppError::AlreadyConnected(_)) => {}
+            Err(e) => return tool_error(id, &connect_failure(&server_name, &e)),
+        }
+    }
 
     info!("Discovery tool call: {server_name}.{tool_name}");
 
     let start = Instant::now();
-    let call_result = client.call_tool(&tool_name, tool_arguments).await;
+    let Some(call_result) = state
+        .backend
+        .call_tool(&server_id, &tool_name, tool_arguments)
+        .await
+    else {
+        return tool_error(
+            id,
+            &format!("Server '{server_name}' is still starting. Try again in a moment."),
+        );
+    };
     let duration_ms = start.elapsed().as_millis() as u64;
 
     let (response, is_error) = match call_result {
@@ -538,15 +844,371 @@ async fn handle_call_tool(
         }
     };
 
-    record_tool_stats(
-        &state.app_handle,
-        &server_id,
-        &tool_name,
-        client_id,
-        duration_ms,
-        is_error,
-    )
-    .await;
+    state
+        .backend
+        .record_call(&server_id, &tool_name, client_id, duration_ms, is_error)
+        .await;
+    usage_changed(state);
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use serde_json::json;
+
+    use crate::mcp::http_transport::HttpTransport;
+    use crate::mcp::test_support::{self, Reply, ScriptedServer, TestBackend};
+    use crate::state::ToolPinning;
+
+    async fn call(transport: &HttpTransport, tool: &str, arguments: Value) -> Value {
+        let params = json!({ "name": tool, "arguments": arguments });
+        let resp = transport
+            .send_request("tools/call", Some(params))
+            .await
+            .unwrap();
+        resp.result.unwrap()
+    }
+
+    fn text(result: &Value) -> &str {
+        result["content"][0]["text"].as_str().unwrap()
+    }
+
+    async fn tool_names(transport: &HttpTransport) -> Vec<String> {
+        let list = transport
+            .send_request("tools/list", None)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        list["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn discovers_and_calls_tools_across_servers() {
+        let github = ScriptedServer::new()
+            .tool("create_issue", Reply::Echo)
+            .tool("close_issue", Reply::ToolError("issue is locked".into()));
+        let slack = ScriptedServer::new().tool("post_message", Reply::Text("sent".into()));
+        let backend = Arc::new(
+            TestBackend::new()
+                .server("github", "GitHub", Some(github))
+                .server("slack", "Slack", Some(slack)),
+        );
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let transport =
+            HttpTransport::connect(&format!("{base}/mcp/discovery"), HashMap::new(), None, None)
+                .await
+                .unwrap();
+
+        let err = transport
+            .send_request("initialize", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Protocol(msg) if msg.starts_with("-32001")));
+        backend.set_discovery(true);
+        transport.send_request("initialize", None).await.unwrap();
+
+        let list = transport
+            .send_request("tools/list", None)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(list["tools"].as_array().unwrap().len(), 3);
+
+        let servers = call(&transport, "list_servers", json!({})).await;
+        assert!(text(&servers).contains("\"post_message\""));
+
+        let found = call(
+            &transport,
+            "discover_tools",
+            json!({ "query": "creat isue" }),
+        )
+        .await;
+        let found: Vec<Value> = serde_json::from_str(text(&found)).unwrap();
+        assert_eq!(found[0]["name"], "create_issue");
+        assert_eq!(found[0]["server_id"], "github");
+
+        // Without embeddings, hybrid ranking falls back to keyword search
+        backend.set_search_mode(SearchMode::Hybrid);
+        let found = call(
+            &transport,
+            "discover_tools",
+            json!({ "query": "creat isue" }),
+        )
+        .await;
+        let found: Vec<Value> = serde_json::from_str(text(&found)).unwrap();
+        assert_eq!(found[0]["name"], "create_issue");
+
+        let found = call(
+            &transport,
+            "discover_tools",
+            json!({ "query": "scripted", "server": "slack", "limit": 5 }),
+        )
+        .await;
+        let found: Vec<Value> = serde_json::from_str(text(&found)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0]["name"], "post_message");
+
+        let created = call(
+            &transport,
+            "call_tool",
+            json!({ "server_id": "github", "tool_name": "create_issue", "arguments": { "title": "Bug" } }),
+        )
+        .await;
+        assert_eq!(text(&created), r#"{"title":"Bug"}"#);
+
+        // Tool errors carry the schema so the model can retry
+        let closed = call(
+            &transport,
+            "call_tool",
+            json!({ "server_id": "github", "tool_name": "close_issue" }),
+        )
+        .await;
+        assert_eq!(closed["isError"], true);
+        assert!(text(&closed).starts_with("issue is locked"));
+        assert!(text(&closed).contains("Expected inputSchema for 'close_issue'"));
+
+        assert_eq!(
+            backend.calls(),
+            [
+                ("github".to_string(), "create_issue".to_string(), false),
+                ("github".to_string(), "close_issue".to_string(), true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn pins_explicit_and_most_called_tools() {
+        let github = ScriptedServer::new()
+            .tool("create_issue", Reply::Echo)
+            .tool("close_issue", Reply::Text("closed".into()));
+        let slack = ScriptedServer::new().tool("post_message", Reply::Text("sent".into()));
+        let backend = Arc::new(
+            TestBackend::new()
+                .server("github", "GitHub", Some(github))
+                .server("slack", "Slack", Some(slack)),
+        );
+        backend.set_discovery(true);
+        backend.set_pinning(ToolPinning {
+            top_n: 1,
+            pinned: vec![PinnedTool {
+                server_id: "slack".into(),
+                tool_name: "post_message".into(),
+            }],
+        });
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let url = format!("{base}/mcp/discovery?client=cursor");
+        let transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+        transport.send_request("initialize", None).await.unwrap();
+
+        let meta = ["discover_tools", "call_tool", "list_servers"];
+        assert_eq!(
+            tool_names(&transport).await,
+            [&meta[..], &["slack__post_message"]].concat()
+        );
+
+        // A call changes the client's most-used tools and notifies it
+        let mut events = reqwest::get(&url).await.unwrap().bytes_stream();
+        let closed = call(
+            &transport,
+            "call_tool",
+            json!({ "server_id": "github", "tool_name": "close_issue" }),
+        )
+        .await;
+        assert_eq!(text(&closed), "closed");
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&event).contains("notifications/tools/list_changed"));
+        assert_eq!(
+            tool_names(&transport).await,
+            [&meta[..], &["slack__post_message", "github__close_issue"]].concat()
+        );
+
+        // Pinned names route straight to the server, as do other tools' names
+        let created = call(
+            &transport,
+            "github__create_issue",
+            json!({ "title": "Bug" }),
+        )
+        .await;
+        assert_eq!(text(&created), r#"{"title":"Bug"}"#);
+        let unknown = transport
+            .send_request("tools/call", Some(json!({ "name": "github__delete_repo" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(unknown, AppError::Protocol(msg) if msg.starts_with("-32602")));
+
+        // Usage is tracked per client
+        let other = HttpTransport::connect(
+            &format!("{base}/mcp/discovery?client=claude-code"),
+            HashMap::new(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        other.send_request("initialize", None).await.unwrap();
+        assert_eq!(
+            tool_names(&other).await,
+            [&meta[..], &["slack__post_message"]].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_names_fit_client_limits() {
+        let server_id = "3f2b8c1e-9a4d-4e6f-8b7a-1c2d3e4f5a6b";
+        let long_tool = "search_repository_issues_and_pull_requests_by_label_and_milestone";
+        let github = ScriptedServer::new()
+            .tool(long_tool, Reply::Echo)
+            .tool("create.issue", Reply::Text("created".into()));
+        let backend = Arc::new(TestBackend::new().server(server_id, "GitHub", Some(github)));
+        backend.set_discovery(true);
+        backend.set_pinning(ToolPinning {
+            top_n: 0,
+            pinned: [long_tool, "create.issue"]
+                .map(|tool_name| PinnedTool {
+                    server_id: server_id.into(),
+                    tool_name: tool_name.into(),
+                })
+                .to_vec(),
+        });
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let transport =
+            HttpTransport::connect(&format!("{base}/mcp/discovery"), HashMap::new(), None, None)
+                .await
+                .unwrap();
+        transport.send_request("initialize", None).await.unwrap();
+
+        let names = tool_names(&transport).await;
+        let pinned = &names[3..];
+        assert_eq!(pinned.len(), 2);
+        for name in pinned {
+            assert!(name.len() <= MAX_TOOL_NAME_LEN, "{name}");
+            assert!(
+                name.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+                "{name}"
+            );
+        }
+        assert_eq!(pinned[1], pinned_name(server_id, "create.issue"));
+
+        // The aliases route back to the original tools
+        let searched = call(&transport, &pinned[0], json!({ "label": "bug" })).await;
+        assert_eq!(text(&searched), r#"{"label":"bug"}"#);
+        let created = call(&transport, &pinned[1], json!({})).await;
+        assert_eq!(text(&created), "created");
+        assert_eq!(
+            backend.calls(),
+            [
+                (server_id.to_string(), long_tool.to_string(), false),
+                (server_id.to_string(), "create.issue".to_string(), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn leaves_out_disabled_tools() {
+        let github = ScriptedServer::new()
+            .tool("create_issue", Reply::Echo)
+            .tool("delete_repo", Reply::Echo);
+        let backend = Arc::new(TestBackend::new().server("github", "GitHub", Some(github)));
+        backend.set_discovery(true);
+        backend.disable_tools("github", &["delete_repo"]);
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let transport =
+            HttpTransport::connect(&format!("{base}/mcp/discovery"), HashMap::new(), None, None)
+                .await
+                .unwrap();
+        transport.send_request("initialize", None).await.unwrap();
+
+        let found = call(&transport, "discover_tools", json!({ "query": "repo" })).await;
+        assert!(!text(&found).contains("delete_repo"));
+        let servers = call(&transport, "list_servers", json!({})).await;
+        let servers: Vec<Value> = serde_json::from_str(text(&servers)).unwrap();
+        assert_eq!(servers[0]["tools"], json!(["create_issue"]));
+
+        let refused = call(
+            &transport,
+            "call_tool",
+            json!({ "server_id": "github", "tool_name": "delete_repo" }),
+        )
+        .await;
+        assert_eq!(refused["isError"], true);
+        assert!(text(&refused).contains("is disabled"));
+        assert!(backend.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reaches_disconnected_servers_through_the_catalog() {
+        let github = ScriptedServer::new().tool("create_issue", Reply::Echo);
+        let backend = Arc::new(
+            TestBackend::new()
+                .server("github", "GitHub", Some(github))
+                .server("jira", "Jira", None),
+        );
+        backend.set_discovery(true);
+        backend.disconnect("github");
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let transport =
+            HttpTransport::connect(&format!("{base}/mcp/discovery"), HashMap::new(), None, None)
+                .await
+                .unwrap();
+        transport.send_request("initialize", None).await.unwrap();
+
+        // Last-known tools stay discoverable, flagged as unavailable
+        let found = call(
+            &transport,
+            "discover_tools",
+            json!({ "query": "create issue" }),
+        )
+        .await;
+        let found: Vec<Value> = serde_json::from_str(text(&found)).unwrap();
+        assert_eq!(found[0]["name"], "create_issue");
+        assert_eq!(found[0]["available"], false);
+        let servers = call(&transport, "list_servers", json!({})).await;
+        let servers: Vec<Value> = serde_json::from_str(text(&servers)).unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0]["server_id"], "github");
+        assert_eq!(servers[0]["available"], false);
+
+        // Calling one connects its server first
+        let created = call(
+            &transport,
+            "call_tool",
+            json!({ "server_id": "github", "tool_name": "create_issue", "arguments": { "title": "Bug" } }),
+        )
+        .await;
+        assert_eq!(text(&created), r#"{"title":"Bug"}"#);
+        assert_eq!(backend.status("github"), Some(ServerStatus::Connected));
+
+        // A server that can't start says why
+        let failed = call(
+            &transport,
+            "call_tool",
+            json!({ "server_id": "jira", "tool_name": "create_ticket" }),
+        )
+        .await;
+        assert_eq!(failed["isError"], true);
+        assert_eq!(
+            text(&failed),
+            "Server 'Jira' can't start because 'npx' isn't installed. Ask the user to install it."
+        );
+    }
+}
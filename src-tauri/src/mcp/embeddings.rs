@@ -0,0 +1,519 @@
+//! Embeddings for semantic tool discovery.
+//!
+//! When a server connects, each of its tools is embedded with the configured
+//! provider (the same [`EmbeddingConfig`] memory uses). Vectors are cached on
+//! disk keyed by a hash of the model and the tool's text, so reconnecting or
+//! restarting only embeds tools that changed.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::mcp::search::SearchHit;
+use crate::persistence::load_openai_api_key;
+use crate::state::{EmbeddingConfig, EmbeddingProvider, McpTool, SearchMode, SharedState};
+
+const OPENAI_URL: &str = "https://api.openai.com/v1/embeddings";
+/// The memory feature's Ollama container publishes this port.
+const OLLAMA_URL: &str = "http://localhost:11434/api/embed";
+/// Inputs sent per embedding request.
+const BATCH_SIZE: usize = 64;
+/// Embedding the query is on the `discover_tools` path, so give up quickly
+/// and fall back to keyword search.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+const INDEX_TIMEOUT: Duration = Duration::from_secs(60);
+/// Cached vectors kept on disk. Past this, vectors for tools that aren't
+/// currently connected are dropped.
+const MAX_CACHED: usize = 10_000;
+
+/// A server's tool vectors and the model that produced them.
+struct ServerVectors {
+    model: String,
+    tools: Vec<(String, Vec<f32>)>,
+    /// Cache keys of the vectors, kept when the cache is trimmed.
+    keys: HashSet<String>,
+}
+
+pub struct ToolEmbeddings {
+    cache_path: PathBuf,
+    /// Vectors by cache key, loaded from disk on first use.
+    cache: Mutex<Option<HashMap<String, Vec<f32>>>>,
+    servers: RwLock<HashMap<String, ServerVectors>>,
+}
+
+impl ToolEmbeddings {
+    pub fn new(cache_path: PathBuf) -> Self {
+        Self {
+            cache_path,
+            cache: Mutex::new(None),
+            servers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Embed a server's tools, reusing cached vectors. An empty list removes
+    /// the server.
+    pub async fn index(
+        &self,
+        config: &EmbeddingConfig,
+        api_key: Option<&str>,
+        server_id: &str,
+        tools: &[McpTool],
+    ) -> Result<(), AppError> {
+        if tools.is_empty() {
+            self.servers.write().unwrap().remove(server_id);
+            return Ok(());
+        }
+
+        let model = model_key(config);
+        let keyed: Vec<(String, String)> = tools
+            .iter()
+            .map(|tool| {
+                let text = tool_text(tool);
+                (cache_key(&model, &text), text)
+            })
+            .collect();
+
+        let missing: Vec<&(String, String)> = {
+            let mut cache = self.cache.lock().unwrap();
+            let cache = cache.get_or_insert_with(|| self.load_cache());
+            keyed
+                .iter()
+                .filter(|(key, _)| !cache.contains_key(key))
+                .collect()
+        };
+        let mut embedded = Vec::with_capacity(missing.len());
+        if !missing.is_empty() {
+            let client = Client::builder()
+                .timeout(INDEX_TIMEOUT)
+                .build()
+                .map_err(|e| AppError::ConnectionFailed(e.to_string()))?;
+            for batch in missing.chunks(BATCH_SIZE) {
+                let inputs: Vec<String> = batch.iter().map(|(_, text)| text.clone()).collect();
+                let vectors = embed(&client, config, api_key, &inputs).await?;
+                embedded.extend(batch.iter().map(|(key, _)| key.clone()).zip(vectors));
+            }
+            info!(
+                "Embedded {} of {} tools for {server_id}",
+                embedded.len(),
+                tools.len()
+            );
+        }
+
+        let mut cache = self.cache.lock().unwrap();
+        let cache = cache.get_or_insert_with(HashMap::new);
+        let changed = !embedded.is_empty();
+        cache.extend(embedded);
+        let vectors = tools
+            .iter()
+            .zip(&keyed)
+            .filter_map(|(tool, (key, _))| Some((tool.name.clone(), cache.get(key)?.clone())))
+            .collect();
+        self.servers.write().unwrap().insert(
+            server_id.to_string(),
+            ServerVectors {
+                model,
+                tools: vectors,
+                keys: keyed.into_iter().map(|(key, _)| key).collect(),
+            },
+        );
+        if changed {
+            self.save_cache(cache);
+        }
+        Ok(())
+    }
+
+    /// Tools most similar to the query vector, from servers in `servers`
+    /// embedded with `config`'s model. `None` if no such vectors exist.
+    pub fn search(
+        &self,
+        config: &EmbeddingConfig,
+        query: &[f32],
+        limit: usize,
+        servers: &HashSet<String>,
+    ) -> Option<Vec<SearchHit>> {
+        let model = model_key(config);
+        let indexed = self.servers.read().unwrap();
+        let mut hits: Vec<SearchHit> = indexed
+            .iter()
+            .filter(|(id, vectors)| servers.contains(*id) && vectors.model == model)
+            .flat_map(|(id, vectors)| {
+                vectors.tools.iter().map(move |(name, vector)| SearchHit {
+                    server_id: id.clone(),
+                    tool_name: name.clone(),
+                    score: cosine_similarity(query, vector),
+                })
+            })
+            .collect();
+        if hits.is_empty() {
+            return None;
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        Some(hits)
+    }
+
+    /// Tools with vectors from `config`'s model.
+    pub fn embedded_tools(&self, config: &EmbeddingConfig) -> usize {
+        let model = model_key(config);
+        self.servers
+            .read()
+            .unwrap()
+            .values()
+            .filter(|vectors| vectors.model == model)
+            .map(|vectors| vectors.tools.len())
+            .sum()
+    }
+
+    fn load_cache(&self) -> HashMap<String, Vec<f32>> {
+        std::fs::read(&self.cache_path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_cache(&self, cache: &mut HashMap<String, Vec<f32>>) {
+        if cache.len() > MAX_CACHED {
+            let servers = self.servers.read().unwrap();
+            cache.retain(|key, _| servers.values().any(|vectors| vectors.keys.contains(key)));
+        }
+        let result = serde_json::to_vec(cache)
+            .map_err(AppError::from)
+            .and_then(|raw| {
+                if let Some(dir) = self.cache_path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                Ok(std::fs::write(&self.cache_path, raw)?)
+            });
+        if let Err(e) = result {
+            warn!("Failed to write tool embedding cache: {e}");
+        }
+    }
+}
+
+/// Embed a server's tools in the background, if discovery ranks semantically.
+/// Called whenever a server's tool list changes; an empty list forgets the
+/// server.
+pub fn index_server(app: &AppHandle, server_id: &str, tools: &[McpTool]) {
+    let Some(embeddings) = app.try_state::<ToolEmbeddings>() else {
+        return;
+    };
+    let config = {
+        let state = app.state::<SharedState>();
+        let s = state.lock().unwrap();
+        if s.discovery_search_mode == SearchMode::Keyword && !tools.is_empty() {
+            return;
+        }
+        s.embedding_config.clone()
+    };
+    if tools.is_empty() {
+        embeddings.servers.write().unwrap().remove(server_id);
+        return;
+    }
+
+    let app = app.clone();
+    let server_id = server_id.to_string();
+    let tools = tools.to_vec();
+    tauri::async_runtime::spawn(async move {
+        let api_key = load_openai_api_key(&app);
+        let embeddings = app.state::<ToolEmbeddings>();
+        if let Err(e) = embeddings
+            .index(&config, api_key.as_deref(), &server_id, &tools)
+            .await
+        {
+            warn!("Failed to embed tools for {server_id}, discovery falls back to keyword search: {e}");
+        }
+    });
+}
+
+/// Embed the tools of every server in the tool catalog, e.g. at startup,
+/// after switching to a semantic mode or after changing the embedding model.
+pub fn index_all(app: &AppHandle) {
+    let servers: Vec<(String, Vec<McpTool>)> = {
+        let state = app.state::<SharedState>();
+        let s = state.lock().unwrap();
+        s.tool_catalog
+            .iter()
+            .map(|(id, tools)| (id.clone(), tools.clone()))
+            .collect()
+    };
+    for (server_id, tools) in servers {
+        if !tools.is_empty() {
+            index_server(app, &server_id, &tools);
+        }
+    }
+}
+
+/// Rank tools by similarity to the query. `None` when embeddings aren't
+/// available, so the caller can fall back to keyword search.
+pub async fn semantic_search(
+    app: &AppHandle,
+    query: &str,
+    limit: usize,
+    servers: &HashSet<String>,
+) -> Option<Vec<SearchHit>> {
+    let embeddings = app.try_state::<ToolEmbeddings>()?;
+    let config = app
+        .state::<SharedState>()
+        .lock()
+        .unwrap()
+        .embedding_config
+        .clone();
+    if embeddings.embedded_tools(&config) == 0 {
+        return None;
+    }
+
+    let api_key = load_openai_api_key(app);
+    let client = Client::builder().timeout(QUERY_TIMEOUT).build().ok()?;
+    let query_vector = match embed(&client, &config, api_key.as_deref(), &[query.to_string()]).await
+    {
+        Ok(mut vectors) => vectors.pop()?,
+        Err(e) => {
+            warn!("Failed to embed discovery query, using keyword search: {e}");
+            return None;
+        }
+    };
+    embeddings.search(&config, &query_vector, limit, servers)
+}
+
+/// Call the provider's embedding API. Returns one vector per input, in order.
+async fn embed(
+    client: &Client,
+    config: &EmbeddingConfig,
+    api_key: Option<&str>,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>, AppError> {
+    let request = match config.provider {
+        EmbeddingProvider::Openai => {
+            let key = api_key.ok_or_else(|| {
+                AppError::Validation("No OpenAI API key configured for embeddings".into())
+            })?;
+            let mut body = serde_json::json!({ "model": config.model, "input": inputs });
+            // Only the text-embedding-3 models can be shortened
+            if config.model.starts_with("text-embedding-3") {
+                body["dimensions"] = config.dimensions.into();
+            }
+            client.post(OPENAI_URL).bearer_auth(key).json(&body)
+        }
+        EmbeddingProvider::Ollama => client
+            .post(OLLAMA_URL)
+            .json(&serde_json::json!({ "model": config.model, "input": inputs })),
+    };
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::ConnectionFailed(format!("Embedding request failed: {e}")))?;
+    let status = response.status();
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::Protocol(format!("Invalid embedding response: {e}")))?;
+    if !status.is_success() {
+        return Err(AppError::ConnectionFailed(format!(
+            "Embedding request returned {status}: {body}"
+        )));
+    }
+
+    let vectors = parse_vectors(config.provider.clone(), &body)
+        .ok_or_else(|| AppError::Protocol("Embedding response has no vectors".into()))?;
+    if vectors.len() != inputs.len() {
+        return Err(AppError::Protocol(format!(
+            "Expected {} embeddings, got {}",
+            inputs.len(),
+            vectors.len()
+        )));
+    }
+    Ok(vectors)
+}
+
+/// Vectors from an OpenAI (`data[].embedding`, ordered by `index`) or
+/// Ollama (`embeddings`) response.
+fn parse_vectors(provider: EmbeddingProvider, body: &Value) -> Option<Vec<Vec<f32>>> {
+    let to_vector = |value: &Value| -> Option<Vec<f32>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|x| x.as_f64().map(|x| x as f32))
+            .collect()
+    };
+    match provider {
+        EmbeddingProvider::Openai => {
+            let mut data: Vec<&Value> = body.get("data")?.as_array()?.iter().collect();
+            data.sort_by_key(|item| item.get("index").and_then(Value::as_u64));
+            data.iter()
+                .map(|item| to_vector(item.get("embedding")?))
+                .collect()
+        }
+        EmbeddingProvider::Ollama => body
+            .get("embeddings")?
+            .as_array()?
+            .iter()
+            .map(to_vector)
+            .collect(),
+    }
+}
+
+/// The text embedded for a tool: its name, title, description and parameters.
+fn tool_text(tool: &McpTool) -> String {
+    let mut text = tool.name.clone();
+    for part in [&tool.title, &tool.description].into_iter().flatten() {
+        text.push('\n');
+        text.push_str(part);
+    }
+    let params: Vec<String> = tool
+        .input_schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(
+            |(name, schema)| match schema.get("description").and_then(Value::as_str) {
+                Some(description) => format!("{name}: {description}"),
+                None => name.clone(),
+            },
+        )
+        .collect();
+    if !params.is_empty() {
+        text.push_str("\nParameters: ");
+        text.push_str(&params.join("; "));
+    }
+    text
+}
+
+/// Identifies the model vectors came from; vectors from different models
+/// can't be compared.
+fn model_key(config: &EmbeddingConfig) -> String {
+    format!(
+        "{:?}/{}/{}",
+        config.provider, config.model, config.dimensions
+    )
+}
+
+fn cache_key(model: &str, text: &str) -> String {
+    let digest = Sha256::digest(format!("{model}\n{text}").as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(*x), f64::from(*y));
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(name: &str, description: &str) -> McpTool {
+        McpTool {
+            name: name.to_string(),
+            title: None,
+            description: Some(description.to_string()),
+            input_schema: Some(json!({
+                "type": "object",
+                "properties": { "repo": { "type": "string", "description": "owner/name" } }
+            })),
+            server_id: "github".to_string(),
+            server_name: "GitHub".to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_provider_responses() {
+        let openai = json!({ "data": [
+            { "index": 1, "embedding": [0.0, 1.0] },
+            { "index": 0, "embedding": [1.0, 0.0] },
+        ] });
+        assert_eq!(
+            parse_vectors(EmbeddingProvider::Openai, &openai).unwrap(),
+            [vec![1.0, 0.0], vec![0.0, 1.0]]
+        );
+        let ollama = json!({ "embeddings": [[0.5, 0.5]] });
+        assert_eq!(
+            parse_vectors(EmbeddingProvider::Ollama, &ollama).unwrap(),
+            [vec![0.5, 0.5]]
+        );
+        assert!(
+            parse_vectors(EmbeddingProvider::Ollama, &json!({ "error": "no model" })).is_none()
+        );
+    }
+
+    #[test]
+    fn cache_keys_change_with_model_and_definition() {
+        let config = EmbeddingConfig::default();
+        let text = tool_text(&tool("create_issue", "Open an issue"));
+        assert_eq!(
+            text,
+            "create_issue\nOpen an issue\nParameters: repo: owner/name"
+        );
+
+        let key = cache_key(&model_key(&config), &text);
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, cache_key(&model_key(&config), &text));
+
+        let other_model = EmbeddingConfig {
+            model: "text-embedding-3-large".into(),
+            ..config.clone()
+        };
+        assert_ne!(key, cache_key(&model_key(&other_model), &text));
+        let changed = tool_text(&tool("create_issue", "Open a new issue"));
+        assert_ne!(key, cache_key(&model_key(&config), &changed));
+    }
+
+    #[test]
+    fn searches_vectors_from_the_current_model_only() {
+        let embeddings = ToolEmbeddings::new(PathBuf::from("unused"));
+        let config = EmbeddingConfig::default();
+        embeddings.servers.write().unwrap().insert(
+            "github".to_string(),
+            ServerVectors {
+                model: model_key(&config),
+                tools: vec![
+                    ("create_issue".into(), vec![1.0, 0.0]),
+                    ("list_repos".into(), vec![0.6, 0.8]),
+                ],
+                keys: HashSet::new(),
+            },
+        );
+        let servers = HashSet::from(["github".to_string()]);
+
+        let hits = embeddings
+            .search(&config, &[0.0, 1.0], 10, &servers)
+            .unwrap();
+        assert_eq!(hits[0].tool_name, "list_repos");
+        assert!((hits[0].score - 0.8).abs() < 1e-6);
+        assert_eq!(embeddings.embedded_tools(&config), 2);
+
+        assert!(embeddings
+            .search(&config, &[0.0, 1.0], 10, &HashSet::new())
+            .is_none());
+        let other_model = EmbeddingConfig {
+            provider: EmbeddingProvider::Ollama,
+            ..config
+        };
+        assert!(embeddings
+            .search(&other_model, &[0.0, 1.0], 10, &servers)
+            .is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! Environment handling for spawned servers: `.env` files and `${VAR}` /
+//! `${VAR:-default}` interpolation in args, env, URL and headers.
+//!
+//! `${secret:NAME}` references, escaped (`$$`) or not, are left alone here and
+//! resolved afterwards by the secret store.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Expands `${VAR}` references against a set of variables, remembering the
+/// names it couldn't resolve so they can be reported before spawning.
+pub struct Interpolator<'a> {
+    vars: &'a HashMap<String, String>,
+    unresolved: Vec<String>,
+}
+
+impl<'a> Interpolator<'a> {
+    pub fn new(vars: &'a HashMap<String, String>) -> Self {
+        Self {
+            vars,
+            unresolved: Vec::new(),
+        }
+    }
+
+    /// Expand every reference in `text`. `${VAR:-default}` uses the default
+    /// when VAR is unset or empty; an unset `${VAR}` becomes an empty string,
+    /// as in a shell. `$$` escapes a literal `$`.
+    pub fn expand(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(pos) = rest.find('$') {
+            out.push_str(&rest[..pos]);
+            let after = &rest[pos + 1..];
+
+            if let Some(tail) = after.strip_prefix('$') {
+                // The secret store, which runs next, unescapes its own references
+                if tail.starts_with("{secret:") {
+                    out.push_str("$$");
+                } else {
+                    out.push('$');
+                }
+                rest = tail;
+                continue;
+            }
+
+            let parsed = after
+                .strip_prefix('{')
+                .and_then(|body| body.find('}').map(|end| (&body[..end], &body[end + 1..])));
+            match parsed {
+                Some((inner, tail)) if is_reference(inner) => {
+                    let (name, default) = match inner.split_once(":-") {
+                        Some((name, default)) => (name, Some(default)),
+                        None => (inner, None),
+                    };
+                    match (self.vars.get(name).filter(|v| !v.is_empty()), default) {
+                        (Some(value), _) => out.push_str(value),
+                        (None, Some(default)) => out.push_str(default),
+                        (None, None) => {
+                            if !self.unresolved.iter().any(|n| n == name) {
+                                self.unresolved.push(name.to_string());
+                            }
+                        }
+                    }
+                    rest = tail;
+                }
+                // Not ours (e.g. `${secret:NAME}` or a bare `$`): keep as written
+                _ => {
+                    out.push('$');
+                    rest = after;
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    pub fn expand_values(&mut self, map: &mut HashMap<String, String>) {
+        for value in map.values_mut() {
+            *value = self.expand(value);
+        }
+    }
+
+    /// Variables referenced without a default that had no value.
+    pub fn unresolved(&self) -> &[String] {
+        &self.unresolved
+    }
+}
+
+/// `NAME` or `NAME:-default`, where NAME is a shell-style identifier.
+fn is_reference(inner: &str) -> bool {
+    let name = inner.split_once(":-").map_or(inner, |(name, _)| name);
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parse a dotenv file: `KEY=value` lines with optional `export`, `#`
+/// comments, and single- or double-quoted values. Values may reference
+/// variables defined earlier in the file or in `base`.
+pub fn parse_env_file(contents: &str, base: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, raw)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let raw = raw.trim();
+
+        let value = if let Some(quoted) = raw.strip_prefix('\'') {
+            // Single quotes are literal
+            quoted
+                .split_once('\'')
+                .map_or(quoted, |(v, _)| v)
+                .to_string()
+        } else {
+            let text = if let Some(quoted) = raw.strip_prefix('"') {
+                quoted
+                    .split_once('"')
+                    .map_or(quoted, |(v, _)| v)
+                    .replace("\\n", "\n")
+            } else {
+                // Unquoted values end at an inline comment
+                raw.split(" #")
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end()
+                    .to_string()
+            };
+            let mut scope = base.clone();
+            scope.extend(vars.clone());
+            Interpolator::new(&scope).expand(&text)
+        };
+        vars.insert(key.to_string(), value);
+    }
+    vars
+}
+
+/// Read the env files in order, later files overriding earlier ones. Relative
+/// paths are resolved against `cwd` (or the home directory), and `~` expands
+/// to the home directory.
+pub fn load_env_files(
+    paths: &[String],
+    cwd: Option<&Path>,
+    base: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, AppError> {
+    let mut vars = HashMap::new();
+    for path in paths {
+        let resolved = resolve_path(path, cwd);
+        let contents = std::fs::read_to_string(&resolved).map_err(|e| {
+            AppError::Validation(format!("Cannot read env file {}: {e}", resolved.display()))
+        })?;
+        let mut scope = base.clone();
+        scope.extend(vars.clone());
+        vars.extend(parse_env_file(&contents, &scope));
+    }
+    Ok(vars)
+}
+
+/// Expand `~` and make relative paths absolute against `base` (or the home directory).
+pub fn resolve_path(path: &str, base: Option<&Path>) -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_default();
+    if path == "~" {
+        return home;
+    }
+    if let Some(rest) = path.strip_prefix("~/") {
+        return home.join(rest);
+    }
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.map_or(home, Path::to_path_buf).join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_variables_and_defaults() {
+        let vars = vars(&[("HOME", "/home/me"), ("EMPTY", "")]);
+        let mut interp = Interpolator::new(&vars);
+
+        assert_eq!(interp.expand("${HOME}/data"), "/home/me/data");
+        assert_eq!(interp.expand("${PORT:-8080}"), "8080");
+        assert_eq!(interp.expand("${EMPTY:-fallback}"), "fallback");
+        assert_eq!(interp.expand("a${MISSING}b"), "ab");
+        assert_eq!(interp.expand("${MISSING}"), "");
+        assert_eq!(interp.unresolved(), ["MISSING"]);
+    }
+
+    #[test]
+    fn leaves_other_dollar_forms_alone() {
+        let vars = vars(&[("X", "1")]);
+        let mut interp = Interpolator::new(&vars);
+
+        assert_eq!(interp.expand("${secret:TOKEN}"), "${secret:TOKEN}");
+        assert_eq!(interp.expand("cost $5 and $X"), "cost $5 and $X");
+        assert_eq!(interp.expand("$${X}"), "${X}");
+        assert_eq!(interp.expand("$${secret:TOKEN}"), "$${secret:TOKEN}");
+        assert_eq!(interp.expand("${X"), "${X");
+        assert!(interp.unresolved().is_empty());
+    }
+
+    #[test]
+    fn parses_env_files() {
+        let base = vars(&[("HOME", "/home/me")]);
+        let parsed = parse_env_file(
+            "# comment\n\
+             export API_KEY=abc123\n\
+             DATA_DIR=${HOME}/data # where data lives\n\
+             GREETING=\"hello\\nworld\"\n\
+             LITERAL='${HOME}'\n\
+             COMBINED=${API_KEY}-x\n\
+             not a pair\n",
+            &base,
+        );
+
+        assert_eq!(parsed["API_KEY"], "abc123");
+        assert_eq!(parsed["DATA_DIR"], "/home/me/data");
+        assert_eq!(parsed["GREETING"], "hello\nworld");
+        assert_eq!(parsed["LITERAL"], "${HOME}");
+        assert_eq!(parsed["COMBINED"], "abc123-x");
+        assert_eq!(parsed.len(), 5);
+    }
+
+    #[test]
+    fn resolves_relative_paths_against_base() {
+        let base = Path::new("/srv/app");
+        assert_eq!(
+            resolve_path(".env", Some(base)),
+            PathBuf::from("/srv/app/.env")
+        );
+        assert_eq!(
+            resolve_path("/etc/x.env", Some(base)),
+            PathBuf::from("/etc/x.env")
+        );
+        assert_eq!(
+            resolve_path("~/x.env", Some(base)),
+            dirs::home_dir().unwrap_or_default().join("x.env")
+        );
+    }
+}
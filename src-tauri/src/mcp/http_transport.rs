@@ -1,19 +1,58 @@
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
+use futures::future::BoxFuture;
 use futures::StreamExt;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use tokio::sync::{oneshot, Mutex};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 use crate::error::AppError;
 use crate::mcp::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::state::HttpMode;
+
+/// How long to wait for a legacy SSE server to answer a request on the stream.
+/// Streamable POSTs have no timeout: tool calls can legitimately run for long.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
 /// Pending request senders, keyed by stringified JSON-RPC id.
 type PendingMap = Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>;
 
+/// Fetches a fresh access token after the current one was rejected.
+pub type TokenRefresher =
+    Arc<dyn Fn() -> BoxFuture<'static, Result<String, AppError>> + Send + Sync>;
+
+/// Called after an expired session was replaced by a new one. The new session
+/// may expose different tools, so the owner re-lists them.
+pub type SessionRenewed = Arc<dyn Fn() + Send + Sync>;
+
+/// Called with each notification or request the server sends on a stream.
+pub type ServerMessageTap = Arc<dyn Fn(&serde_json::Value) + Send + Sync>;
+
+/// Which flavour of the HTTP transport the server speaks.
+enum Mode {
+    /// Not known yet — the first request (always `initialize`) probes it.
+    Unknown,
+    Streamable,
+    LegacySse {
+        /// The URL to POST to, from the stream's `endpoint` event.
+        post_url: String,
+        /// Background task reading responses off the SSE stream.
+        reader: JoinHandle<()>,
+    },
+}
+
+impl Drop for Mode {
+    fn drop(&mut self) {
+        if let Mode::LegacySse { reader, .. } = self {
+            reader.abort();
+        }
+    }
+}
+
 /// HTTP transport for remote MCP servers.
 ///
 /// Supports two modes:
@@ -22,91 +61,157 @@ type PendingMap = Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>;
 /// - **Legacy SSE**: GET an SSE endpoint that returns an `endpoint` event,
 ///   then POST to that URL. Responses arrive on the SSE stream, not in
 ///   the POST response body.
+///
+/// When the mode isn't known up front, it is detected per the spec's
+/// backwards-compatibility rules: the first request is POSTed as streamable
+/// HTTP, and a 4xx response means the server only speaks legacy SSE.
 pub struct HttpTransport {
     next_id: AtomicU64,
     client: Client,
-    /// The URL to POST JSON-RPC requests to.
-    post_url: String,
+    /// The configured MCP endpoint URL.
+    url: String,
     /// Extra headers to include on every request (e.g. Authorization).
     headers: HashMap<String, String>,
     /// Session ID returned by the server, sent on subsequent requests.
     session_id: Arc<Mutex<Option<String>>>,
     /// OAuth access token, injected as Bearer header when present.
     access_token: Arc<Mutex<Option<String>>>,
-    /// Whether this transport uses legacy SSE mode.
-    legacy_sse: bool,
+    /// Called on a 401 to get a new access token before retrying once.
+    token_refresher: Option<TokenRefresher>,
+    /// Serializes refreshes so concurrent 401s only refresh once.
+    refresh_lock: Mutex<()>,
+    /// Params of our `initialize` request, replayed to open a new session
+    /// when the server expires ours.
+    init_params: Mutex<Option<Option<serde_json::Value>>>,
+    /// Serializes session renewals so concurrent 404s only re-initialize once.
+    renew_lock: Mutex<()>,
+    /// Called once a renewed session is initialized.
+    on_session_renewed: Option<SessionRenewed>,
+    /// Shared with the legacy SSE reader, which may start before it's set.
+    on_server_message: Arc<OnceLock<ServerMessageTap>>,
+    /// Detected (or remembered) transport mode.
+    mode: Mutex<Mode>,
     /// For legacy SSE: pending request senders keyed by JSON-RPC id.
     pending: PendingMap,
-    /// Background SSE reader task handle (legacy SSE only).
-    _sse_reader: Option<JoinHandle<()>>,
+    /// Tests shorten this to exercise timeouts; `None` leaves streamable POSTs
+    /// unbounded and legacy SSE waits at [`REQUEST_TIMEOUT`].
+    timeout: Option<Duration>,
 }
 
 impl HttpTransport {
     /// Connect to a remote MCP server via HTTP.
     ///
-    /// If the URL path ends with `/sse`, connects in legacy SSE mode (GET for endpoint
-    /// discovery, then POST to discovered URL). Otherwise, assumes streamable HTTP
-    /// and POSTs directly to the given URL.
+    /// `mode` is the mode remembered from a previous connection. With `None`,
+    /// nothing is sent until the first request, which probes the server.
+    /// A remembered legacy SSE mode opens the SSE stream right away.
     pub async fn connect(
         url: &str,
         headers: HashMap<String, String>,
         access_token: Option<String>,
+        mode: Option<HttpMode>,
     ) -> Result<Self, AppError> {
-        let client = Client::new();
-        let token = Arc::new(Mutex::new(access_token));
-
-        // Heuristic: if the URL ends with /sse, use legacy SSE mode
-        if url.ends_with("/sse") {
-            info!("URL ends with /sse, using legacy SSE transport for {url}");
-            return Self::connect_legacy_sse(url, headers, client, token).await;
-        }
-
-        // Default: streamable HTTP — just store the URL, no probing needed.
-        info!("Using streamable HTTP transport for {url}");
-
-        Ok(Self {
+        let transport = Self {
             next_id: AtomicU64::new(1),
-            client,
-            post_url: url.to_string(),
+            client: Client::new(),
+            url: url.to_string(),
             headers,
             session_id: Arc::new(Mutex::new(None)),
-            access_token: token,
-            legacy_sse: false,
+            access_token: Arc::new(Mutex::new(access_token)),
+            token_refresher: None,
+            refresh_lock: Mutex::new(()),
+            init_params: Mutex::new(None),
+            renew_lock: Mutex::new(()),
+            on_session_renewed: None,
+            on_server_message: Arc::new(OnceLock::new()),
+            mode: Mutex::new(Mode::Unknown),
             pending: Arc::new(Mutex::new(HashMap::new())),
-            _sse_reader: None,
-        })
+            timeout: None,
+        };
+
+        match mode {
+            Some(HttpMode::Streamable) => {
+                info!("Using streamable HTTP transport for {url}");
+                *transport.mode.lock().await = Mode::Streamable;
+            }
+            Some(HttpMode::LegacySse) => {
+                info!("Using legacy SSE transport for {url}");
+                let (post_url, reader) = transport.open_legacy_sse().await?;
+                *transport.mode.lock().await = Mode::LegacySse { post_url, reader };
+            }
+            None => debug!("Transport mode for {url} unknown, will probe on first request"),
+        }
+
+        Ok(transport)
+    }
+
+    /// Install a hook that refreshes the access token when the server rejects it.
+    pub fn set_token_refresher(&mut self, refresher: TokenRefresher) {
+        self.token_refresher = Some(refresher);
+    }
+
+    /// Install a hook that runs after an expired session was renewed.
+    pub fn set_session_renewed_hook(&mut self, hook: SessionRenewed) {
+        self.on_session_renewed = Some(hook);
+    }
+
+    /// Install a hook that sees the notifications and requests the server sends.
+    pub fn set_server_message_tap(&self, tap: ServerMessageTap) {
+        let _ = self.on_server_message.set(tap);
+    }
+
+    /// Swap in a new access token for subsequent requests.
+    pub async fn set_access_token(&self, token: String) {
+        *self.access_token.lock().await = Some(token);
+    }
+
+    /// The error for a 401: if we sent a token it has expired (or was revoked),
+    /// otherwise the server needs us to sign in first.
+    async fn unauthorized(&self, url: &str) -> AppError {
+        if self.access_token.lock().await.is_some() {
+            AppError::AuthExpired(url.to_string())
+        } else {
+            AppError::AuthRequired(url.to_string())
+        }
+    }
+
+    /// The detected transport mode, once known.
+    pub async fn mode(&self) -> Option<HttpMode> {
+        match &*self.mode.lock().await {
+            Mode::Unknown => None,
+            Mode::Streamable => Some(HttpMode::Streamable),
+            Mode::LegacySse { .. } => Some(HttpMode::LegacySse),
+        }
     }
 
     /// Legacy SSE connection: GET the URL to establish the SSE stream,
     /// find the `endpoint` event, then spawn a background task to read
-    /// responses from the stream.
-    async fn connect_legacy_sse(
-        url: &str,
-        headers: HashMap<String, String>,
-        client: Client,
-        access_token: Arc<Mutex<Option<String>>>,
-    ) -> Result<Self, AppError> {
-        let mut req = client.get(url).header("Accept", "text/event-stream");
+    /// responses from the stream. Returns the POST URL and the reader task.
+    async fn open_legacy_sse(&self) -> Result<(String, JoinHandle<()>), AppError> {
+        let url = self.url.as_str();
+        let mut req = self.client.get(url).header("Accept", "text/event-stream");
 
-        for (k, v) in &headers {
+        for (k, v) in &self.headers {
             req = req.header(k.as_str(), v.as_str());
         }
 
         // Inject Bearer token if available
         {
-            let tok = access_token.lock().await;
+            let tok = self.access_token.lock().await;
             if let Some(ref token) = *tok {
                 req = req.header("Authorization", format!("Bearer {token}"));
             }
         }
 
-        let response = req
-            .send()
-            .await
-            .map_err(|e| AppError::Transport(format!("SSE GET request failed: {e}")))?;
+        let response = req.send().await.map_err(|e| {
+            if e.is_connect() {
+                AppError::ConnectionFailed(format!("Could not reach {url}: {e}"))
+            } else {
+                AppError::Transport(format!("SSE GET request failed: {e}"))
+            }
+        })?;
 
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(AppError::AuthRequired(url.to_string()));
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(self.unauthorized(url).await);
         }
 
         if !response.status().is_success() {
@@ -116,11 +221,13 @@ impl HttpTransport {
             )));
         }
 
-        let session_id = response
+        if let Some(sid) = response
             .headers()
             .get("mcp-session-id")
             .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
+        {
+            *self.session_id.lock().await = Some(sid.to_string());
+        }
 
         // Stream the SSE response incrementally to find the `endpoint` event.
         let mut stream = response.bytes_stream();
@@ -162,17 +269,17 @@ impl HttpTransport {
 
         // Spawn a background task that continues reading the SSE stream
         // and dispatches JSON-RPC responses to pending request waiters.
-        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
-        let pending_clone = pending.clone();
+        let pending_clone = self.pending.clone();
+        let on_message = self.on_server_message.clone();
 
-        let sse_reader = tokio::spawn(async move {
+        let reader = tokio::spawn(async move {
             let mut buf = remaining;
             loop {
                 match stream.next().await {
                     Some(Ok(chunk)) => {
                         let text = String::from_utf8_lossy(&chunk).replace("\r\n", "\n");
                         buf.push_str(&text);
-                        dispatch_sse_responses(&mut buf, &pending_clone).await;
+                        dispatch_sse_responses(&mut buf, &pending_clone, on_message.get()).await;
                     }
                     Some(Err(e)) => {
                         error!("Legacy SSE stream error: {e}");
@@ -201,52 +308,24 @@ impl HttpTransport {
             }
         });
 
-        Ok(Self {
-            next_id: AtomicU64::new(1),
-            client,
-            post_url,
-            headers,
-            session_id: Arc::new(Mutex::new(session_id)),
-            access_token,
-            legacy_sse: true,
-            pending,
-            _sse_reader: Some(sse_reader),
-        })
+        Ok((post_url, reader))
     }
 
-    /// Send a JSON-RPC request and return the response.
-    pub async fn send_request(
+    /// POST a JSON-RPC message with our headers, token and session id,
+    /// recording any session id the server hands back.
+    async fn post(
         &self,
-        method: &str,
-        params: Option<serde_json::Value>,
-    ) -> Result<JsonRpcResponse, AppError> {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(serde_json::Value::Number(id.into())),
-            method: method.to_string(),
-            params,
-        };
-
-        let body = serde_json::to_value(&request)
-            .map_err(|e| AppError::Transport(format!("Failed to serialize request: {e}")))?;
-
-        debug!(
-            "HTTP send_request id={id} method={method} -> {}",
-            self.post_url
-        );
-
-        if self.legacy_sse {
-            return self.send_request_legacy_sse(id, &body, method).await;
-        }
-
-        // Streamable HTTP: POST and read response from body
+        url: &str,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, reqwest::Error> {
         let mut req = self
             .client
-            .post(&self.post_url)
+            .post(url)
             .header("Content-Type", "application/json")
             .header("Accept", "application/json, text/event-stream");
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
 
         for (k, v) in &self.headers {
             req = req.header(k.as_str(), v.as_str());
@@ -266,11 +345,7 @@ impl HttpTransport {
             }
         }
 
-        let response = req
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::Transport(format!("HTTP request failed: {e}")))?;
+        let response = req.json(body).send().await?;
 
         if let Some(new_sid) = response
             .headers()
@@ -281,8 +356,218 @@ impl HttpTransport {
             *sid = Some(new_sid.to_string());
         }
 
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
-            return Err(AppError::AuthRequired(self.post_url.clone()));
+        Ok(response)
+    }
+
+    /// Reserve a request ID for [`send_request_with_id`](Self::send_request_with_id).
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Shorthand for tests: send a JSON-RPC request and return the response.
+    #[cfg(test)]
+    pub async fn send_request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        self.send_request_with_id(self.next_id(), method, params)
+            .await
+    }
+
+    /// Send a request with an ID from [`next_id`](Self::next_id). If the
+    /// server expired our session, a new one is initialized and the request
+    /// retried once.
+    pub async fn send_request_with_id(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        if method == "initialize" {
+            *self.init_params.lock().await = Some(params.clone());
+        }
+        let sent_session = self.session_id.lock().await.clone();
+        match self
+            .send_request_authorized(id, method, params.clone())
+            .await
+        {
+            Err(AppError::SessionExpired(_)) if method != "initialize" => {
+                self.renew_session(sent_session).await?;
+                self.send_request_authorized(id, method, params).await
+            }
+            result => result,
+        }
+    }
+
+    /// Send a request; if the server rejects our access token and a refresher
+    /// is installed, the token is refreshed and the request retried once.
+    async fn send_request_authorized(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        let sent_token = self.access_token.lock().await.clone();
+        match self.send_request_once(id, method, params.clone()).await {
+            Err(AppError::AuthExpired(url)) if self.token_refresher.is_some() => {
+                self.refresh_access_token(sent_token, &url).await?;
+                self.send_request_once(id, method, params).await
+            }
+            result => result,
+        }
+    }
+
+    /// Replace a rejected access token via the refresher. `stale` is the token
+    /// the failed request carried; if another request already replaced it,
+    /// there's nothing to do.
+    async fn refresh_access_token(&self, stale: Option<String>, url: &str) -> Result<(), AppError> {
+        let Some(refresher) = &self.token_refresher else {
+            return Err(AppError::AuthExpired(url.to_string()));
+        };
+        let _guard = self.refresh_lock.lock().await;
+        if *self.access_token.lock().await != stale {
+            return Ok(());
+        }
+
+        info!("Access token for {} rejected, refreshing", self.url);
+        match refresher().await {
+            Ok(token) => {
+                self.set_access_token(token).await;
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Token refresh for {} failed: {e}", self.url);
+                Err(AppError::AuthExpired(url.to_string()))
+            }
+        }
+    }
+
+    /// Open a new session by replaying our `initialize` handshake. `stale` is
+    /// the session the failed request carried; if another request already
+    /// renewed it, there's nothing to do.
+    async fn renew_session(&self, stale: Option<String>) -> Result<(), AppError> {
+        let _guard = self.renew_lock.lock().await;
+        {
+            let sid = self.session_id.lock().await;
+            if sid.is_some() && *sid != stale {
+                return Ok(());
+            }
+        }
+        let Some(params) = self.init_params.lock().await.clone() else {
+            return Err(AppError::SessionExpired(self.url.clone()));
+        };
+
+        info!("Server {} expired our session, re-initializing", self.url);
+        *self.session_id.lock().await = None;
+        self.send_request_authorized(self.next_id(), "initialize", params)
+            .await?;
+        self.send_notification("notifications/initialized", None)
+            .await?;
+        if let Some(hook) = &self.on_session_renewed {
+            hook();
+        }
+        Ok(())
+    }
+
+    async fn send_request_once(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::Value::Number(id.into())),
+            method: method.to_string(),
+            params,
+        };
+
+        let body = serde_json::to_value(&request)
+            .map_err(|e| AppError::Transport(format!("Failed to serialize request: {e}")))?;
+
+        debug!("HTTP send_request id={id} method={method} -> {}", self.url);
+
+        // Hold the mode lock while probing so concurrent requests wait for the result.
+        let mut mode = self.mode.lock().await;
+        match &*mode {
+            Mode::LegacySse { post_url, .. } => {
+                let post_url = post_url.clone();
+                drop(mode);
+                self.send_request_legacy_sse(&post_url, id, &body, method)
+                    .await
+            }
+            Mode::Streamable => {
+                drop(mode);
+                let sent_session = self.session_id.lock().await.clone();
+                let response = self
+                    .post(&self.url, &body)
+                    .await
+                    .map_err(|e| request_failed(e, method, id))?;
+                // A 404 to a request carrying a session id means the server dropped our session
+                if sent_session.is_some() && response.status() == StatusCode::NOT_FOUND {
+                    warn!("Server {} expired session, needs re-initialize", self.url);
+                    let mut sid = self.session_id.lock().await;
+                    // Keep a session another request has already renewed
+                    if *sid == sent_session {
+                        *sid = None;
+                    }
+                    return Err(AppError::SessionExpired(self.url.clone()));
+                }
+                self.read_streamable_response(response, method).await
+            }
+            Mode::Unknown => {
+                let response = self
+                    .post(&self.url, &body)
+                    .await
+                    .map_err(|e| request_failed(e, method, id))?;
+
+                if is_legacy_fallback_status(response.status()) {
+                    let status = response.status();
+                    info!(
+                        "POST to {} returned {status}, falling back to legacy SSE",
+                        self.url
+                    );
+                    let (post_url, reader) = match self.open_legacy_sse().await {
+                        Ok(opened) => opened,
+                        Err(e @ (AppError::AuthRequired(_) | AppError::AuthExpired(_))) => {
+                            return Err(e)
+                        }
+                        // Neither transport worked; report both attempts
+                        Err(e) => {
+                            return Err(AppError::Transport(format!(
+                                "POST returned status {status}, and the legacy SSE fallback failed: {e}"
+                            )))
+                        }
+                    };
+                    *mode = Mode::LegacySse {
+                        post_url: post_url.clone(),
+                        reader,
+                    };
+                    drop(mode);
+                    return self
+                        .send_request_legacy_sse(&post_url, id, &body, method)
+                        .await;
+                }
+
+                if response.status().is_success() {
+                    info!("Detected streamable HTTP transport for {}", self.url);
+                    *mode = Mode::Streamable;
+                }
+                drop(mode);
+                self.read_streamable_response(response, method).await
+            }
+        }
+    }
+
+    /// Turn a streamable HTTP response (JSON or single-event SSE) into a JSON-RPC response.
+    async fn read_streamable_response(
+        &self,
+        response: reqwest::Response,
+        method: &str,
+    ) -> Result<JsonRpcResponse, AppError> {
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(self.unauthorized(&self.url).await);
         }
 
         if !response.status().is_success() {
@@ -299,13 +584,20 @@ impl HttpTransport {
             .unwrap_or("")
             .to_string();
 
-        let response_text = response
-            .text()
-            .await
-            .map_err(|e| AppError::Transport(format!("Failed to read HTTP response: {e}")))?;
+        let response_text = response.text().await.map_err(|e| {
+            if e.is_timeout() {
+                AppError::Transport(format!("Timeout waiting for response to {method}"))
+            } else {
+                AppError::Transport(format!("Failed to read HTTP response: {e}"))
+            }
+        })?;
 
         let json_text = if content_type.contains("text/event-stream") {
-            extract_json_from_sse(&response_text)?
+            extract_json_from_sse(&response_text, |message| {
+                if let Some(tap) = self.on_server_message.get() {
+                    tap(message);
+                }
+            })?
         } else {
             response_text
         };
@@ -326,6 +618,7 @@ impl HttpTransport {
     /// Legacy SSE: POST the request and wait for the response on the SSE stream.
     async fn send_request_legacy_sse(
         &self,
+        post_url: &str,
         id: u64,
         body: &serde_json::Value,
         method: &str,
@@ -340,43 +633,17 @@ impl HttpTransport {
         }
 
         // POST the request — legacy SSE servers return 200/202 with no useful body
-        let mut req = self
-            .client
-            .post(&self.post_url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json, text/event-stream");
-
-        for (k, v) in &self.headers {
-            req = req.header(k.as_str(), v.as_str());
-        }
-
-        {
-            let tok = self.access_token.lock().await;
-            if let Some(ref token) = *tok {
-                req = req.header("Authorization", format!("Bearer {token}"));
-            }
-        }
-
-        {
-            let sid = self.session_id.lock().await;
-            if let Some(ref s) = *sid {
-                req = req.header("Mcp-Session-Id", s.as_str());
+        let response = match self.post(post_url, body).await {
+            Ok(response) => response,
+            Err(e) => {
+                self.pending.lock().await.remove(&id_str);
+                return Err(request_failed(e, method, id));
             }
-        }
-
-        let response = req.json(body).send().await.map_err(|e| {
-            // Clean up pending entry on send failure
-            let pending = self.pending.clone();
-            let id_str = id_str.clone();
-            tokio::spawn(async move {
-                pending.lock().await.remove(&id_str);
-            });
-            AppError::Transport(format!("HTTP request failed: {e}"))
-        })?;
+        };
 
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        if response.status() == StatusCode::UNAUTHORIZED {
             self.pending.lock().await.remove(&id_str);
-            return Err(AppError::AuthRequired(self.post_url.clone()));
+            return Err(self.unauthorized(post_url).await);
         }
 
         // Accept 200 and 202 as success for legacy SSE
@@ -389,7 +656,7 @@ impl HttpTransport {
         }
 
         // Wait for the response to arrive on the SSE stream
-        let timeout = tokio::time::Duration::from_secs(60);
+        let timeout = self.timeout.unwrap_or(REQUEST_TIMEOUT);
         match tokio::time::timeout(timeout, rx).await {
             Ok(Ok(rpc_response)) => {
                 if let Some(err) = &rpc_response.error {
@@ -409,6 +676,38 @@ impl HttpTransport {
         }
     }
 
+    /// End the session with a `DELETE` carrying its id, as the spec asks clients
+    /// to when they're done. Servers that don't support it answer 405, which is fine.
+    pub async fn close_session(&self) {
+        let Some(sid) = self.session_id.lock().await.take() else {
+            return;
+        };
+        if !matches!(*self.mode.lock().await, Mode::Streamable) {
+            return;
+        }
+
+        let mut req = self
+            .client
+            .delete(&self.url)
+            .header("Mcp-Session-Id", sid.as_str())
+            .timeout(std::time::Duration::from_secs(3));
+        for (k, v) in &self.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        if let Some(ref token) = *self.access_token.lock().await {
+            req = req.header("Authorization", format!("Bearer {token}"));
+        }
+
+        match req.send().await {
+            Ok(response) => debug!(
+                "HTTP session DELETE for {} returned {}",
+                self.url,
+                response.status()
+            ),
+            Err(e) => debug!("HTTP session DELETE for {} failed: {e}", self.url),
+        }
+    }
+
     /// Send a JSON-RPC notification (no response expected).
     pub async fn send_notification(
         &self,
@@ -427,45 +726,16 @@ impl HttpTransport {
 
         debug!("HTTP send_notification method={method}");
 
-        let mut req = self
-            .client
-            .post(&self.post_url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json, text/event-stream");
-
-        for (k, v) in &self.headers {
-            req = req.header(k.as_str(), v.as_str());
-        }
-
-        {
-            let tok = self.access_token.lock().await;
-            if let Some(ref token) = *tok {
-                req = req.header("Authorization", format!("Bearer {token}"));
-            }
-        }
-
-        {
-            let sid = self.session_id.lock().await;
-            if let Some(ref s) = *sid {
-                req = req.header("Mcp-Session-Id", s.as_str());
-            }
-        }
+        let post_url = match &*self.mode.lock().await {
+            Mode::LegacySse { post_url, .. } => post_url.clone(),
+            Mode::Streamable | Mode::Unknown => self.url.clone(),
+        };
 
-        let response = req
-            .json(&body)
-            .send()
+        let response = self
+            .post(&post_url, &body)
             .await
             .map_err(|e| AppError::Transport(format!("HTTP notification failed: {e}")))?;
 
-        if let Some(new_sid) = response
-            .headers()
-            .get("mcp-session-id")
-            .and_then(|v| v.to_str().ok())
-        {
-            let mut sid = self.session_id.lock().await;
-            *sid = Some(new_sid.to_string());
-        }
-
         // Notifications may return 200 or 202; we don't need the body.
         if !response.status().is_success() {
             warn!(
@@ -478,14 +748,25 @@ impl HttpTransport {
     }
 }
 
-impl Drop for HttpTransport {
-    fn drop(&mut self) {
-        if let Some(ref handle) = self._sse_reader {
-            handle.abort();
-        }
+/// The error for a POST that failed before we got a response.
+fn request_failed(e: reqwest::Error, method: &str, id: u64) -> AppError {
+    if e.is_timeout() {
+        AppError::Transport(format!(
+            "Timeout waiting for response to {method} (id={id})"
+        ))
+    } else if e.is_connect() {
+        AppError::ConnectionFailed(format!("Could not reach server: {e}"))
+    } else {
+        AppError::Transport(format!("HTTP request failed: {e}"))
     }
 }
 
+/// Whether a response to the probing POST means "this is a legacy SSE server".
+/// Per the spec any 4xx qualifies — except 401, which means we need to sign in.
+fn is_legacy_fallback_status(status: StatusCode) -> bool {
+    status.is_client_error() && status != StatusCode::UNAUTHORIZED
+}
+
 /// Parse the `endpoint` event from an SSE body to get the POST URL.
 fn parse_endpoint_from_sse(body: &str, base_url: &str) -> Result<String, AppError> {
     let mut current_event = String::new();
@@ -557,7 +838,11 @@ fn drain_consumed_events(buffer: &str) -> String {
 /// Parse complete SSE events from the buffer and dispatch JSON-RPC responses
 /// to pending request waiters. Removes consumed events from the buffer,
 /// leaving any incomplete trailing data.
-async fn dispatch_sse_responses(buffer: &mut String, pending: &PendingMap) {
+async fn dispatch_sse_responses(
+    buffer: &mut String,
+    pending: &PendingMap,
+    on_message: Option<&ServerMessageTap>,
+) {
     loop {
         // Find a complete event (terminated by double newline)
         let Some(event_end) = buffer.find("\n\n") else {
@@ -590,13 +875,26 @@ async fn dispatch_sse_responses(buffer: &mut String, pending: &PendingMap) {
         }
 
         let json_text = data_parts.join("");
-        let rpc_response: JsonRpcResponse = match serde_json::from_str(&json_text) {
-            Ok(r) => r,
+        let message: serde_json::Value = match serde_json::from_str(&json_text) {
+            Ok(m) => m,
             Err(e) => {
                 warn!("Legacy SSE: failed to parse JSON-RPC from SSE data: {e} — raw: {json_text}");
                 continue;
             }
         };
+        if message.get("method").is_some() {
+            if let Some(tap) = on_message {
+                tap(&message);
+            }
+            continue;
+        }
+        let rpc_response: JsonRpcResponse = match serde_json::from_value(message) {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("Legacy SSE: failed to parse JSON-RPC response: {e} — raw: {json_text}");
+                continue;
+            }
+        };
 
         // Extract the id to find the matching pending request
         let id_str = match &rpc_response.id {
@@ -619,8 +917,12 @@ async fn dispatch_sse_responses(buffer: &mut String, pending: &PendingMap) {
 }
 
 /// Extract JSON-RPC response data from an SSE response body (streamable HTTP mode).
-/// SSE responses contain `data:` lines with JSON fragments.
-fn extract_json_from_sse(body: &str) -> Result<String, AppError> {
+/// SSE responses contain `data:` lines with JSON fragments. Notifications and
+/// requests the server sends ahead of the response go to `on_message`.
+fn extract_json_from_sse(
+    body: &str,
+    mut on_message: impl FnMut(&serde_json::Value),
+) -> Result<String, AppError> {
     let mut json_parts = Vec::new();
     let mut current_event = String::new();
 
@@ -630,7 +932,11 @@ fn extract_json_from_sse(body: &str) -> Result<String, AppError> {
         } else if let Some(data) = line.strip_prefix("data:") {
             // Accept "message" events or events with no type (default is "message")
             if current_event.is_empty() || current_event == "message" {
-                json_parts.push(data.trim().to_string());
+                let data = data.trim();
+                match serde_json::from_str::<serde_json::Value>(data) {
+                    Ok(message) if message.get("method").is_some() => on_message(&message),
+                    _ => json_parts.push(data.to_string()),
+                }
             }
         }
     }
@@ -643,3 +949,474 @@ fn extract_json_from_sse(body: &str) -> Result<String, AppError> {
 
     Ok(json_parts.last().expect("non-empty after guard").clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::AtomicBool;
+
+    use axum::http::StatusCode as ServerStatus;
+    use axum::response::IntoResponse;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use futures::FutureExt;
+    use serde_json::{json, Value};
+
+    use crate::mcp::test_support::{self, Reply, ScriptedServer};
+
+    async fn serve(router: Router) -> String {
+        format!("{}/mcp", test_support::serve(router).await)
+    }
+
+    fn result_for(req: &Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": req["id"], "result": { "method": req["method"] } })
+    }
+
+    fn scripted() -> Arc<ScriptedServer> {
+        Arc::new(
+            ScriptedServer::new()
+                .tool("echo", Reply::Echo)
+                .tool("broken", Reply::RpcError(-32000, "backend exploded".into()))
+                .tool("hang", Reply::Silent),
+        )
+    }
+
+    async fn call(transport: &HttpTransport, tool: &str) -> Result<JsonRpcResponse, AppError> {
+        let params = json!({ "name": tool, "arguments": { "text": "hi" } });
+        transport.send_request("tools/call", Some(params)).await
+    }
+
+    #[tokio::test]
+    async fn detects_streamable_http() {
+        let server = scripted();
+        let url = serve(test_support::streamable_http(server.clone(), false)).await;
+
+        let transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(transport.mode().await, None);
+
+        let resp = transport.send_request("initialize", None).await.unwrap();
+        assert_eq!(resp.result.unwrap()["serverInfo"]["name"], "scripted");
+        assert_eq!(transport.mode().await, Some(HttpMode::Streamable));
+        assert_eq!(
+            transport.session_id.lock().await.as_deref(),
+            Some("session-1")
+        );
+
+        let resp = transport.send_request("tools/list", None).await.unwrap();
+        assert_eq!(resp.result.unwrap()["tools"].as_array().unwrap().len(), 3);
+        assert_eq!(server.methods(), ["initialize", "tools/list"]);
+    }
+
+    #[tokio::test]
+    async fn reads_sse_framed_streamable_responses() {
+        let url = serve(test_support::streamable_http(scripted(), true)).await;
+        let transport =
+            HttpTransport::connect(&url, HashMap::new(), None, Some(HttpMode::Streamable))
+                .await
+                .unwrap();
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let tap_seen = seen.clone();
+        transport.set_server_message_tap(Arc::new(move |message| {
+            tap_seen.lock().unwrap().push(message["method"].clone());
+        }));
+
+        // The log notification ahead of the response goes to the tap, not the caller
+        let resp = call(&transport, "echo").await.unwrap();
+        let result = resp.result.unwrap();
+        assert_eq!(result["content"][0]["text"], r#"{"text":"hi"}"#);
+        assert_eq!(*seen.lock().unwrap(), [json!("notifications/message")]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_legacy_sse_on_4xx() {
+        let probes = Arc::new(AtomicU64::new(0));
+        let url = serve(test_support::legacy_sse(scripted(), probes.clone())).await;
+
+        let transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+
+        let resp = transport.send_request("initialize", None).await.unwrap();
+        assert_eq!(resp.result.unwrap()["serverInfo"]["name"], "scripted");
+        assert_eq!(transport.mode().await, Some(HttpMode::LegacySse));
+
+        // Responses are pretty-printed over several `data:` lines
+        let resp = call(&transport, "echo").await.unwrap();
+        assert_eq!(resp.result.unwrap()["isError"], false);
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn remembered_mode_skips_probe() {
+        let probes = Arc::new(AtomicU64::new(0));
+        let url = serve(test_support::legacy_sse(scripted(), probes.clone())).await;
+
+        let transport =
+            HttpTransport::connect(&url, HashMap::new(), None, Some(HttpMode::LegacySse))
+                .await
+                .unwrap();
+        assert_eq!(transport.mode().await, Some(HttpMode::LegacySse));
+
+        transport.send_request("initialize", None).await.unwrap();
+        assert_eq!(probes.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn json_rpc_errors_become_protocol_errors() {
+        let url = serve(test_support::streamable_http(scripted(), true)).await;
+        let transport =
+            HttpTransport::connect(&url, HashMap::new(), None, Some(HttpMode::Streamable))
+                .await
+                .unwrap();
+
+        let err = call(&transport, "broken").await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Protocol(msg) if msg == "-32000: backend exploded"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unanswered_requests_time_out() {
+        for legacy in [false, true] {
+            let server = scripted();
+            let (router, mode) = if legacy {
+                let probes = Arc::new(AtomicU64::new(0));
+                (
+                    test_support::legacy_sse(server, probes),
+                    HttpMode::LegacySse,
+                )
+            } else {
+                (
+                    test_support::streamable_http(server, false),
+                    HttpMode::Streamable,
+                )
+            };
+            let url = serve(router).await;
+            let mut transport = HttpTransport::connect(&url, HashMap::new(), None, Some(mode))
+                .await
+                .unwrap();
+            transport.timeout = Some(Duration::from_millis(200));
+
+            let err = call(&transport, "hang").await.unwrap_err();
+            let message = err.to_string();
+            assert!(
+                message.contains("Timeout waiting for") && message.contains("tools/call"),
+                "{mode:?}: {message}"
+            );
+            // The transport is still usable afterwards
+            call(&transport, "echo").await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn unauthorized_probe_does_not_fall_back() {
+        let gets = Arc::new(AtomicU64::new(0));
+        let counter = gets.clone();
+        let router = Router::new().route(
+            "/mcp",
+            post(|| async { ServerStatus::UNAUTHORIZED }).get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                ServerStatus::UNAUTHORIZED
+            }),
+        );
+        let url = serve(router).await;
+
+        let transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+        let err = transport
+            .send_request("initialize", None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::AuthRequired(_)));
+        assert_eq!(gets.load(Ordering::SeqCst), 0);
+        assert_eq!(transport.mode().await, None);
+    }
+
+    #[tokio::test]
+    async fn failed_fallback_reports_both_attempts() {
+        let router = Router::new().route(
+            "/mcp",
+            post(|| async { ServerStatus::METHOD_NOT_ALLOWED })
+                .get(|| async { ServerStatus::INTERNAL_SERVER_ERROR }),
+        );
+        let url = serve(router).await;
+
+        let transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+        let err = transport
+            .send_request("initialize", None)
+            .await
+            .unwrap_err();
+
+        let message = err.to_string();
+        assert!(matches!(err, AppError::Transport(_)));
+        assert!(message.contains("405"), "{message}");
+        assert!(message.contains("500"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn expired_session_is_renewed_and_closed_sessions_are_deleted() {
+        let inits = Arc::new(AtomicU64::new(0));
+        let deletes = Arc::new(AtomicU64::new(0));
+        let (init_counter, delete_counter) = (inits.clone(), deletes.clone());
+        let router = Router::new().route(
+            "/mcp",
+            post(
+                move |headers: axum::http::HeaderMap, Json(req): Json<Value>| async move {
+                    let session = headers.get("mcp-session-id").map(|v| v.to_str().unwrap());
+                    if req["method"] == "initialize" {
+                        let n = init_counter.fetch_add(1, Ordering::SeqCst) + 1;
+                        let sid = format!("session-{n}");
+                        return ([("mcp-session-id", sid)], Json(result_for(&req))).into_response();
+                    }
+                    if session == Some("session-1") && req["id"].is_number() {
+                        return ServerStatus::NOT_FOUND.into_response();
+                    }
+                    Json(result_for(&req)).into_response()
+                },
+            )
+            .delete(move |headers: axum::http::HeaderMap| async move {
+                assert_eq!(headers["mcp-session-id"], "session-2");
+                delete_counter.fetch_add(1, Ordering::SeqCst);
+                ServerStatus::NO_CONTENT
+            }),
+        );
+        let url = serve(router).await;
+
+        let transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+        transport
+            .send_request(
+                "initialize",
+                Some(json!({ "protocolVersion": "2025-03-26" })),
+            )
+            .await
+            .unwrap();
+
+        // Concurrent requests on the expired session renew it once, whatever the method
+        let (list, call) = tokio::join!(
+            transport.send_request("tools/list", None),
+            transport.send_request("tools/call", None)
+        );
+        assert_eq!(list.unwrap().result.unwrap()["method"], "tools/list");
+        assert_eq!(call.unwrap().result.unwrap()["method"], "tools/call");
+        assert_eq!(inits.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            transport.session_id.lock().await.as_deref(),
+            Some("session-2")
+        );
+
+        // Closing the session sends DELETE once
+        transport.close_session().await;
+        transport.close_session().await;
+        assert_eq!(deletes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn renewed_session_triggers_a_tool_refresh() {
+        let expired = Arc::new(AtomicBool::new(false));
+        let expire = expired.clone();
+        let router = Router::new().route(
+            "/mcp",
+            post(
+                move |headers: axum::http::HeaderMap, Json(req): Json<Value>| async move {
+                    let expired = expire.load(Ordering::SeqCst);
+                    let session = headers.get("mcp-session-id").map(|v| v.to_str().unwrap());
+                    if req["method"] == "initialize" {
+                        let sid = if expired { "session-2" } else { "session-1" };
+                        return ([("mcp-session-id", sid)], Json(result_for(&req))).into_response();
+                    }
+                    if expired && session == Some("session-1") && req["id"].is_number() {
+                        return ServerStatus::NOT_FOUND.into_response();
+                    }
+                    // The new session serves a different set of tools
+                    let tool = if session == Some("session-2") {
+                        "search"
+                    } else {
+                        "fetch"
+                    };
+                    Json(json!({
+                        "jsonrpc": "2.0",
+                        "id": req["id"],
+                        "result": { "tools": [{ "name": tool }] },
+                    }))
+                    .into_response()
+                },
+            ),
+        );
+        let url = serve(router).await;
+
+        let mut transport = HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap();
+        let (renewed_tx, mut renewed) = tokio::sync::mpsc::unbounded_channel();
+        transport.set_session_renewed_hook(Arc::new(move || {
+            let _ = renewed_tx.send(());
+        }));
+        transport
+            .send_request(
+                "initialize",
+                Some(json!({ "protocolVersion": "2025-03-26" })),
+            )
+            .await
+            .unwrap();
+        let tools = transport.send_request("tools/list", None).await.unwrap();
+        assert_eq!(tools.result.unwrap()["tools"][0]["name"], "fetch");
+        assert!(renewed.try_recv().is_err());
+
+        // The request that hits the expired session renews it and tells the
+        // owner, whose re-list sees the new session's tools
+        expired.store(true, Ordering::SeqCst);
+        transport.send_request("tools/call", None).await.unwrap();
+        assert!(renewed.try_recv().is_ok());
+        assert!(renewed.try_recv().is_err());
+        let tools = transport.send_request("tools/list", None).await.unwrap();
+        assert_eq!(tools.result.unwrap()["tools"][0]["name"], "search");
+    }
+
+    #[tokio::test]
+    async fn session_is_not_renewed_without_a_handshake_to_replay() {
+        let router = Router::new().route("/mcp", post(|| async { ServerStatus::NOT_FOUND }));
+        let url = serve(router).await;
+
+        let transport =
+            HttpTransport::connect(&url, HashMap::new(), None, Some(HttpMode::Streamable))
+                .await
+                .unwrap();
+        *transport.session_id.lock().await = Some("session-1".into());
+        let err = transport
+            .send_request("tools/list", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::SessionExpired(_)));
+        assert!(transport.session_id.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn rejected_token_is_refreshed_and_retried() {
+        let router = Router::new().route(
+            "/mcp",
+            post(
+                |headers: axum::http::HeaderMap, Json(req): Json<Value>| async move {
+                    if headers["authorization"] != "Bearer fresh" {
+                        return ServerStatus::UNAUTHORIZED.into_response();
+                    }
+                    Json(result_for(&req)).into_response()
+                },
+            ),
+        );
+        let url = serve(router).await;
+
+        let refreshes = Arc::new(AtomicU64::new(0));
+        let counter = refreshes.clone();
+        let mut transport = HttpTransport::connect(
+            &url,
+            HashMap::new(),
+            Some("stale".to_string()),
+            Some(HttpMode::Streamable),
+        )
+        .await
+        .unwrap();
+        transport.set_token_refresher(Arc::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Ok("fresh".to_string()) }.boxed()
+        }));
+
+        transport.send_request("initialize", None).await.unwrap();
+        transport.send_request("tools/list", None).await.unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_token_without_refresher_is_auth_expired() {
+        let router = Router::new().route("/mcp", post(|| async { ServerStatus::UNAUTHORIZED }));
+        let url = serve(router).await;
+
+        let transport = HttpTransport::connect(
+            &url,
+            HashMap::new(),
+            Some("stale".to_string()),
+            Some(HttpMode::Streamable),
+        )
+        .await
+        .unwrap();
+        let err = transport
+            .send_request("tools/list", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::AuthExpired(_)));
+    }
+
+    #[test]
+    fn resolves_endpoint_events() {
+        let base = "http://localhost:3000/sse";
+        let body = "event: endpoint\ndata: /messages?sessionId=abc\n\n";
+        assert_eq!(
+            parse_endpoint_from_sse(body, base).unwrap(),
+            "http://localhost:3000/messages?sessionId=abc"
+        );
+        let body = "event:endpoint\ndata:messages\n\n";
+        assert_eq!(
+            parse_endpoint_from_sse(body, base).unwrap(),
+            "http://localhost:3000/messages"
+        );
+        let body = "event: endpoint\ndata: https://other.example/rpc\n\n";
+        assert_eq!(
+            parse_endpoint_from_sse(body, base).unwrap(),
+            "https://other.example/rpc"
+        );
+        assert!(parse_endpoint_from_sse("event: message\ndata: {}\n\n", base).is_err());
+    }
+
+    #[tokio::test]
+    async fn dispatches_sse_events_split_across_chunks() {
+        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+        let (tx1, rx1) = oneshot::channel();
+        let (tx2, rx2) = oneshot::channel();
+        pending.lock().await.insert("1".into(), tx1);
+        pending.lock().await.insert("2".into(), tx2);
+
+        let stream = concat!(
+            "event: ping\ndata: {}\n\n",
+            "event: message\ndata: {\"jsonrpc\": \"2.0\",\ndata: \"id\": 2, \"result\": {}}\n\n",
+            "data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\n\n",
+            "data: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"ok\":true}}\n\n",
+        );
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let tap_seen = seen.clone();
+        let tap: ServerMessageTap = Arc::new(move |message| {
+            tap_seen.lock().unwrap().push(message["method"].clone());
+        });
+        let (head, tail) = stream.split_at(40);
+        let mut buffer = head.to_string();
+        dispatch_sse_responses(&mut buffer, &pending, Some(&tap)).await;
+        assert!(!buffer.is_empty(), "incomplete event stays buffered");
+        buffer.push_str(tail);
+        dispatch_sse_responses(&mut buffer, &pending, Some(&tap)).await;
+
+        assert!(buffer.is_empty());
+        assert_eq!(*seen.lock().unwrap(), [json!("notifications/progress")]);
+        assert_eq!(rx1.await.unwrap().result.unwrap()["ok"], true);
+        assert!(rx2.await.unwrap().result.is_some());
+        assert!(pending.lock().await.is_empty());
+    }
+
+    #[test]
+    fn fallback_statuses() {
+        assert!(is_legacy_fallback_status(StatusCode::METHOD_NOT_ALLOWED));
+        assert!(is_legacy_fallback_status(StatusCode::NOT_FOUND));
+        assert!(!is_legacy_fallback_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_legacy_fallback_status(
+            StatusCode::INTERNAL_SERVER_ERROR
+        ));
+    }
+}
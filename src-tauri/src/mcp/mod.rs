@@ -1,9 +1,19 @@
 pub mod client;
+pub mod container;
 pub mod discovery;
+pub mod embeddings;
+pub mod env;
 pub mod http_common;
 pub mod http_transport;
 pub mod oauth;
 pub mod oauth_callback;
 pub mod proxy;
+pub mod replay;
+pub mod sandbox;
+pub mod search;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod traffic;
 pub mod transport;
 pub mod types;
+pub mod ws_transport;
@@ -1,12 +1,18 @@
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::Rng;
 use reqwest::Client;
+use serde::Deserialize;
 use sha2::{Digest, Sha256};
-use tracing::{debug, info};
+use tauri::{Emitter, Manager};
+use tracing::{debug, info, warn};
 use url::Url;
 
 use crate::error::AppError;
-use crate::state::{AuthServerMetadata, OAuthTokens, SharedOAuthStore};
+use crate::mcp::client::SharedConnections;
+use crate::state::{
+    AuthMode, AuthServerMetadata, OAuthConfig, OAuthState, OAuthTokens, SharedOAuthStore,
+    SharedState,
+};
 
 /// PKCE challenge pair.
 pub struct PkceChallenge {
@@ -26,7 +32,13 @@ pub struct PkceChallenge {
 pub async fn discover_metadata(server_url: &str) -> Result<AuthServerMetadata, AppError> {
     let parsed =
         Url::parse(server_url).map_err(|e| AppError::OAuth(format!("Invalid server URL: {e}")))?;
-    let origin = format!("{}://{}", parsed.scheme(), parsed.authority());
+    // WebSocket servers publish their OAuth metadata over plain HTTP(S).
+    let scheme = match parsed.scheme() {
+        "ws" => "http",
+        "wss" => "https",
+        other => other,
+    };
+    let origin = format!("{scheme}://{}", parsed.authority());
     let client = Client::new();
 
     // Attempt 1: RFC 9728 protected-resource discovery
@@ -130,14 +142,20 @@ pub fn generate_state_nonce() -> String {
 
 /// Dynamically register a client per RFC 7591.
 /// POST to registration_endpoint with client metadata.
+/// Device flow clients also ask for the device code grant.
 pub async fn dynamic_register(
     registration_endpoint: &str,
     redirect_uri: &str,
+    device_flow: bool,
 ) -> Result<(String, Option<String>), AppError> {
     let client = Client::new();
+    let mut grant_types = vec!["authorization_code", "refresh_token"];
+    if device_flow {
+        grant_types.push(DEVICE_CODE_GRANT);
+    }
     let body = serde_json::json!({
         "redirect_uris": [redirect_uri],
-        "grant_types": ["authorization_code", "refresh_token"],
+        "grant_types": grant_types,
         "response_types": ["code"],
         "client_name": "Agent Hub",
         "token_endpoint_auth_method": "none",
@@ -183,13 +201,55 @@ pub async fn dynamic_register(
 
 // --- Authorization URL ---
 
+/// Query parameters the authorization request sets itself; `extra_params`
+/// can't override them.
+const RESERVED_AUTHORIZE_PARAMS: &[&str] = &[
+    "response_type",
+    "client_id",
+    "redirect_uri",
+    "code_challenge",
+    "code_challenge_method",
+    "state",
+    "scope",
+    "resource",
+];
+
+/// The canonical form of an MCP server URL for use as an RFC 8707 resource
+/// indicator: lowercase scheme and host, no fragment, no bare trailing slash.
+pub fn canonical_resource_uri(server_url: &str) -> String {
+    let Ok(mut url) = Url::parse(server_url) else {
+        return server_url.to_string();
+    };
+    url.set_fragment(None);
+    let canonical = url.to_string();
+    if url.path() == "/" && url.query().is_none() {
+        canonical.trim_end_matches('/').to_string()
+    } else {
+        canonical
+    }
+}
+
+/// Configured scopes, or everything the server advertises.
+fn requested_scopes<'a>(metadata: &'a AuthServerMetadata, config: &'a OAuthConfig) -> &'a [String] {
+    if config.scopes.is_empty() {
+        &metadata.scopes_supported
+    } else {
+        &config.scopes
+    }
+}
+
 /// Build the full authorization URL with PKCE and state.
+///
+/// Scopes, the resource indicator and any extra parameters come from the
+/// server's OAuth settings; without configured scopes, everything the
+/// server advertises is requested.
 pub fn build_authorization_url(
     metadata: &AuthServerMetadata,
     client_id: &str,
     redirect_uri: &str,
     pkce: &PkceChallenge,
     state: &str,
+    config: &OAuthConfig,
 ) -> Result<String, AppError> {
     let mut url = Url::parse(&metadata.authorization_endpoint)
         .map_err(|e| AppError::OAuth(format!("Invalid authorization_endpoint URL: {e}")))?;
@@ -202,10 +262,24 @@ pub fn build_authorization_url(
         .append_pair("code_challenge_method", "S256")
         .append_pair("state", state);
 
-    // Add scopes if the server supports any
-    if !metadata.scopes_supported.is_empty() {
+    let scopes = requested_scopes(metadata, config);
+    if !scopes.is_empty() {
         url.query_pairs_mut()
-            .append_pair("scope", &metadata.scopes_supported.join(" "));
+            .append_pair("scope", &scopes.join(" "));
+    }
+
+    if let Some(ref resource) = config.resource {
+        url.query_pairs_mut().append_pair("resource", resource);
+    }
+
+    let mut extra: Vec<_> = config.extra_params.iter().collect();
+    extra.sort();
+    for (key, value) in extra {
+        if RESERVED_AUTHORIZE_PARAMS.contains(&key.as_str()) {
+            warn!("Ignoring extra OAuth parameter '{key}', it is set by Agent Hub");
+            continue;
+        }
+        url.query_pairs_mut().append_pair(key, value);
     }
 
     Ok(url.to_string())
@@ -221,6 +295,7 @@ pub async fn exchange_code(
     code: &str,
     redirect_uri: &str,
     code_verifier: &str,
+    resource: Option<&str>,
 ) -> Result<OAuthTokens, AppError> {
     let client = Client::new();
 
@@ -239,6 +314,10 @@ pub async fn exchange_code(
         params.push(("client_secret", &secret_string));
     }
 
+    if let Some(resource) = resource {
+        params.push(("resource", resource));
+    }
+
     debug!("Exchanging code at {}", metadata.token_endpoint);
 
     let response = client
@@ -261,32 +340,12 @@ pub async fn exchange_code(
         .await
         .map_err(|e| AppError::OAuth(format!("Failed to parse token response: {e}")))?;
 
-    let access_token = result
-        .get("access_token")
-        .and_then(|v| v.as_str())
-        .map(String::from)
-        .ok_or_else(|| AppError::OAuth("No access_token in token response".into()))?;
-
-    let refresh_token = result
-        .get("refresh_token")
-        .and_then(|v| v.as_str())
-        .map(String::from);
-
-    let expires_in = result.get("expires_in").and_then(|v| v.as_u64());
-
-    let obtained_at = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("system clock before UNIX epoch")
-        .as_secs();
-
-    info!("Token exchange successful, expires_in={expires_in:?}");
-
-    Ok(OAuthTokens {
-        access_token,
-        refresh_token,
-        expires_in,
-        obtained_at,
-    })
+    let tokens = tokens_from_response(&result)?;
+    info!(
+        "Token exchange successful, expires_in={:?}",
+        tokens.expires_in
+    );
+    Ok(tokens)
 }
 
 /// Refresh an access token using a refresh_token grant.
@@ -295,6 +354,7 @@ pub async fn refresh_token(
     client_id: &str,
     client_secret: Option<&str>,
     refresh_tok: &str,
+    resource: Option<&str>,
 ) -> Result<OAuthTokens, AppError> {
     let client = Client::new();
 
@@ -310,6 +370,10 @@ pub async fn refresh_token(
         params.push(("client_secret", &secret_string));
     }
 
+    if let Some(resource) = resource {
+        params.push(("resource", resource));
+    }
+
     debug!("Refreshing token at {}", metadata.token_endpoint);
 
     let response = client
@@ -322,6 +386,9 @@ pub async fn refresh_token(
     if !response.status().is_success() {
         let status = response.status();
         let body_text = response.text().await.unwrap_or_default();
+        if matches!(status.as_u16(), 400 | 401) {
+            return Err(rejected_grant(status, body_text));
+        }
         return Err(AppError::OAuth(format!(
             "Token refresh returned status {status}: {body_text}"
         )));
@@ -332,19 +399,361 @@ pub async fn refresh_token(
         .await
         .map_err(|e| AppError::OAuth(format!("Failed to parse refresh response: {e}")))?;
 
+    let mut tokens = tokens_from_response(&result)?;
+    // Servers that don't rotate refresh tokens leave it out; keep using ours
+    tokens
+        .refresh_token
+        .get_or_insert_with(|| refresh_tok.to_string());
+    Ok(tokens)
+}
+
+// --- Client Credentials (RFC 6749 §4.4) ---
+
+/// Request a token with the client credentials grant, for machine-to-machine
+/// servers where nobody signs in. No refresh token is issued; a new token is
+/// requested whenever the old one runs out.
+pub async fn client_credentials_token(
+    token_endpoint: &str,
+    client_id: &str,
+    client_secret: Option<&str>,
+    scopes: &[String],
+    resource: Option<&str>,
+) -> Result<OAuthTokens, AppError> {
+    let client = Client::new();
+
+    let mut params = vec![
+        ("grant_type", "client_credentials"),
+        ("client_id", client_id),
+    ];
+
+    if let Some(secret) = client_secret {
+        params.push(("client_secret", secret));
+    }
+
+    let scope = scopes.join(" ");
+    if !scope.is_empty() {
+        params.push(("scope", scope.as_str()));
+    }
+
+    if let Some(resource) = resource {
+        params.push(("resource", resource));
+    }
+
+    debug!("Requesting client credentials token at {token_endpoint}");
+
+    let response = client
+        .post(token_endpoint)
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| AppError::OAuth(format!("Client credentials request failed: {e}")))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body_text = response.text().await.unwrap_or_default();
+        if matches!(status.as_u16(), 400 | 401) {
+            return Err(rejected_grant(status, body_text));
+        }
+        return Err(AppError::OAuth(format!(
+            "Client credentials request returned status {status}: {body_text}"
+        )));
+    }
+
+    let result: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::OAuth(format!("Failed to parse token response: {e}")))?;
+
+    tokens_from_response(&result)
+}
+
+/// Fetch a fresh client credentials token for a server and store it, so the
+/// refresh scheduler renews it ahead of expiry like any other token.
+async fn fetch_client_credentials(
+    oauth_store: &SharedOAuthStore,
+    server_id: &str,
+    app: &tauri::AppHandle,
+    server_url: &str,
+    config: &OAuthConfig,
+) -> Result<String, AppError> {
+    let client_id = config.client_id.clone().ok_or_else(|| {
+        AppError::OAuth(
+            "Client credentials mode needs a client ID in the server's OAuth settings".into(),
+        )
+    })?;
+
+    let stored_metadata = {
+        let store = oauth_store.lock().await;
+        store
+            .get(server_id)
+            .filter(|s| s.mode == AuthMode::ClientCredentials)
+            .map(|s| s.auth_server_metadata.clone())
+    };
+    let metadata = match (&config.token_endpoint, stored_metadata) {
+        (Some(endpoint), _) => AuthServerMetadata {
+            issuer: String::new(),
+            authorization_endpoint: String::new(),
+            token_endpoint: endpoint.clone(),
+            registration_endpoint: None,
+            device_authorization_endpoint: None,
+            revocation_endpoint: None,
+            scopes_supported: Vec::new(),
+            code_challenge_methods_supported: Vec::new(),
+        },
+        (None, Some(metadata)) => metadata,
+        (None, None) => discover_metadata(server_url).await?,
+    };
+    let resource = config
+        .resource
+        .clone()
+        .unwrap_or_else(|| canonical_resource_uri(server_url));
+
+    let tokens = client_credentials_token(
+        &metadata.token_endpoint,
+        &client_id,
+        config.client_secret.as_deref(),
+        &config.scopes,
+        Some(&resource),
+    )
+    .await?;
+    let access_token = tokens.access_token.clone();
+
+    {
+        let mut store = oauth_store.lock().await;
+        store.set(
+            server_id.to_string(),
+            OAuthState {
+                auth_server_metadata: metadata,
+                client_id: Some(client_id),
+                client_secret: config.client_secret.clone(),
+                tokens: Some(tokens),
+                resource: Some(resource),
+                mode: AuthMode::ClientCredentials,
+                last_refreshed_at: Some(now_secs()),
+            },
+        );
+        crate::persistence::save_oauth_store(app, store.entries());
+    }
+
+    info!("Obtained client credentials token for server {server_id}");
+    Ok(access_token)
+}
+
+/// Read a static bearer token from the secret store.
+fn static_token(app: &tauri::AppHandle, config: &OAuthConfig) -> Result<String, AppError> {
+    let name = config.token_secret.as_deref().ok_or_else(|| {
+        AppError::OAuth("Static token mode needs the name of the secret holding the token".into())
+    })?;
+    crate::persistence::load_secret(app, name)
+        .ok_or_else(|| AppError::OAuth(format!("Secret '{name}' not found")))
+}
+
+/// A server's URL and auth settings, read from app state.
+fn server_auth_config(
+    app: &tauri::AppHandle,
+    server_id: &str,
+) -> Result<(String, OAuthConfig), AppError> {
+    let state = app.state::<SharedState>();
+    let s = state.lock().unwrap();
+    let server = s
+        .servers
+        .iter()
+        .find(|s| s.id == server_id)
+        .ok_or_else(|| AppError::ServerNotFound(server_id.to_string()))?;
+    Ok((
+        server.url.clone().unwrap_or_default(),
+        server.oauth.clone().unwrap_or_default(),
+    ))
+}
+
+// --- Device Authorization (RFC 8628) ---
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Longest wait between polls when the token endpoint keeps failing.
+const MAX_POLL_INTERVAL_SECS: u64 = 60;
+
+/// Response from the device authorization endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    // Some providers still use the draft's `verification_url`
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    /// Minimum seconds between token polls.
+    #[serde(default = "default_poll_interval")]
+    pub interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+/// Whether to sign in with the device flow rather than a browser redirect:
+/// when the server's settings ask for it, or when there's probably no local
+/// browser to reach our loopback callback (SSH session, no display).
+pub fn prefers_device_flow(config: &OAuthConfig) -> bool {
+    if config.device_flow {
+        return true;
+    }
+    let var_set = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+    if var_set("SSH_CONNECTION") || var_set("SSH_TTY") {
+        return true;
+    }
+    cfg!(target_os = "linux") && !var_set("DISPLAY") && !var_set("WAYLAND_DISPLAY")
+}
+
+/// Start a device flow: get a device code and the user code to show.
+pub async fn request_device_authorization(
+    metadata: &AuthServerMetadata,
+    client_id: &str,
+    client_secret: Option<&str>,
+    config: &OAuthConfig,
+) -> Result<DeviceAuthorization, AppError> {
+    let endpoint = metadata
+        .device_authorization_endpoint
+        .as_deref()
+        .ok_or_else(|| {
+            AppError::OAuth("Server does not support the device authorization flow".into())
+        })?;
+
+    let scope = requested_scopes(metadata, config).join(" ");
+    let mut params = vec![("client_id", client_id)];
+    if let Some(secret) = client_secret {
+        params.push(("client_secret", secret));
+    }
+    if !scope.is_empty() {
+        params.push(("scope", scope.as_str()));
+    }
+    if let Some(ref resource) = config.resource {
+        params.push(("resource", resource));
+    }
+
+    debug!("Requesting device authorization at {endpoint}");
+
+    let response = Client::new()
+        .post(endpoint)
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| AppError::OAuth(format!("Device authorization failed: {e}")))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body_text = response.text().await.unwrap_or_default();
+        return Err(AppError::OAuth(format!(
+            "Device authorization returned status {status}: {body_text}"
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::OAuth(format!("Failed to parse device authorization: {e}")))
+}
+
+/// Poll the token endpoint until the user finishes signing in, honouring the
+/// server's interval, `slow_down` responses and the device code's lifetime.
+pub async fn poll_device_token(
+    metadata: &AuthServerMetadata,
+    client_id: &str,
+    client_secret: Option<&str>,
+    device: &DeviceAuthorization,
+    resource: Option<&str>,
+) -> Result<OAuthTokens, AppError> {
+    let client = Client::new();
+
+    let mut params = vec![
+        ("grant_type", DEVICE_CODE_GRANT),
+        ("device_code", device.device_code.as_str()),
+        ("client_id", client_id),
+    ];
+    if let Some(secret) = client_secret {
+        params.push(("client_secret", secret));
+    }
+    if let Some(resource) = resource {
+        params.push(("resource", resource));
+    }
+
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(device.expires_in);
+    let mut interval = device.interval.max(1);
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        if tokio::time::Instant::now() >= deadline {
+            return Err(AppError::OAuth(
+                "Device code expired before sign-in was completed".into(),
+            ));
+        }
+
+        let response = match client
+            .post(&metadata.token_endpoint)
+            .form(&params)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                // Network trouble: back off rather than hammering the server
+                warn!("Device token poll failed: {e}");
+                interval = (interval * 2).min(MAX_POLL_INTERVAL_SECS);
+                continue;
+            }
+        };
+
+        let status = response.status();
+        let result: serde_json::Value = match response.json().await {
+            Ok(result) => result,
+            Err(e) if status.is_success() => {
+                return Err(AppError::OAuth(format!(
+                    "Failed to parse token response: {e}"
+                )));
+            }
+            Err(e) => {
+                // A gateway error page rather than an OAuth error; try again later
+                warn!("Device token poll returned status {status} without a JSON body: {e}");
+                interval = (interval * 2).min(MAX_POLL_INTERVAL_SECS);
+                continue;
+            }
+        };
+
+        if status.is_success() {
+            info!("Device authorization complete");
+            return tokens_from_response(&result);
+        }
+
+        match result.get("error").and_then(|v| v.as_str()) {
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += 5,
+            Some("access_denied") => {
+                return Err(AppError::OAuth("Sign-in was denied".into()));
+            }
+            Some("expired_token") => {
+                return Err(AppError::OAuth(
+                    "Device code expired before sign-in was completed".into(),
+                ));
+            }
+            other => {
+                return Err(AppError::OAuth(format!(
+                    "Device token request failed: {}",
+                    other.unwrap_or("unknown error")
+                )));
+            }
+        }
+    }
+}
+
+/// Read tokens out of a successful token endpoint response.
+fn tokens_from_response(result: &serde_json::Value) -> Result<OAuthTokens, AppError> {
     let access_token = result
         .get("access_token")
         .and_then(|v| v.as_str())
         .map(String::from)
-        .ok_or_else(|| AppError::OAuth("No access_token in refresh response".into()))?;
-
-    let new_refresh = result
-        .get("refresh_token")
-        .and_then(|v| v.as_str())
-        .map(String::from)
-        .or_else(|| Some(refresh_tok.to_string()));
-
-    let expires_in = result.get("expires_in").and_then(|v| v.as_u64());
+        .ok_or_else(|| AppError::OAuth("No access_token in token response".into()))?;
 
     let obtained_at = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -353,37 +762,207 @@ pub async fn refresh_token(
 
     Ok(OAuthTokens {
         access_token,
-        refresh_token: new_refresh,
-        expires_in,
+        refresh_token: result
+            .get("refresh_token")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        expires_in: result.get("expires_in").and_then(|v| v.as_u64()),
         obtained_at,
+        scope: granted_scope(result),
     })
 }
 
-// --- Token expiry check ---
+fn granted_scope(result: &serde_json::Value) -> Option<String> {
+    result
+        .get("scope")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
 
-/// Check whether an access token has expired (with 60s buffer).
-pub fn is_token_expired(tokens: &OAuthTokens) -> bool {
-    let Some(expires_in) = tokens.expires_in else {
-        // No expiry information — assume valid
-        return false;
+/// A token endpoint error that retrying won't fix: the grant or client was
+/// rejected (RFC 6749 §5.2), so only a new sign-in or new credentials help.
+fn rejected_grant(status: reqwest::StatusCode, body: String) -> AppError {
+    AppError::AuthExpired(format!(
+        "token endpoint rejected the grant ({status}): {body}"
+    ))
+}
+
+// --- Revocation (RFC 7009) ---
+
+/// Revoke a token at the authorization server.
+pub async fn revoke_token(
+    revocation_endpoint: &str,
+    client_id: &str,
+    client_secret: Option<&str>,
+    token: &str,
+    token_type_hint: &str,
+) -> Result<(), AppError> {
+    let client = Client::new();
+
+    let mut params = vec![
+        ("token", token),
+        ("token_type_hint", token_type_hint),
+        ("client_id", client_id),
+    ];
+
+    if let Some(secret) = client_secret {
+        params.push(("client_secret", secret));
+    }
+
+    let response = client
+        .post(revocation_endpoint)
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| AppError::OAuth(format!("Token revocation failed: {e}")))?;
+
+    // Unknown or already invalid tokens also get a 200
+    if !response.status().is_success() {
+        let status = response.status();
+        let body_text = response.text().await.unwrap_or_default();
+        return Err(AppError::OAuth(format!(
+            "Token revocation returned status {status}: {body_text}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Revoke a server's tokens if its authorization server supports revocation.
+/// The refresh token goes first, since revoking it usually ends the whole grant.
+/// Returns whether anything was revoked.
+pub async fn revoke_tokens(oauth_state: &OAuthState) -> Result<bool, AppError> {
+    let (Some(endpoint), Some(tokens)) = (
+        oauth_state
+            .auth_server_metadata
+            .revocation_endpoint
+            .as_deref(),
+        oauth_state.tokens.as_ref(),
+    ) else {
+        return Ok(false);
     };
+    let client_id = oauth_state.client_id.as_deref().unwrap_or_default();
+    let client_secret = oauth_state.client_secret.as_deref();
+
+    if let Some(ref refresh) = tokens.refresh_token {
+        revoke_token(endpoint, client_id, client_secret, refresh, "refresh_token").await?;
+    }
+    revoke_token(
+        endpoint,
+        client_id,
+        client_secret,
+        &tokens.access_token,
+        "access_token",
+    )
+    .await?;
+
+    Ok(true)
+}
+
+// --- Token expiry check ---
+
+/// Seconds left before the access token expires, if the server told us.
+pub fn seconds_until_expiry(tokens: &OAuthTokens) -> Option<u64> {
+    let expires_in = tokens.expires_in?;
+
+    Some((tokens.obtained_at + expires_in).saturating_sub(now_secs()))
+}
 
-    let now = std::time::SystemTime::now()
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .expect("system clock before UNIX epoch")
-        .as_secs();
+        .as_secs()
+}
 
-    let expiry = tokens.obtained_at + expires_in;
-    now + 60 >= expiry
+/// Check whether an access token has expired (with 60s buffer).
+pub fn is_token_expired(tokens: &OAuthTokens) -> bool {
+    // No expiry information — assume valid
+    seconds_until_expiry(tokens).is_some_and(|left| left <= 60)
 }
 
-/// Attempt to refresh the stored token for a server. Returns the new access_token on success.
+/// Get a new access token for a server according to its auth mode: refresh the
+/// stored OAuth token, request a new client credentials token, or re-read the
+/// static token from the secret store. Returns the new access_token on success.
+///
+/// When the provider rejects the grant outright, the dead tokens are dropped and
+/// `oauth-refresh-failed` is emitted so the UI can ask the user to sign in again.
 pub async fn try_refresh_token(
     oauth_store: &SharedOAuthStore,
     server_id: &str,
     app: &tauri::AppHandle,
 ) -> Result<String, AppError> {
-    let (metadata, client_id, client_secret, refresh_tok) = {
+    refresh_serialized(oauth_store, server_id, || async {
+        let result = renew_token(oauth_store, server_id, app).await;
+        if let Err(AppError::AuthExpired(ref reason)) = result {
+            warn!("Token refresh for server {server_id} failed for good: {reason}");
+            {
+                let mut store = oauth_store.lock().await;
+                if let Some(oauth_state) = store.entries_mut().get_mut(server_id) {
+                    oauth_state.tokens = None;
+                }
+                crate::persistence::save_oauth_store(app, store.entries());
+            }
+            let _ = app.emit(
+                "oauth-refresh-failed",
+                serde_json::json!({ "serverId": server_id, "error": reason }),
+            );
+        }
+        result
+    })
+    .await
+}
+
+/// Run `refresh` under the server's refresh lock, unless a refresh that
+/// finished while we waited already stored a usable token. Providers that
+/// rotate refresh tokens accept each one only once, so the scheduler and a
+/// transport's 401 handler must never refresh at the same time.
+async fn refresh_serialized<F, Fut>(
+    oauth_store: &SharedOAuthStore,
+    server_id: &str,
+    refresh: F,
+) -> Result<String, AppError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<String, AppError>>,
+{
+    let stored_token = |store: &crate::state::OAuthStore| {
+        store
+            .get(server_id)
+            .and_then(|s| s.tokens.as_ref())
+            .filter(|t| !is_token_expired(t))
+            .map(|t| t.access_token.clone())
+    };
+    let (lock, seen) = {
+        let mut store = oauth_store.lock().await;
+        (store.refresh_lock(server_id), stored_token(&store))
+    };
+
+    let _guard = lock.lock().await;
+    let current = stored_token(&*oauth_store.lock().await);
+    if let Some(token) = current.filter(|t| Some(t) != seen.as_ref()) {
+        debug!("Token for server {server_id} was refreshed while waiting, reusing it");
+        return Ok(token);
+    }
+    refresh().await
+}
+
+async fn renew_token(
+    oauth_store: &SharedOAuthStore,
+    server_id: &str,
+    app: &tauri::AppHandle,
+) -> Result<String, AppError> {
+    let (server_url, config) = server_auth_config(app, server_id)?;
+    match config.mode {
+        AuthMode::Interactive => {}
+        AuthMode::ClientCredentials => {
+            return fetch_client_credentials(oauth_store, server_id, app, &server_url, &config)
+                .await;
+        }
+        AuthMode::StaticToken => return static_token(app, &config),
+    }
+
+    let (metadata, client_id, client_secret, refresh_tok, resource, scope) = {
         let store = oauth_store.lock().await;
         let oauth_state = store
             .get(server_id)
@@ -401,16 +980,23 @@ pub async fn try_refresh_token(
             oauth_state.client_id.clone().unwrap_or_default(),
             oauth_state.client_secret.clone(),
             refresh,
+            oauth_state.resource.clone(),
+            tokens.scope.clone(),
         )
     };
 
-    let new_tokens = refresh_token(
+    let mut new_tokens = refresh_token(
         &metadata,
         &client_id,
         client_secret.as_deref(),
         &refresh_tok,
+        resource.as_deref(),
     )
     .await?;
+    // An omitted scope means the grant is unchanged (RFC 6749 §5.1)
+    if new_tokens.scope.is_none() {
+        new_tokens.scope = scope;
+    }
 
     let new_access = new_tokens.access_token.clone();
 
@@ -419,9 +1005,428 @@ pub async fn try_refresh_token(
         let mut store = oauth_store.lock().await;
         if let Some(oauth_state) = store.entries_mut().get_mut(server_id) {
             oauth_state.tokens = Some(new_tokens);
+            oauth_state.last_refreshed_at = Some(now_secs());
         }
         crate::persistence::save_oauth_store(app, store.entries());
     }
 
     Ok(new_access)
 }
+
+// --- Background refresh ---
+
+/// Refresh tokens this long before they expire.
+const REFRESH_AHEAD_SECS: u64 = 300;
+
+/// How often the scheduler looks for tokens about to expire.
+const REFRESH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Keep the tokens of connected OAuth servers fresh for the lifetime of the app.
+/// Shortly before a token runs out it's refreshed, persisted, and handed to the
+/// live connection, so requests never see the 401 in the first place.
+pub async fn run_refresh_scheduler(app: tauri::AppHandle) {
+    let oauth_store = app.state::<SharedOAuthStore>();
+    let connections = app.state::<SharedConnections>();
+    let mut interval = tokio::time::interval(REFRESH_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let connected = connections.lock().await.ids();
+        let due: Vec<String> = {
+            let store = oauth_store.lock().await;
+            connected
+                .into_iter()
+                .filter(|id| {
+                    store
+                        .get(id)
+                        .filter(|s| {
+                            s.mode == AuthMode::ClientCredentials
+                                || s.tokens.as_ref().is_some_and(|t| t.refresh_token.is_some())
+                        })
+                        .and_then(|s| s.tokens.as_ref())
+                        .and_then(seconds_until_expiry)
+                        .is_some_and(|left| left <= REFRESH_AHEAD_SECS)
+                })
+                .collect()
+        };
+
+        for id in due {
+            match try_refresh_token(&oauth_store, &id, &app).await {
+                Ok(token) => {
+                    let client = connections.lock().await.get(&id).cloned();
+                    if let Some(client) = client {
+                        client.set_access_token(token).await;
+                    }
+                    info!("Refreshed access token for server {id} ahead of expiry");
+                }
+                Err(e) => warn!("Scheduled token refresh for server {id} failed: {e}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::State;
+    use axum::response::IntoResponse;
+    use axum::routing::post;
+    use axum::{Form, Json, Router};
+
+    type Requests = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+    fn metadata(token_endpoint: &str) -> AuthServerMetadata {
+        AuthServerMetadata {
+            issuer: "https://auth.example.com".into(),
+            authorization_endpoint: "https://auth.example.com/authorize".into(),
+            token_endpoint: token_endpoint.into(),
+            registration_endpoint: None,
+            device_authorization_endpoint: None,
+            revocation_endpoint: None,
+            scopes_supported: vec!["read".into(), "write".into()],
+            code_challenge_methods_supported: vec!["S256".into()],
+        }
+    }
+
+    fn query(url: &str) -> HashMap<String, String> {
+        Url::parse(url)
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect()
+    }
+
+    /// A token endpoint that records each form it receives.
+    async fn mock_auth_server(requests: Requests) -> String {
+        let router = Router::new()
+            .route(
+                "/token",
+                post(
+                    |State(requests): State<Requests>,
+                     Form(form): Form<HashMap<String, String>>| async move {
+                        requests.lock().unwrap().push(form);
+                        Json(serde_json::json!({
+                            "access_token": "access-1",
+                            "refresh_token": "refresh-1",
+                            "expires_in": 3600,
+                        }))
+                    },
+                ),
+            )
+            .with_state(requests);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}/token")
+    }
+
+    #[tokio::test]
+    async fn device_flow_polls_until_authorized() {
+        let polls = Arc::new(Mutex::new(0u32));
+        let router = Router::new()
+            .route(
+                "/device",
+                post(|| async {
+                    Json(serde_json::json!({
+                        "device_code": "device-1",
+                        "user_code": "ABCD-EFGH",
+                        "verification_url": "https://auth.example.com/device",
+                        "expires_in": 60,
+                        "interval": 1,
+                    }))
+                }),
+            )
+            .route(
+                "/token",
+                post(
+                    |State(polls): State<Arc<Mutex<u32>>>,
+                     Form(form): Form<HashMap<String, String>>| async move {
+                        assert_eq!(form["device_code"], "device-1");
+                        let mut polls = polls.lock().unwrap();
+                        *polls += 1;
+                        match *polls {
+                            1 => (
+                                axum::http::StatusCode::BAD_REQUEST,
+                                Json(serde_json::json!({ "error": "authorization_pending" })),
+                            )
+                                .into_response(),
+                            // A proxy hiccup is retried, not taken as a failed sign-in
+                            2 => {
+                                (axum::http::StatusCode::BAD_GATEWAY, "Bad Gateway").into_response()
+                            }
+                            _ => Json(serde_json::json!({ "access_token": "device-access" }))
+                                .into_response(),
+                        }
+                    },
+                ),
+            )
+            .with_state(polls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let mut metadata = metadata(&format!("http://{addr}/token"));
+        metadata.device_authorization_endpoint = Some(format!("http://{addr}/device"));
+
+        let device =
+            request_device_authorization(&metadata, "client-1", None, &OAuthConfig::default())
+                .await
+                .unwrap();
+        assert_eq!(device.user_code, "ABCD-EFGH");
+        assert_eq!(device.verification_uri, "https://auth.example.com/device");
+
+        let tokens = poll_device_token(&metadata, "client-1", None, &device, None)
+            .await
+            .unwrap();
+        assert_eq!(tokens.access_token, "device-access");
+        assert_eq!(*polls.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn authorization_url_uses_configured_settings() {
+        let config = OAuthConfig {
+            scopes: vec!["mcp:tools".into()],
+            extra_params: HashMap::from([
+                ("audience".to_string(), "api://mcp".to_string()),
+                ("state".to_string(), "ignored".to_string()),
+            ]),
+            resource: Some("https://mcp.example.com/mcp".into()),
+            ..Default::default()
+        };
+        let url = build_authorization_url(
+            &metadata("https://auth.example.com/token"),
+            "client-1",
+            "http://127.0.0.1:1234/oauth/callback",
+            &generate_pkce(),
+            "nonce",
+            &config,
+        )
+        .unwrap();
+
+        let params = query(&url);
+        assert_eq!(params["client_id"], "client-1");
+        assert_eq!(params["scope"], "mcp:tools");
+        assert_eq!(params["resource"], "https://mcp.example.com/mcp");
+        assert_eq!(params["audience"], "api://mcp");
+        assert_eq!(params["state"], "nonce");
+    }
+
+    #[test]
+    fn authorization_url_defaults_to_advertised_scopes() {
+        let url = build_authorization_url(
+            &metadata("https://auth.example.com/token"),
+            "client-1",
+            "http://127.0.0.1:1234/oauth/callback",
+            &generate_pkce(),
+            "nonce",
+            &OAuthConfig::default(),
+        )
+        .unwrap();
+
+        let params = query(&url);
+        assert_eq!(params["scope"], "read write");
+        assert!(!params.contains_key("resource"));
+    }
+
+    #[test]
+    fn canonical_resource_uris() {
+        assert_eq!(
+            canonical_resource_uri("HTTPS://MCP.Example.com/mcp#frag"),
+            "https://mcp.example.com/mcp"
+        );
+        assert_eq!(
+            canonical_resource_uri("https://mcp.example.com/"),
+            "https://mcp.example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn token_requests_carry_client_secret_and_resource() {
+        let requests = Requests::default();
+        let token_endpoint = mock_auth_server(requests.clone()).await;
+        let metadata = metadata(&token_endpoint);
+        let resource = Some("https://mcp.example.com/mcp");
+
+        let tokens = exchange_code(
+            &metadata,
+            "client-1",
+            Some("secret-1"),
+            "code-1",
+            "http://127.0.0.1:1234/oauth/callback",
+            "verifier-1",
+            resource,
+        )
+        .await
+        .unwrap();
+        assert_eq!(tokens.access_token, "access-1");
+        assert_eq!(tokens.expires_in, Some(3600));
+
+        refresh_token(
+            &metadata,
+            "client-1",
+            Some("secret-1"),
+            "refresh-1",
+            resource,
+        )
+        .await
+        .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0]["grant_type"], "authorization_code");
+        assert_eq!(requests[0]["client_secret"], "secret-1");
+        assert_eq!(requests[0]["resource"], "https://mcp.example.com/mcp");
+        assert_eq!(requests[1]["grant_type"], "refresh_token");
+        assert_eq!(requests[1]["refresh_token"], "refresh-1");
+        assert_eq!(requests[1]["resource"], "https://mcp.example.com/mcp");
+    }
+
+    #[tokio::test]
+    async fn client_credentials_request_sends_scopes_and_resource() {
+        let requests = Requests::default();
+        let token_endpoint = mock_auth_server(requests.clone()).await;
+
+        let tokens = client_credentials_token(
+            &token_endpoint,
+            "gateway-client",
+            Some("gateway-secret"),
+            &["mcp:tools".to_string(), "mcp:read".to_string()],
+            Some("https://mcp.example.com/mcp"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(tokens.access_token, "access-1");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0]["grant_type"], "client_credentials");
+        assert_eq!(requests[0]["client_id"], "gateway-client");
+        assert_eq!(requests[0]["client_secret"], "gateway-secret");
+        assert_eq!(requests[0]["scope"], "mcp:tools mcp:read");
+        assert_eq!(requests[0]["resource"], "https://mcp.example.com/mcp");
+    }
+
+    #[tokio::test]
+    async fn revocation_sends_refresh_token_then_access_token() {
+        let requests = Requests::default();
+        let router = Router::new()
+            .route(
+                "/revoke",
+                post(
+                    |State(requests): State<Requests>,
+                     Form(form): Form<HashMap<String, String>>| async move {
+                        requests.lock().unwrap().push(form);
+                    },
+                ),
+            )
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let mut oauth_state = OAuthState {
+            auth_server_metadata: metadata("https://auth.example.com/token"),
+            client_id: Some("client-1".into()),
+            client_secret: None,
+            tokens: Some(OAuthTokens {
+                access_token: "access-1".into(),
+                refresh_token: Some("refresh-1".into()),
+                expires_in: Some(3600),
+                obtained_at: 0,
+                scope: None,
+            }),
+            resource: None,
+            mode: AuthMode::Interactive,
+            last_refreshed_at: None,
+        };
+        assert!(!revoke_tokens(&oauth_state).await.unwrap());
+
+        oauth_state.auth_server_metadata.revocation_endpoint =
+            Some(format!("http://{addr}/revoke"));
+        assert!(revoke_tokens(&oauth_state).await.unwrap());
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["token"], "refresh-1");
+        assert_eq!(requests[0]["token_type_hint"], "refresh_token");
+        assert_eq!(requests[1]["token"], "access-1");
+        assert_eq!(requests[1]["token_type_hint"], "access_token");
+    }
+
+    #[tokio::test]
+    async fn concurrent_refreshes_run_once() {
+        let oauth_state = OAuthState {
+            auth_server_metadata: metadata("https://auth.example.com/token"),
+            client_id: Some("client-1".into()),
+            client_secret: None,
+            tokens: Some(OAuthTokens {
+                access_token: "access-1".into(),
+                refresh_token: Some("refresh-1".into()),
+                expires_in: Some(3600),
+                obtained_at: 0,
+                scope: None,
+            }),
+            resource: None,
+            mode: AuthMode::Interactive,
+            last_refreshed_at: None,
+        };
+        let store: SharedOAuthStore =
+            tokio::sync::Mutex::new(crate::state::OAuthStore::from_entries(HashMap::from([(
+                "srv".to_string(),
+                oauth_state.clone(),
+            )])));
+
+        let refreshes = Mutex::new(0);
+        let refresh = || async {
+            *refreshes.lock().unwrap() += 1;
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let mut refreshed = oauth_state.clone();
+            refreshed.tokens = Some(OAuthTokens {
+                access_token: "access-2".into(),
+                refresh_token: Some("refresh-2".into()),
+                expires_in: Some(3600),
+                obtained_at: now_secs(),
+                scope: None,
+            });
+            store.lock().await.set("srv".into(), refreshed);
+            Ok("access-2".to_string())
+        };
+
+        let (first, second) = tokio::join!(
+            refresh_serialized(&store, "srv", refresh),
+            refresh_serialized(&store, "srv", refresh)
+        );
+        assert_eq!(first.unwrap(), "access-2");
+        assert_eq!(second.unwrap(), "access-2");
+        assert_eq!(*refreshes.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_refresh_is_reported_as_expired() {
+        let router = Router::new().route(
+            "/token",
+            post(|| async {
+                (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({ "error": "invalid_grant" })),
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let err = refresh_token(
+            &metadata(&format!("http://{addr}/token")),
+            "client-1",
+            None,
+            "refresh-1",
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::AuthExpired(_)), "{err}");
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
@@ -9,7 +9,9 @@ use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
 use axum::routing::post;
 use axum::{Json, Router};
+use futures::future::BoxFuture;
 use futures::stream::Stream;
+use futures::FutureExt;
 use serde_json::Value;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::net::TcpListener;
@@ -17,11 +19,14 @@ use tokio::sync::{broadcast, RwLock};
 use tokio::time::Instant;
 use tracing::{error, info};
 
-use crate::mcp::client::SharedConnections;
+use crate::error::AppError;
+use crate::mcp::client::{CallToolResult, SharedConnections};
 use crate::mcp::http_common::{
     accepted_response, client_accepts_sse, mcp_response, negotiate_version, new_session_id,
     validate_origin,
 };
+use crate::mcp::search::{SearchHit, ToolIndex};
+use crate::mcp::traffic::{self, Direction, Exchange};
 use crate::persistence::save_stats;
 use crate::state::SharedState;
 use crate::stats::{unix_now, StatsStore, ToolCallEntry, ToolStats};
@@ -97,6 +102,14 @@ pub async fn notify_if_tools_changed(
     server_id: &str,
     new_tools: &[crate::state::McpTool],
 ) {
+    // A disconnect leaves the server's last-known tools searchable; see `AppState::tool_catalog`
+    if !new_tools.is_empty() {
+        if let Some(index) = app.try_state::<ToolIndex>() {
+            index.update(server_id, new_tools);
+        }
+        crate::mcp::embeddings::index_server(app, server_id, new_tools);
+    }
+
     let new_hash = hash_tool_names(new_tools);
 
     if let Some(hashes) = app.try_state::<ToolListHashes>() {
@@ -117,14 +130,223 @@ pub async fn notify_if_tools_changed(
     }
 }
 
+/// What the proxy's request handlers need from the rest of the app. The app
+/// implementation reads Tauri managed state; tests build the router around an
+/// in-memory one, so no `AppHandle` is needed there.
+pub(crate) trait ProxyBackend: Send + Sync {
+    /// Server configs, connection state and the discovery setting.
+    fn state(&self) -> &SharedState;
+
+    /// The search index behind `discover_tools`.
+    fn tool_index(&self) -> &ToolIndex;
+
+    /// Rank tools on `servers` by embedding similarity. `None` when embeddings
+    /// aren't available; see [`embeddings::semantic_search`].
+    ///
+    /// [`embeddings::semantic_search`]: crate::mcp::embeddings::semantic_search
+    fn semantic_search<'a>(
+        &'a self,
+        _query: &'a str,
+        _limit: usize,
+        _servers: &'a HashSet<String>,
+    ) -> BoxFuture<'a, Option<Vec<SearchHit>>> {
+        async { None }.boxed()
+    }
+
+    /// Call a tool on a connected server. `None` if the server isn't connected.
+    fn call_tool<'a>(
+        &'a self,
+        server_id: &'a str,
+        tool_name: &'a str,
+        arguments: Value,
+    ) -> BoxFuture<'a, Option<Result<CallToolResult, AppError>>>;
+
+    /// Connect a configured server, e.g. when discovery calls one of its
+    /// last-known tools while it isn't running.
+    fn connect<'a>(&'a self, server_id: &'a str) -> BoxFuture<'a, Result<(), AppError>>;
+
+    /// Count a finished tool call in the usage stats.
+    fn record_call<'a>(
+        &'a self,
+        server_id: &'a str,
+        tool_name: &'a str,
+        client_id: &'a str,
+        duration_ms: u64,
+        is_error: bool,
+    ) -> BoxFuture<'a, ()>;
+
+    /// Calls per `(server ID, tool name)` by a client, or by every client if
+    /// `client_id` is empty. Picks the tools the discovery endpoint pins.
+    fn tool_call_counts<'a>(
+        &'a self,
+        client_id: &'a str,
+    ) -> BoxFuture<'a, HashMap<(String, String), u64>>;
+
+    /// Capture a client request for the traffic inspector; see [`traffic::request`].
+    fn capture_request(
+        &self,
+        _server_id: &str,
+        _session: Option<&str>,
+        _message: &Value,
+    ) -> Option<Exchange> {
+        None
+    }
+
+    /// Capture the proxy's reply; see [`traffic::response`].
+    fn capture_response(
+        &self,
+        _server_id: &str,
+        _session: Option<&str>,
+        _message: &Value,
+        _exchange: Option<Exchange>,
+    ) {
+    }
+}
+
+/// The running app: managed state, live connections, stats and traffic capture.
+pub(crate) struct AppBackend(pub(crate) AppHandle);
+
+impl ProxyBackend for AppBackend {
+    fn state(&self) -> &SharedState {
+        self.0.state::<SharedState>().inner()
+    }
+
+    fn tool_index(&self) -> &ToolIndex {
+        self.0.state::<ToolIndex>().inner()
+    }
+
+    fn semantic_search<'a>(
+        &'a self,
+        query: &'a str,
+        limit: usize,
+        servers: &'a HashSet<String>,
+    ) -> BoxFuture<'a, Option<Vec<SearchHit>>> {
+        crate::mcp::embeddings::semantic_search(&self.0, query, limit, servers).boxed()
+    }
+
+    fn call_tool<'a>(
+        &'a self,
+        server_id: &'a str,
+        tool_name: &'a str,
+        arguments: Value,
+    ) -> BoxFuture<'a, Option<Result<CallToolResult, AppError>>> {
+        async move {
+            // Clone an Arc handle and drop the lock before doing async I/O.
+            // This avoids blocking all other proxy requests while a tool call is in flight.
+            let client = {
+                let connections = self.0.state::<SharedConnections>();
+                let conns = connections.lock().await;
+                conns.get(server_id).cloned()?
+            };
+            Some(
+                crate::commands::connections::call_tool_prompting_sign_in(
+                    &self.0, server_id, client, tool_name, arguments,
+                )
+                .await,
+            )
+        }
+        .boxed()
+    }
+
+    fn connect<'a>(&'a self, server_id: &'a str) -> BoxFuture<'a, Result<(), AppError>> {
+        let app = &self.0;
+        crate::commands::connections::connect_server(
+            app.clone(),
+            app.state(),
+            app.state(),
+            app.state(),
+            server_id.to_string(),
+        )
+        .boxed()
+    }
+
+    fn record_call<'a>(
+        &'a self,
+        server_id: &'a str,
+        tool_name: &'a str,
+        client_id: &'a str,
+        duration_ms: u64,
+        is_error: bool,
+    ) -> BoxFuture<'a, ()> {
+        record_tool_stats(
+            &self.0,
+            server_id,
+            tool_name,
+            client_id,
+            duration_ms,
+            is_error,
+        )
+        .boxed()
+    }
+
+    fn tool_call_counts<'a>(
+        &'a self,
+        client_id: &'a str,
+    ) -> BoxFuture<'a, HashMap<(String, String), u64>> {
+        async move {
+            let stats_store = self.0.state::<StatsStore>();
+            let store = stats_store.read().await;
+            crate::stats::tool_call_counts(&store, client_id)
+        }
+        .boxed()
+    }
+
+    fn capture_request(
+        &self,
+        server_id: &str,
+        session: Option<&str>,
+        message: &Value,
+    ) -> Option<Exchange> {
+        traffic::request(
+            &self.0,
+            server_id,
+            Direction::ClientToProxy,
+            session,
+            message,
+        )
+    }
+
+    fn capture_response(
+        &self,
+        server_id: &str,
+        session: Option<&str>,
+        message: &Value,
+        exchange: Option<Exchange>,
+    ) {
+        traffic::response(
+            &self.0,
+            server_id,
+            Direction::ProxyToClient,
+            session,
+            message,
+            exchange,
+        );
+    }
+}
+
 /// Shared state passed into axum handlers.
 #[derive(Clone)]
 pub(crate) struct ProxyAppState {
-    pub(crate) app_handle: AppHandle,
+    pub(crate) backend: Arc<dyn ProxyBackend>,
     /// Broadcast channel for tool list change notifications.
     pub(crate) notify_tx: broadcast::Sender<String>,
 }
 
+/// The proxy's routes: one endpoint per server plus the discovery endpoint.
+pub(crate) fn router(state: ProxyAppState) -> Router {
+    Router::new()
+        .route(
+            "/mcp/discovery",
+            post(super::discovery::handle_discovery_post)
+                .get(super::discovery::handle_discovery_get),
+        )
+        .route(
+            "/mcp/{server_id}",
+            post(handle_mcp_post).get(handle_mcp_get),
+        )
+        .with_state(state)
+}
+
 /// Start the MCP proxy HTTP server on a random available port.
 pub async fn start_proxy(
     app_handle: AppHandle,
@@ -136,21 +358,10 @@ pub async fn start_proxy(
     app_handle.manage(NotifySender(notify_tx.clone()));
     app_handle.manage(ToolListHashes::new());
 
-    let state = ProxyAppState {
-        app_handle: app_handle.clone(),
+    let app = router(ProxyAppState {
+        backend: Arc::new(AppBackend(app_handle.clone())),
         notify_tx: notify_tx.clone(),
-    };
-
-    let app = Router::new()
-        .route(
-            "/mcp/discovery",
-            post(super::discovery::handle_discovery_post),
-        )
-        .route(
-            "/mcp/{server_id}",
-            post(handle_mcp_post).get(handle_mcp_get),
-        )
-        .with_state(state);
+    });
 
     // Bind to a stable preferred port, falling back to OS-assigned if busy
     let listener = bind_preferred_port().await?;
@@ -263,6 +474,10 @@ async fn handle_mcp_post(
         .and_then(|v| v.to_str().ok())
         .map(String::from);
 
+    let exchange = state
+        .backend
+        .capture_request(&server_id, req_session.as_deref(), &body);
+
     // Per spec: if the message has no "id", it's a notification or response.
     // Notifications must get 202 Accepted with no body.
     if id.is_none() {
@@ -271,8 +486,7 @@ async fn handle_mcp_post(
 
     // Look up the server by ID
     let server_name = {
-        let app_state = state.app_handle.state::<SharedState>();
-        let s = app_state.lock().unwrap();
+        let s = state.backend.state().lock().unwrap();
         s.servers
             .iter()
             .find(|srv| srv.id == server_id)
@@ -284,13 +498,16 @@ async fn handle_mcp_post(
         None => {
             let resp =
                 make_error_response(id, -32602, &format!("No server found with ID: {server_id}"));
+            state
+                .backend
+                .capture_response(&server_id, req_session.as_deref(), &resp, exchange);
             return mcp_response(&resp, req_session.as_deref(), use_sse);
         }
     };
 
     info!("Proxy [{server_name}] {method}");
 
-    match method {
+    let (response, session) = match method {
         "initialize" => {
             // Negotiate protocol version from client's requested version
             let client_version = params
@@ -319,23 +536,26 @@ async fn handle_mcp_post(
                     }
                 }
             });
-            mcp_response(&response, Some(&session_id), use_sse)
+            (response, Some(session_id))
         }
-        "tools/list" => {
-            let response = handle_tools_list(id, &server_id, &state);
-            mcp_response(&response, req_session.as_deref(), use_sse)
-        }
-        "tools/call" => {
-            let response =
-                handle_tools_call(id, params, &server_id, &server_name, &client, &state).await;
-            mcp_response(&response, req_session.as_deref(), use_sse)
-        }
-        _ => {
-            let response =
-                make_error_response(id, -32601, &format!("Method not found: {method}"));
-            mcp_response(&response, req_session.as_deref(), use_sse)
-        }
-    }
+        "tools/list" => (
+            handle_tools_list(id, &server_id, &state),
+            req_session,
+        ),
+        "tools/call" => (
+            handle_tools_call(id, params, &server_id, &server_name, &client, &state).await,
+            req_session,
+        ),
+        _ => (
+            make_error_response(id, -32601, &format!("Method not found: {method}")),
+            req_session,
+        ),
+    };
+
+    state
+        .backend
+        .capture_response(&server_id, session.as_deref(), &response, exchange);
+    mcp_response(&response, session.as_deref(), use_sse)
 }
 
 /// Handle `tools/list` -- return tools for this specific server only.
@@ -374,32 +594,39 @@ async fn handle_tools_call(
         }
     };
 
+    let hidden = {
+        let s = state.backend.state().lock().unwrap();
+        s.servers
+            .iter()
+            .any(|srv| srv.id == server_id && !srv.tool_enabled(&tool_name))
+    };
+    if hidden {
+        return make_error_response(
+            id,
+            -32602,
+            &format!("Tool '{tool_name}' is disabled on server '{server_name}'"),
+        );
+    }
+
     let arguments = params
         .get("arguments")
         .cloned()
         .unwrap_or(serde_json::json!({}));
 
-    // Clone an Arc handle and drop the lock before doing async I/O.
-    // This avoids blocking all other proxy requests while a tool call is in flight.
-    let connections = state.app_handle.state::<SharedConnections>();
-    let client = {
-        let conns = connections.lock().await;
-        match conns.get(server_id).cloned() {
-            Some(c) => c,
-            None => {
-                return make_error_response(
-                    id,
-                    -32602,
-                    &format!("Server '{server_name}' is not connected"),
-                );
-            }
-        }
-    };
-
     info!("Proxy tool call: {server_name}.{tool_name}");
 
     let start = Instant::now();
-    let call_result = client.call_tool(&tool_name, arguments).await;
+    let Some(call_result) = state
+        .backend
+        .call_tool(server_id, &tool_name, arguments)
+        .await
+    else {
+        return make_error_response(
+            id,
+            -32602,
+            &format!("Server '{server_name}' is not connected"),
+        );
+    };
     let duration_ms = start.elapsed().as_millis() as u64;
 
     let (response, is_error) = match call_result {
@@ -439,21 +666,17 @@ async fn handle_tools_call(
     };
 
     // Record stats
-    record_tool_stats(
-        &state.app_handle,
-        server_id,
-        &tool_name,
-        client_id,
-        duration_ms,
-        is_error,
-    )
-    .await;
+    state
+        .backend
+        .record_call(server_id, &tool_name, client_id, duration_ms, is_error)
+        .await;
+    super::discovery::usage_changed(state);
 
     response
 }
 
 /// Record a tool call in the stats store, persist periodically, and emit event.
-pub(crate) async fn record_tool_stats(
+async fn record_tool_stats(
     app: &AppHandle,
     server_id: &str,
     tool_name: &str,
@@ -483,6 +706,9 @@ pub(crate) async fn record_tool_stats(
         tool_stats.errors += 1;
     }
     tool_stats.total_duration_ms += duration_ms;
+    if !client_id.is_empty() {
+        *tool_stats.clients.entry(client_id.to_string()).or_insert(0) += 1;
+    }
 
     // Per-client aggregates
     if !client_id.is_empty() {
@@ -517,17 +743,22 @@ pub(crate) async fn record_tool_stats(
 }
 
 /// Collect tools for a specific server (no namespacing — original tool names).
+/// Disabled tools are left out.
 fn collect_server_tools(server_id: &str, state: &ProxyAppState) -> Vec<Value> {
-    let app_state = state.app_handle.state::<SharedState>();
-    let s = app_state.lock().unwrap();
+    let s = state.backend.state().lock().unwrap();
 
     let conn_state = match s.connections.get(server_id) {
         Some(c) => c,
         None => return Vec::new(),
     };
+    let server = s.servers.iter().find(|srv| srv.id == server_id);
 
     let mut tools = Vec::new();
-    for tool in &conn_state.tools {
+    for tool in conn_state
+        .tools
+        .iter()
+        .filter(|t| server.is_none_or(|srv| srv.tool_enabled(&t.name)))
+    {
         let mut entry = serde_json::json!({
             "name": tool.name,
             "inputSchema": tool.input_schema,
@@ -554,3 +785,160 @@ pub(crate) fn make_error_response(id: Option<Value>, code: i64, message: &str) -
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    use crate::mcp::http_transport::HttpTransport;
+    use crate::mcp::test_support::{self, Reply, ScriptedServer, TestBackend};
+
+    fn backend() -> TestBackend {
+        let files = ScriptedServer::new()
+            .tool("read_file", Reply::Echo)
+            .tool("delete_file", Reply::ToolError("permission denied".into()))
+            .tool("crash", Reply::RpcError(-32000, "backend exploded".into()));
+        TestBackend::new()
+            .server("files", "Files", Some(files))
+            .server("offline", "Offline", None)
+    }
+
+    /// An MCP client connected to one of the proxy's per-server endpoints.
+    async fn client(base: &str, server_id: &str) -> HttpTransport {
+        let url = format!("{base}/mcp/{server_id}?client=test");
+        HttpTransport::connect(&url, HashMap::new(), None, None)
+            .await
+            .unwrap()
+    }
+
+    async fn call(transport: &HttpTransport, tool: &str) -> Result<Value, AppError> {
+        let params = json!({ "name": tool, "arguments": { "path": "/tmp/a" } });
+        let resp = transport.send_request("tools/call", Some(params)).await?;
+        Ok(resp.result.unwrap())
+    }
+
+    #[tokio::test]
+    async fn proxies_a_server_end_to_end() {
+        let backend = Arc::new(backend());
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let transport = client(&base, "files").await;
+
+        let init = transport
+            .send_request(
+                "initialize",
+                Some(json!({ "protocolVersion": "2025-03-26" })),
+            )
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(init["protocolVersion"], "2025-03-26");
+        assert_eq!(init["serverInfo"]["name"], "Agent Hub — Files");
+        transport
+            .send_notification("notifications/initialized", None)
+            .await
+            .unwrap();
+
+        let list = transport
+            .send_request("tools/list", None)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        let names: Vec<&str> = list["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["read_file", "delete_file", "crash"]);
+
+        let result = call(&transport, "read_file").await.unwrap();
+        assert_eq!(result["content"][0]["text"], r#"{"path":"/tmp/a"}"#);
+
+        let result = call(&transport, "delete_file").await.unwrap();
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["content"][0]["text"], "permission denied");
+
+        let err = call(&transport, "crash").await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Protocol(msg) if msg.starts_with("-32603: Tool call failed")),
+            "{err}"
+        );
+
+        let err = transport
+            .send_request("prompts/list", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Protocol(msg) if msg.starts_with("-32601")));
+
+        assert_eq!(
+            backend.calls(),
+            [
+                ("files".to_string(), "read_file".to_string(), false),
+                ("files".to_string(), "delete_file".to_string(), true),
+                ("files".to_string(), "crash".to_string(), true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_and_disconnected_servers() {
+        let backend = Arc::new(backend());
+        let base = test_support::serve_proxy(backend.clone()).await;
+
+        let err = client(&base, "missing")
+            .await
+            .send_request("tools/list", None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::Protocol(msg) if msg.contains("No server found with ID: missing"))
+        );
+
+        let offline = client(&base, "offline").await;
+        let list = offline
+            .send_request("tools/list", None)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(list["tools"], json!([]));
+        let err = call(&offline, "anything").await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Protocol(msg) if msg.contains("Server 'Offline' is not connected"))
+        );
+        assert!(backend.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn hides_disabled_tools() {
+        let backend = Arc::new(backend());
+        backend.disable_tools("files", &["delete_file"]);
+        let base = test_support::serve_proxy(backend.clone()).await;
+        let transport = client(&base, "files").await;
+
+        let list = transport
+            .send_request("tools/list", None)
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        let names: Vec<&str> = list["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["read_file", "crash"]);
+
+        let err = call(&transport, "delete_file").await.unwrap_err();
+        assert!(
+            matches!(&err, AppError::Protocol(msg) if msg.contains("Tool 'delete_file' is disabled")),
+            "{err}"
+        );
+        assert!(backend.calls().is_empty());
+    }
+}
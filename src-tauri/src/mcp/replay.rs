@@ -0,0 +1,460 @@
+//! Record-and-replay for deterministic agent testing.
+//!
+//! A [`Recorder`] saves the `tools/list` and every `tools/call` exchange of a
+//! real server to a fixture file. A replay server ([`ReplayTransport`]) serves
+//! that fixture back: the recorded tool list, and recorded results for calls
+//! whose arguments match under the configured [`MatchStrategy`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::mcp::client::CallToolResult;
+use crate::mcp::types::{JsonRpcError, JsonRpcResponse, McpToolDef};
+use crate::state::{MatchStrategy, ReplayConfig};
+
+const FIXTURE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+    pub version: u32,
+    /// Name of the server the fixture was recorded from.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub server_name: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub recorded_at: u64,
+    pub tools: Vec<McpToolDef>,
+    #[serde(default)]
+    pub exchanges: Vec<RecordedCall>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedCall {
+    pub tool: String,
+    pub arguments: Value,
+    pub result: CallToolResult,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Validation(format!("Cannot read fixture {}: {e}", path.display()))
+        })?;
+        let fixture: Fixture = serde_json::from_str(&content)?;
+        if fixture.version > FIXTURE_VERSION {
+            return Err(AppError::Validation(format!(
+                "Fixture {} has unsupported version {}",
+                path.display(),
+                fixture.version
+            )));
+        }
+        Ok(fixture)
+    }
+
+    fn save(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recording
+// ---------------------------------------------------------------------------
+
+struct Recording {
+    path: PathBuf,
+    fixture: Fixture,
+}
+
+/// Active recordings, keyed by server ID.
+pub struct Recorder {
+    active: Mutex<HashMap<String, Recording>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStatus {
+    pub server_id: String,
+    pub path: String,
+    pub exchanges: usize,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self {
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start recording a server. The fixture is written immediately (with the
+    /// tool list) and rewritten after every call.
+    pub fn start(
+        &self,
+        server_id: &str,
+        server_name: &str,
+        path: PathBuf,
+        tools: Vec<McpToolDef>,
+    ) -> Result<(), AppError> {
+        let fixture = Fixture {
+            version: FIXTURE_VERSION,
+            server_name: Some(server_name.to_string()),
+            recorded_at: crate::stats::unix_now(),
+            tools,
+            exchanges: Vec::new(),
+        };
+        fixture.save(&path)?;
+        self.active
+            .lock()
+            .unwrap()
+            .insert(server_id.to_string(), Recording { path, fixture });
+        Ok(())
+    }
+
+    /// Append a call to the server's recording, if one is active.
+    pub fn record(&self, server_id: &str, tool: &str, arguments: &Value, result: &CallToolResult) {
+        let mut active = self.active.lock().unwrap();
+        let Some(recording) = active.get_mut(server_id) else {
+            return;
+        };
+        recording.fixture.exchanges.push(RecordedCall {
+            tool: tool.to_string(),
+            arguments: arguments.clone(),
+            result: result.clone(),
+        });
+        if let Err(e) = recording.fixture.save(&recording.path) {
+            tracing::warn!("Failed to write fixture for {server_id}: {e}");
+        }
+    }
+
+    pub fn stop(&self, server_id: &str) -> Option<RecordingStatus> {
+        let recording = self.active.lock().unwrap().remove(server_id)?;
+        Some(status(server_id, &recording))
+    }
+
+    pub fn list(&self) -> Vec<RecordingStatus> {
+        self.active
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, recording)| status(id, recording))
+            .collect()
+    }
+}
+
+fn status(server_id: &str, recording: &Recording) -> RecordingStatus {
+    RecordingStatus {
+        server_id: server_id.to_string(),
+        path: recording.path.to_string_lossy().into_owned(),
+        exchanges: recording.fixture.exchanges.len(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Replay
+// ---------------------------------------------------------------------------
+
+/// Serves a fixture as if it were a live server.
+pub struct ReplayTransport {
+    fixture: Fixture,
+    matching: MatchStrategy,
+    ignore_fields: Vec<String>,
+    /// Which exchanges have been served, so repeated identical calls replay
+    /// repeated recordings in order.
+    served: Mutex<Vec<bool>>,
+    next_id: AtomicU64,
+}
+
+impl ReplayTransport {
+    pub fn open(config: &ReplayConfig) -> Result<Self, AppError> {
+        let path = crate::mcp::env::resolve_path(&config.fixture, None);
+        let fixture = Fixture::load(&path)?;
+        Ok(Self {
+            served: Mutex::new(vec![false; fixture.exchanges.len()]),
+            next_id: AtomicU64::new(1),
+            fixture,
+            matching: config.matching,
+            ignore_fields: config.ignore_fields.clone(),
+        })
+    }
+
+    /// Reserve a request ID, so replayed responses carry IDs like live ones.
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    pub async fn send_request_with_id(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        let result = match method {
+            "initialize" => Ok(serde_json::json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": format!(
+                        "Replay — {}",
+                        self.fixture.server_name.as_deref().unwrap_or("fixture")
+                    ),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "tools/list" => Ok(serde_json::json!({ "tools": self.fixture.tools })),
+            "tools/call" => {
+                let params = params.unwrap_or_default();
+                let name = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let arguments = params.get("arguments").cloned().unwrap_or_default();
+                Ok(serde_json::to_value(self.call(name, &arguments))?)
+            }
+            "ping" => Ok(serde_json::json!({})),
+            _ => Err(JsonRpcError {
+                code: -32601,
+                message: format!("Method not found: {method}"),
+                data: None,
+            }),
+        };
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Ok(JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            id: Some(id.into()),
+            result,
+            error,
+        })
+    }
+
+    /// The recorded result for a call. Unmatched calls get a tool error so the
+    /// agent under test sees what went wrong.
+    fn call(&self, tool: &str, arguments: &Value) -> CallToolResult {
+        let mut served = self.served.lock().unwrap();
+        let candidates: Vec<usize> = self
+            .fixture
+            .exchanges
+            .iter()
+            .enumerate()
+            .filter(|(_, call)| {
+                call.tool == tool
+                    && arguments_match(
+                        self.matching,
+                        &self.ignore_fields,
+                        &call.arguments,
+                        arguments,
+                    )
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        // First unserved match, then keep repeating the last one
+        let Some(index) = candidates
+            .iter()
+            .copied()
+            .find(|&i| !served[i])
+            .or_else(|| candidates.last().copied())
+        else {
+            return CallToolResult {
+                content: vec![crate::mcp::types::McpContent {
+                    r#type: "text".into(),
+                    text: Some(format!(
+                        "No recorded result for {tool} with arguments {arguments}"
+                    )),
+                    data: None,
+                    mime_type: None,
+                }],
+                is_error: Some(true),
+            };
+        };
+        served[index] = true;
+        self.fixture.exchanges[index].result.clone()
+    }
+}
+
+pub fn arguments_match(
+    strategy: MatchStrategy,
+    ignore_fields: &[String],
+    recorded: &Value,
+    actual: &Value,
+) -> bool {
+    match strategy {
+        MatchStrategy::Exact => recorded == actual,
+        MatchStrategy::Canonical => canonicalize(recorded) == canonicalize(actual),
+        MatchStrategy::IgnoreFields => {
+            let mut recorded = canonicalize(recorded);
+            let mut actual = canonicalize(actual);
+            for field in ignore_fields {
+                remove_path(&mut recorded, field);
+                remove_path(&mut actual, field);
+            }
+            recorded == actual
+        }
+    }
+}
+
+/// Normalize a value so equivalent JSON compares equal: integral floats
+/// become integers and null object fields are dropped.
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), canonicalize(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() && f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+                Value::from(f as i64)
+            }
+            _ => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+/// Remove a dot-separated field path from nested objects.
+fn remove_path(value: &mut Value, path: &str) {
+    let mut current = value;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let Some(map) = current.as_object_mut() else {
+            return;
+        };
+        if parts.peek().is_none() {
+            map.remove(part);
+            return;
+        }
+        let Some(next) = map.get_mut(part) else {
+            return;
+        };
+        current = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn text_result(text: &str) -> CallToolResult {
+        CallToolResult {
+            content: vec![crate::mcp::types::McpContent {
+                r#type: "text".into(),
+                text: Some(text.into()),
+                data: None,
+                mime_type: None,
+            }],
+            is_error: None,
+        }
+    }
+
+    fn transport(
+        matching: MatchStrategy,
+        ignore: &[&str],
+        calls: Vec<RecordedCall>,
+    ) -> ReplayTransport {
+        ReplayTransport {
+            served: Mutex::new(vec![false; calls.len()]),
+            next_id: AtomicU64::new(1),
+            fixture: Fixture {
+                version: FIXTURE_VERSION,
+                server_name: None,
+                recorded_at: 0,
+                tools: Vec::new(),
+                exchanges: calls,
+            },
+            matching,
+            ignore_fields: ignore.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn call(tool: &str, arguments: Value, text: &str) -> RecordedCall {
+        RecordedCall {
+            tool: tool.into(),
+            arguments,
+            result: text_result(text),
+        }
+    }
+
+    fn text(result: &CallToolResult) -> &str {
+        result.content[0].text.as_deref().unwrap()
+    }
+
+    #[test]
+    fn matching_strategies() {
+        let recorded = json!({ "limit": 10.0, "cursor": null, "meta": { "requestId": "a" } });
+        let actual = json!({ "limit": 10, "meta": { "requestId": "b" } });
+
+        assert!(!arguments_match(
+            MatchStrategy::Exact,
+            &[],
+            &recorded,
+            &actual
+        ));
+        assert!(!arguments_match(
+            MatchStrategy::Canonical,
+            &[],
+            &recorded,
+            &actual
+        ));
+        assert!(arguments_match(
+            MatchStrategy::IgnoreFields,
+            &["meta.requestId".into()],
+            &recorded,
+            &actual
+        ));
+        assert!(arguments_match(
+            MatchStrategy::Canonical,
+            &[],
+            &json!({ "a": 1.0, "b": null }),
+            &json!({ "a": 1 })
+        ));
+        assert!(arguments_match(
+            MatchStrategy::Exact,
+            &[],
+            &json!({ "a": 1, "b": 2 }),
+            &json!({ "b": 2, "a": 1 })
+        ));
+    }
+
+    #[test]
+    fn replays_repeated_calls_in_order_and_reports_misses() {
+        let replay = transport(
+            MatchStrategy::Canonical,
+            &[],
+            vec![
+                call("read", json!({ "path": "a" }), "first"),
+                call("read", json!({ "path": "a" }), "second"),
+                call("read", json!({ "path": "b" }), "other"),
+            ],
+        );
+
+        assert_eq!(text(&replay.call("read", &json!({ "path": "a" }))), "first");
+        assert_eq!(
+            text(&replay.call("read", &json!({ "path": "a" }))),
+            "second"
+        );
+        assert_eq!(
+            text(&replay.call("read", &json!({ "path": "a" }))),
+            "second"
+        );
+        assert_eq!(text(&replay.call("read", &json!({ "path": "b" }))), "other");
+
+        let miss = replay.call("write", &json!({}));
+        assert_eq!(miss.is_error, Some(true));
+    }
+}
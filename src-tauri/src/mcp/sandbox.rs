@@ -0,0 +1,403 @@
+//! Sandboxing for stdio MCP servers on Linux.
+//!
+//! Rather than forking with a custom `pre_exec` (the shell plugin doesn't expose
+//! one), the server command is wrapped in standard tools:
+//!
+//! ```text
+//! systemd-run --user --scope ... -- prlimit ... -- bwrap ... -- <command> <args>
+//! ```
+//!
+//! - `systemd-run --scope` puts the process in a transient cgroup (`MemoryMax`, `CPUQuota`).
+//! - `prlimit` sets rlimits (`RLIMIT_CPU`, and `RLIMIT_AS` when cgroups are unavailable).
+//! - `bwrap` (bubblewrap) provides the read-only root, writable binds, private `/tmp`
+//!   and network namespace.
+//!
+//! Missing tools fail the spawn rather than silently running unsandboxed.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::state::SandboxConfig;
+
+/// Wrapper binaries found on the host.
+#[derive(Debug, Clone, Default)]
+pub struct SandboxTools {
+    pub bwrap: Option<PathBuf>,
+    pub systemd_run: Option<PathBuf>,
+    pub prlimit: Option<PathBuf>,
+}
+
+impl SandboxTools {
+    /// Look up the wrapper binaries on the login-shell `PATH`.
+    pub fn detect() -> Self {
+        Self {
+            bwrap: find_in_path("bwrap"),
+            systemd_run: find_in_path("systemd-run"),
+            prlimit: find_in_path("prlimit"),
+        }
+    }
+}
+
+/// Human-readable summary of a server's sandbox, returned to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxProfile {
+    pub enabled: bool,
+    /// Whether the host can enforce this profile.
+    pub supported: bool,
+    /// One line per active restriction, e.g. "Memory limit: 512 MiB (cgroup)".
+    pub rules: Vec<String>,
+    /// Why the profile can't be applied, if `supported` is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    /// The full wrapped command line that will be spawned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<Vec<String>>,
+}
+
+/// Find an executable by name on the login-shell `PATH`.
+pub(crate) fn find_in_path(name: &str) -> Option<PathBuf> {
+    crate::shell_env::get().which(name)
+}
+
+/// Expand a leading `~` to the user's home directory.
+pub(crate) fn expand_home(path: &str) -> String {
+    if path == "~" {
+        if let Some(home) = dirs::home_dir() {
+            return home.to_string_lossy().into_owned();
+        }
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest).to_string_lossy().into_owned();
+        }
+    }
+    path.to_string()
+}
+
+fn tool_path(path: &Option<PathBuf>, name: &str, purpose: &str) -> Result<String, AppError> {
+    path.as_ref()
+        .map(|p| p.to_string_lossy().into_owned())
+        .ok_or_else(|| AppError::DependencyNotFound(format!("{name} (required for {purpose})")))
+}
+
+/// Whether the profile needs bubblewrap (filesystem or network isolation).
+fn needs_bwrap(config: &SandboxConfig) -> bool {
+    config.read_only_root || !config.network || config.private_tmp
+}
+
+/// Build the full argv (program first) that runs `command` inside the sandbox.
+pub fn wrap_command(
+    config: &SandboxConfig,
+    tools: &SandboxTools,
+    command: &str,
+    args: &[String],
+) -> Result<Vec<String>, AppError> {
+    if !cfg!(target_os = "linux") {
+        return Err(AppError::Validation(
+            "Server sandboxing is only supported on Linux".into(),
+        ));
+    }
+
+    let mut argv: Vec<String> = Vec::new();
+
+    // 1. cgroup limits via a transient systemd scope
+    let wants_cgroup = config.memory_limit_mb.is_some() || config.cpu_quota_percent.is_some();
+    let use_cgroup = wants_cgroup && tools.systemd_run.is_some();
+    if use_cgroup {
+        argv.push(tool_path(
+            &tools.systemd_run,
+            "systemd-run",
+            "cgroup limits",
+        )?);
+        argv.extend(["--user", "--scope", "--quiet", "--collect"].map(String::from));
+        if let Some(mb) = config.memory_limit_mb {
+            argv.push("-p".into());
+            argv.push(format!("MemoryMax={mb}M"));
+            argv.push("-p".into());
+            argv.push("MemorySwapMax=0".into());
+        }
+        if let Some(pct) = config.cpu_quota_percent {
+            argv.push("-p".into());
+            argv.push(format!("CPUQuota={pct}%"));
+        }
+        argv.push("--".into());
+    } else if config.cpu_quota_percent.is_some() {
+        return Err(AppError::DependencyNotFound(
+            "systemd-run (required for CPU quota)".into(),
+        ));
+    }
+
+    // 2. rlimits — CPU time always, address space only as a memory fallback
+    let rlimit_memory = config.memory_limit_mb.filter(|_| !use_cgroup);
+    if config.cpu_time_secs.is_some() || rlimit_memory.is_some() {
+        argv.push(tool_path(&tools.prlimit, "prlimit", "resource limits")?);
+        if let Some(secs) = config.cpu_time_secs {
+            argv.push(format!("--cpu={secs}"));
+        }
+        if let Some(mb) = rlimit_memory {
+            argv.push(format!("--as={}", mb.saturating_mul(1024 * 1024)));
+        }
+        argv.push("--".into());
+    }
+
+    // 3. Filesystem + network isolation
+    if needs_bwrap(config) {
+        argv.push(tool_path(
+            &tools.bwrap,
+            "bwrap",
+            "filesystem/network isolation",
+        )?);
+        argv.push("--die-with-parent".into());
+        if config.read_only_root {
+            argv.extend(["--ro-bind", "/", "/"].map(String::from));
+        } else {
+            argv.extend(["--bind", "/", "/"].map(String::from));
+        }
+        argv.extend(["--dev", "/dev", "--proc", "/proc"].map(String::from));
+        if config.private_tmp {
+            argv.extend(["--tmpfs", "/tmp"].map(String::from));
+        }
+        for path in &config.writable_paths {
+            let expanded = expand_home(path);
+            argv.push("--bind-try".into());
+            argv.push(expanded.clone());
+            argv.push(expanded);
+        }
+        if !config.network {
+            argv.push("--unshare-net".into());
+        }
+        argv.push("--".into());
+    }
+
+    argv.push(command.to_string());
+    argv.extend(args.iter().cloned());
+    Ok(argv)
+}
+
+/// Describe the restrictions a profile applies, for display and logs.
+pub fn describe(
+    config: &SandboxConfig,
+    tools: &SandboxTools,
+    command: &str,
+    args: &[String],
+) -> SandboxProfile {
+    if !config.enabled {
+        return SandboxProfile {
+            enabled: false,
+            supported: true,
+            rules: vec!["Runs with full user privileges".into()],
+            problem: None,
+            command_line: None,
+        };
+    }
+
+    let mut rules = Vec::new();
+    let cgroup = tools.systemd_run.is_some();
+    if let Some(mb) = config.memory_limit_mb {
+        let mechanism = if cgroup {
+            "cgroup"
+        } else {
+            "address-space rlimit"
+        };
+        rules.push(format!("Memory limit: {mb} MiB ({mechanism})"));
+    }
+    if let Some(pct) = config.cpu_quota_percent {
+        rules.push(format!("CPU quota: {pct}% of one core"));
+    }
+    if let Some(secs) = config.cpu_time_secs {
+        rules.push(format!("CPU time limit: {secs}s"));
+    }
+    if config.read_only_root {
+        rules.push("Filesystem: read-only".into());
+    } else {
+        rules.push("Filesystem: read-write".into());
+    }
+    for path in &config.writable_paths {
+        let expanded = expand_home(path);
+        if Path::new(&expanded).exists() {
+            rules.push(format!("Writable: {expanded}"));
+        } else {
+            rules.push(format!("Writable: {expanded} (missing, not mounted)"));
+        }
+    }
+    rules.push(if config.network {
+        "Network: allowed".into()
+    } else {
+        "Network: blocked".into()
+    });
+    if config.private_tmp {
+        rules.push("Private /tmp".into());
+    }
+
+    match wrap_command(config, tools, command, args) {
+        Ok(argv) => SandboxProfile {
+            enabled: true,
+            supported: true,
+            rules,
+            problem: None,
+            command_line: Some(argv),
+        },
+        Err(e) => SandboxProfile {
+            enabled: true,
+            supported: false,
+            rules,
+            problem: Some(e.to_string()),
+            command_line: None,
+        },
+    }
+}
+
+/// Recognize stderr output caused by a sandbox restriction.
+pub fn detect_violation(config: &SandboxConfig, text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    if lower.starts_with("bwrap:") {
+        return Some("sandbox setup failed");
+    }
+    if config.read_only_root && (lower.contains("read-only file system") || lower.contains("erofs"))
+    {
+        return Some("write blocked by read-only filesystem");
+    }
+    if !config.network
+        && (lower.contains("network is unreachable")
+            || lower.contains("enetunreach")
+            || lower.contains("eai_again")
+            || lower.contains("temporary failure in name resolution"))
+    {
+        return Some("network access blocked");
+    }
+    if config.memory_limit_mb.is_some()
+        && (lower.contains("cannot allocate memory")
+            || lower.contains("out of memory")
+            || lower.contains("memoryerror")
+            || lower.contains("enomem"))
+    {
+        return Some("memory limit reached");
+    }
+    None
+}
+
+/// Explain a process exit that was likely caused by a sandbox limit.
+pub fn describe_termination(config: &SandboxConfig, signal: Option<i32>) -> Option<String> {
+    match signal {
+        // SIGKILL — the cgroup OOM killer
+        Some(9) => config
+            .memory_limit_mb
+            .map(|mb| format!("killed by SIGKILL, likely exceeded memory limit of {mb} MiB")),
+        // SIGXCPU — RLIMIT_CPU exceeded
+        Some(24) => config
+            .cpu_time_secs
+            .map(|secs| format!("killed by SIGXCPU after exceeding CPU time limit of {secs}s")),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn all_tools() -> SandboxTools {
+        SandboxTools {
+            bwrap: Some(PathBuf::from("/usr/bin/bwrap")),
+            systemd_run: Some(PathBuf::from("/usr/bin/systemd-run")),
+            prlimit: Some(PathBuf::from("/usr/bin/prlimit")),
+        }
+    }
+
+    fn enabled() -> SandboxConfig {
+        SandboxConfig {
+            enabled: true,
+            ..SandboxConfig::default()
+        }
+    }
+
+    #[test]
+    fn default_profile_wraps_in_bwrap_only() {
+        let argv = wrap_command(
+            &enabled(),
+            &all_tools(),
+            "npx",
+            &["-y".into(), "srv".into()],
+        )
+        .expect("wrap");
+        assert_eq!(argv[0], "/usr/bin/bwrap");
+        assert!(argv.windows(3).any(|w| w == ["--ro-bind", "/", "/"]));
+        assert!(argv.windows(2).any(|w| w == ["--tmpfs", "/tmp"]));
+        assert!(!argv.contains(&"--unshare-net".to_string()));
+        assert_eq!(&argv[argv.len() - 3..], ["npx", "-y", "srv"]);
+    }
+
+    #[test]
+    fn limits_use_cgroup_and_prlimit() {
+        let config = SandboxConfig {
+            memory_limit_mb: Some(512),
+            cpu_quota_percent: Some(50),
+            cpu_time_secs: Some(300),
+            network: false,
+            ..enabled()
+        };
+        let argv = wrap_command(&config, &all_tools(), "node", &[]).expect("wrap");
+        assert_eq!(argv[0], "/usr/bin/systemd-run");
+        assert!(argv.contains(&"MemoryMax=512M".to_string()));
+        assert!(argv.contains(&"CPUQuota=50%".to_string()));
+        assert!(argv.contains(&"--cpu=300".to_string()));
+        // Memory is enforced by the cgroup, so no address-space rlimit
+        assert!(!argv.iter().any(|a| a.starts_with("--as=")));
+        assert!(argv.contains(&"--unshare-net".to_string()));
+    }
+
+    #[test]
+    fn memory_falls_back_to_rlimit_without_systemd() {
+        let config = SandboxConfig {
+            memory_limit_mb: Some(1),
+            read_only_root: false,
+            private_tmp: false,
+            ..enabled()
+        };
+        let tools = SandboxTools {
+            systemd_run: None,
+            ..all_tools()
+        };
+        let argv = wrap_command(&config, &tools, "node", &[]).expect("wrap");
+        assert_eq!(argv, ["/usr/bin/prlimit", "--as=1048576", "--", "node"]);
+
+        // Absurd limits clamp instead of overflowing
+        let config = SandboxConfig {
+            memory_limit_mb: Some(u64::MAX),
+            ..config
+        };
+        let argv = wrap_command(&config, &tools, "node", &[]).expect("wrap");
+        assert_eq!(argv[1], format!("--as={}", u64::MAX));
+    }
+
+    #[test]
+    fn missing_bwrap_fails_closed() {
+        let tools = SandboxTools {
+            bwrap: None,
+            ..all_tools()
+        };
+        let err = wrap_command(&enabled(), &tools, "node", &[]).unwrap_err();
+        assert!(matches!(err, AppError::DependencyNotFound(_)));
+    }
+
+    #[test]
+    fn detects_violations_only_for_active_rules() {
+        let config = SandboxConfig {
+            network: false,
+            ..enabled()
+        };
+        assert_eq!(
+            detect_violation(&config, "Error: EROFS: read-only file system, open '/x'"),
+            Some("write blocked by read-only filesystem")
+        );
+        assert_eq!(
+            detect_violation(&config, "getaddrinfo EAI_AGAIN api.example.com"),
+            Some("network access blocked")
+        );
+        assert_eq!(
+            detect_violation(&config, "JavaScript heap out of memory"),
+            None
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! Ranked tool search for discovery mode.
+//!
+//! An in-memory inverted index over every connected server's tools, scored
+//! with BM25. Fields are weighted (a hit in the name counts for more than one
+//! in the description), identifiers are split on `snake_case` and `camelCase`
+//! boundaries, and query terms that aren't in the index fall back to prefix
+//! and small-typo matches.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::state::McpTool;
+
+/// BM25 term-frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 document-length normalization.
+const B: f64 = 0.75;
+
+const NAME_WEIGHT: f64 = 3.0;
+const TITLE_WEIGHT: f64 = 2.0;
+const PARAM_WEIGHT: f64 = 1.5;
+const DESCRIPTION_WEIGHT: f64 = 1.0;
+
+/// Score multiplier for an index term the query term is a prefix of.
+const PREFIX_FACTOR: f64 = 0.8;
+/// Score multiplier for an index term within the typo budget.
+const TYPO_FACTOR: f64 = 0.6;
+
+/// Dampens the weight of top ranks in [`fuse`]; 60 is the usual choice.
+const RRF_K: f64 = 60.0;
+
+/// Words too common in tool descriptions to say anything about a match.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "by", "for", "from", "in", "is", "of", "on", "or", "the", "to", "with",
+];
+
+/// One tool in the index.
+#[derive(Debug, Clone)]
+struct Document {
+    server_id: String,
+    tool_name: String,
+    /// Sum of field weights over the document's tokens.
+    length: f64,
+}
+
+#[derive(Default)]
+struct Inner {
+    documents: Vec<Document>,
+    /// Term → (document index, weighted term frequency).
+    postings: HashMap<String, Vec<(usize, f64)>>,
+    average_length: f64,
+}
+
+/// A ranked search result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub server_id: String,
+    pub tool_name: String,
+    pub score: f64,
+}
+
+/// Inverted index over the tools of every connected server.
+pub struct ToolIndex {
+    /// Each server's tools, as last reported. The postings are rebuilt from
+    /// these on every update.
+    tools: RwLock<HashMap<String, Vec<McpTool>>>,
+    inner: RwLock<Inner>,
+}
+
+impl ToolIndex {
+    pub fn new() -> Self {
+        Self {
+            tools: RwLock::new(HashMap::new()),
+            inner: RwLock::new(Inner::default()),
+        }
+    }
+
+    /// Replace a server's tools. An empty list removes the server.
+    pub fn update(&self, server_id: &str, tools: &[McpTool]) {
+        let mut by_server = self.tools.write().unwrap();
+        if tools.is_empty() {
+            by_server.remove(server_id);
+        } else {
+            by_server.insert(server_id.to_string(), tools.to_vec());
+        }
+        *self.inner.write().unwrap() = build(&by_server);
+    }
+
+    /// The best matches for `query`, highest score first. `include` decides
+    /// which servers' tools are eligible.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        include: impl Fn(&str) -> bool,
+    ) -> Vec<SearchHit> {
+        let inner = self.inner.read().unwrap();
+        let total = inner.documents.len() as f64;
+        if total == 0.0 {
+            return Vec::new();
+        }
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for term in tokenize(query) {
+            for (index_term, factor) in expand(&inner, &term) {
+                let postings = &inner.postings[index_term];
+                let df = postings.len() as f64;
+                let idf = (1.0 + (total - df + 0.5) / (df + 0.5)).ln();
+                for &(doc, tf) in postings {
+                    let length = inner.documents[doc].length / inner.average_length;
+                    let saturated = tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length));
+                    *scores.entry(doc).or_default() += factor * idf * saturated;
+                }
+            }
+        }
+
+        let mut hits: Vec<SearchHit> = scores
+            .into_iter()
+            .map(|(doc, score)| (&inner.documents[doc], score))
+            .filter(|(doc, _)| include(&doc.server_id))
+            .map(|(doc, score)| SearchHit {
+                server_id: doc.server_id.clone(),
+                tool_name: doc.tool_name.clone(),
+                score,
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.tool_name.cmp(&b.tool_name))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Merge two rankings with reciprocal rank fusion: each hit scores
+/// `1 / (RRF_K + rank)` in every list it appears in. Ranks are compared rather
+/// than scores, since BM25 and cosine similarity aren't on the same scale.
+pub fn fuse(keyword: &[SearchHit], semantic: &[SearchHit], limit: usize) -> Vec<SearchHit> {
+    let mut fused: Vec<SearchHit> = Vec::new();
+    for ranking in [keyword, semantic] {
+        for (rank, hit) in ranking.iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f64 + 1.0);
+            match fused
+                .iter_mut()
+                .find(|f| f.server_id == hit.server_id && f.tool_name == hit.tool_name)
+            {
+                Some(existing) => existing.score += score,
+                None => fused.push(SearchHit {
+                    score,
+                    ..hit.clone()
+                }),
+            }
+        }
+    }
+    fused.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.tool_name.cmp(&b.tool_name))
+    });
+    fused.truncate(limit);
+    fused
+}
+
+fn build(by_server: &HashMap<String, Vec<McpTool>>) -> Inner {
+    let mut inner = Inner::default();
+    for (server_id, tools) in by_server {
+        for tool in tools {
+            let doc = inner.documents.len();
+            let mut frequencies: HashMap<String, f64> = HashMap::new();
+            let mut add = |text: &str, weight: f64| {
+                for token in tokenize(text) {
+                    *frequencies.entry(token).or_default() += weight;
+                }
+            };
+            add(&tool.name, NAME_WEIGHT);
+            add(tool.title.as_deref().unwrap_or_default(), TITLE_WEIGHT);
+            add(
+                tool.description.as_deref().unwrap_or_default(),
+                DESCRIPTION_WEIGHT,
+            );
+            for param in param_names(tool) {
+                add(param, PARAM_WEIGHT);
+            }
+
+            let length = frequencies.values().sum();
+            for (term, tf) in frequencies {
+                inner.postings.entry(term).or_default().push((doc, tf));
+            }
+            inner.documents.push(Document {
+                server_id: server_id.clone(),
+                tool_name: tool.name.clone(),
+                length,
+            });
+        }
+    }
+    let total: f64 = inner.documents.iter().map(|d| d.length).sum();
+    inner.average_length = (total / inner.documents.len().max(1) as f64).max(1.0);
+    inner
+}
+
+fn param_names(tool: &McpTool) -> impl Iterator<Item = &str> {
+    tool.input_schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(|props| props.as_object())
+        .into_iter()
+        .flat_map(|props| props.keys().map(String::as_str))
+}
+
+/// Index terms a query term matches, with a score factor: itself if it's
+/// indexed, otherwise terms it's a prefix of and terms within a small edit
+/// distance.
+fn expand<'a>(inner: &'a Inner, term: &str) -> Vec<(&'a String, f64)> {
+    if let Some((key, _)) = inner.postings.get_key_value(term) {
+        return vec![(key, 1.0)];
+    }
+    let budget = typo_budget(term);
+    inner
+        .postings
+        .keys()
+        .filter_map(|candidate| {
+            if term.len() >= 3 && candidate.starts_with(term) {
+                Some((candidate, PREFIX_FACTOR))
+            } else if budget > 0 && edit_distance(term, candidate) <= budget {
+                Some((candidate, TYPO_FACTOR))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Edits tolerated for a query term: none for short words, where a typo is
+/// as likely to be a different word.
+fn typo_budget(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Levenshtein distance, counting an adjacent transposition as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > 2 {
+        return usize::MAX;
+    }
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Lowercase words, with identifiers split on `_`, `-`, `.` and camelCase
+/// boundaries: `getPullRequests` and `get_pull_requests` both give
+/// `get`, `pull`, `request`.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = word.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, cur) = (chars[i - 1], chars[i]);
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            // fooBar, or the last capital of an acronym: HTTPServer → HTTP, Server
+            let boundary = (prev.is_lowercase() && cur.is_uppercase())
+                || (prev.is_uppercase() && cur.is_uppercase() && next_lower)
+                || (prev.is_alphabetic() != cur.is_alphabetic());
+            if boundary {
+                tokens.push(chars[start..i].iter().collect::<String>());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            tokens.push(chars[start..].iter().collect::<String>());
+        }
+    }
+    tokens
+        .into_iter()
+        .map(|t| stem(&t.to_lowercase()))
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Fold simple plurals, so `issues` finds `issue` and `repos` finds `repo`.
+fn stem(token: &str) -> String {
+    match token.strip_suffix('s') {
+        Some(stem) if stem.len() >= 3 && !stem.ends_with('s') => stem.to_string(),
+        _ => token.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(server: &str, name: &str, description: &str, params: &[&str]) -> McpTool {
+        let properties: serde_json::Map<String, serde_json::Value> = params
+            .iter()
+            .map(|p| (p.to_string(), json!({ "type": "string" })))
+            .collect();
+        McpTool {
+            name: name.to_string(),
+            title: None,
+            description: Some(description.to_string()),
+            input_schema: Some(json!({ "type": "object", "properties": properties })),
+            server_id: server.to_string(),
+            server_name: server.to_string(),
+        }
+    }
+
+    fn index() -> ToolIndex {
+        let index = ToolIndex::new();
+        index.update(
+            "github",
+            &[
+                tool(
+                    "github",
+                    "gh_issue_new",
+                    "Open a new issue in a repository",
+                    &["repo", "title"],
+                ),
+                tool(
+                    "github",
+                    "createPullRequest",
+                    "Create a pull request",
+                    &["repo", "head", "base"],
+                ),
+                tool(
+                    "github",
+                    "list_issues",
+                    "List issues in a repository",
+                    &["repo", "state"],
+                ),
+            ],
+        );
+        index.update(
+            "slack",
+            &[
+                tool(
+                    "slack",
+                    "post_message",
+                    "Post to a channel",
+                    &["channel", "text"],
+                ),
+                tool(
+                    "slack",
+                    "list_channels",
+                    "Channels a message can go to",
+                    &[],
+                ),
+            ],
+        );
+        index
+    }
+
+    fn names(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.tool_name.as_str()).collect()
+    }
+
+    #[test]
+    fn splits_identifiers() {
+        assert_eq!(tokenize("getPullRequests"), ["get", "pull", "request"]);
+        assert_eq!(tokenize("gh_issue_new"), ["gh", "issue", "new"]);
+        assert_eq!(
+            tokenize("parseHTTPResponse v2"),
+            ["parse", "http", "response", "v", "2"]
+        );
+        assert_eq!(
+            tokenize("Search the repos for access"),
+            ["search", "repo", "access"]
+        );
+    }
+
+    #[test]
+    fn ranks_partial_matches_instead_of_requiring_every_term() {
+        let index = index();
+        let hits = index.search("create github issue", 10, |_| true);
+        assert!(names(&hits).contains(&"gh_issue_new"));
+        assert!(names(&hits).contains(&"list_issues"));
+
+        // A name hit outranks a description-only hit
+        let hits = index.search("message", 10, |_| true);
+        assert_eq!(names(&hits), ["post_message", "list_channels"]);
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn tolerates_prefixes_and_typos() {
+        let index = index();
+        let first = |query: &str| index.search(query, 10, |_| true)[0].tool_name.clone();
+        assert_eq!(first("pul requst"), "createPullRequest");
+        assert_eq!(first("mesage"), "post_message");
+        assert_eq!(first("chan"), "list_channels");
+        assert!(index.search("zzz", 10, |_| true).is_empty());
+    }
+
+    #[test]
+    fn filters_limits_and_forgets_servers() {
+        let index = index();
+        assert_eq!(index.search("repo", 1, |_| true).len(), 1);
+        assert!(index.search("repo", 10, |s| s == "slack").is_empty());
+
+        index.update("github", &[]);
+        assert!(index.search("issue", 10, |_| true).is_empty());
+        assert_eq!(index.search("message", 10, |_| true).len(), 2);
+    }
+
+    #[test]
+    fn fusion_favors_tools_both_rankings_agree_on() {
+        let hit = |name: &str, score: f64| SearchHit {
+            server_id: "srv".to_string(),
+            tool_name: name.to_string(),
+            score,
+        };
+        let keyword = [hit("a", 9.0), hit("b", 5.0), hit("c", 1.0)];
+        let semantic = [hit("b", 0.9), hit("d", 0.8), hit("c", 0.7)];
+
+        let fused = fuse(&keyword, &semantic, 3);
+        assert_eq!(names(&fused), ["b", "c", "a"]);
+        assert_eq!(names(&fuse(&keyword, &[], 10)), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(edit_distance("issue", "isuse"), 1);
+        assert_eq!(edit_distance("issue", "issues"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,579 @@
+//! Test harness: a scripted MCP server, and stand-ins that serve it over each
+//! transport Agent Hub speaks — a fake stdio process, streamable HTTP, legacy
+//! HTTP+SSE — plus a proxy backend that needs no Tauri app.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::sse::{Event, Sse};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde_json::{json, Value};
+use tauri_plugin_shell::process::{CommandEvent, TerminatedPayload};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::error::AppError;
+use crate::mcp::client::CallToolResult;
+use crate::mcp::proxy::{self, ProxyAppState, ProxyBackend};
+use crate::mcp::search::ToolIndex;
+use crate::mcp::transport::ServerProcess;
+use crate::state::{
+    AppState, ConnectionState, McpTool, SearchMode, ServerConfig, ServerStatus, SharedState,
+    ToolPinning,
+};
+use crate::stats::{tool_call_counts, ServerStats};
+
+/// How the scripted server answers a call to one of its tools.
+#[derive(Debug, Clone)]
+pub(crate) enum Reply {
+    /// A successful result with this text content.
+    Text(String),
+    /// A tool-level failure (`isError: true`) with this text content.
+    ToolError(String),
+    /// A JSON-RPC error response.
+    RpcError(i64, String),
+    /// The call's arguments, echoed back as JSON text.
+    Echo,
+    /// Another reply, sent after a delay.
+    Delayed(Duration, Box<Reply>),
+    /// No response at all.
+    Silent,
+}
+
+/// An MCP server driven by a script: a fixed tool list and a canned reply
+/// per tool. Every message it receives is kept for assertions.
+pub(crate) struct ScriptedServer {
+    tools: Vec<(Value, Reply)>,
+    received: Mutex<Vec<Value>>,
+}
+
+impl ScriptedServer {
+    pub(crate) fn new() -> Self {
+        Self {
+            tools: Vec::new(),
+            received: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn tool(mut self, name: &str, reply: Reply) -> Self {
+        let def = json!({
+            "name": name,
+            "description": format!("Scripted {name} tool"),
+            "inputSchema": { "type": "object" },
+        });
+        self.tools.push((def, reply));
+        self
+    }
+
+    /// Methods of every message received so far, in order.
+    pub(crate) fn methods(&self) -> Vec<String> {
+        self.received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|m| m["method"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    pub(crate) fn tool_defs(&self) -> Vec<Value> {
+        self.tools.iter().map(|(def, _)| def.clone()).collect()
+    }
+
+    /// The response to a message and how long to wait before sending it.
+    /// `None` for notifications and [`Reply::Silent`] tools.
+    pub(crate) fn respond(&self, message: &Value) -> Option<(Duration, Value)> {
+        self.received.lock().unwrap().push(message.clone());
+        let id = message.get("id").filter(|id| !id.is_null())?.clone();
+        let method = message["method"].as_str().unwrap_or_default();
+
+        let (delay, outcome) = match method {
+            "initialize" => (
+                Duration::ZERO,
+                Ok(json!({
+                    "protocolVersion": message["params"]["protocolVersion"]
+                        .as_str()
+                        .unwrap_or("2025-03-26"),
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "scripted", "version": "1.0.0" },
+                })),
+            ),
+            "tools/list" => (Duration::ZERO, Ok(json!({ "tools": self.tool_defs() }))),
+            "tools/call" => {
+                let name = message["params"]["name"].as_str().unwrap_or_default();
+                let arguments = &message["params"]["arguments"];
+                match self.tools.iter().find(|(def, _)| def["name"] == name) {
+                    Some((_, reply)) => resolve(reply, arguments)?,
+                    None => (
+                        Duration::ZERO,
+                        Err((-32602, format!("Unknown tool: {name}"))),
+                    ),
+                }
+            }
+            "ping" => (Duration::ZERO, Ok(json!({}))),
+            _ => (
+                Duration::ZERO,
+                Err((-32601, format!("Method not found: {method}"))),
+            ),
+        };
+
+        let response = match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        Some((delay, response))
+    }
+}
+
+type Outcome = Result<Value, (i64, String)>;
+
+fn resolve(reply: &Reply, arguments: &Value) -> Option<(Duration, Outcome)> {
+    let text = |text: &str, is_error: bool| {
+        json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        })
+    };
+    let outcome = match reply {
+        Reply::Text(t) => Ok(text(t, false)),
+        Reply::ToolError(t) => Ok(text(t, true)),
+        Reply::RpcError(code, message) => Err((*code, message.clone())),
+        Reply::Echo => Ok(text(&arguments.to_string(), false)),
+        Reply::Delayed(delay, inner) => {
+            let (more, outcome) = resolve(inner, arguments)?;
+            return Some((*delay + more, outcome));
+        }
+        Reply::Silent => return None,
+    };
+    Some((Duration::ZERO, outcome))
+}
+
+// ---------------------------------------------------------------------------
+// stdio
+// ---------------------------------------------------------------------------
+
+/// Stands in for a spawned stdio server: lines written to it are answered by
+/// the script as `Stdout` events, the way the shell plugin reports a real
+/// process's output.
+pub(crate) struct ScriptedProcess {
+    server: Arc<ScriptedServer>,
+    output: ProcessOutput,
+    buffer: String,
+}
+
+/// The output side of a [`ScriptedProcess`], for injecting stderr, stray
+/// stdout or an exit.
+#[derive(Clone)]
+pub(crate) struct ProcessOutput(mpsc::Sender<CommandEvent>);
+
+impl ProcessOutput {
+    pub(crate) async fn stdout(&self, text: &str) {
+        let _ = self
+            .0
+            .send(CommandEvent::Stdout(text.as_bytes().to_vec()))
+            .await;
+    }
+
+    pub(crate) async fn stderr(&self, text: &str) {
+        let _ = self
+            .0
+            .send(CommandEvent::Stderr(text.as_bytes().to_vec()))
+            .await;
+    }
+
+    pub(crate) async fn exit(&self, code: i32) {
+        let _ = self
+            .0
+            .send(CommandEvent::Terminated(TerminatedPayload {
+                code: Some(code),
+                signal: None,
+            }))
+            .await;
+    }
+}
+
+/// A scripted process, the event channel a transport reads it through, and a
+/// handle for writing to that channel directly.
+pub(crate) fn stdio_process(
+    server: Arc<ScriptedServer>,
+) -> (
+    Box<ScriptedProcess>,
+    mpsc::Receiver<CommandEvent>,
+    ProcessOutput,
+) {
+    let (tx, rx) = mpsc::channel(256);
+    let output = ProcessOutput(tx);
+    let process = ScriptedProcess {
+        server,
+        output: output.clone(),
+        buffer: String::new(),
+    };
+    (Box::new(process), rx, output)
+}
+
+impl ServerProcess for ScriptedProcess {
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        self.buffer.push_str(&String::from_utf8_lossy(data));
+        while let Some(end) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..=end).collect();
+            let message: Value = serde_json::from_str(line.trim()).map_err(|e| e.to_string())?;
+            let Some((delay, response)) = self.server.respond(&message) else {
+                continue;
+            };
+            let line = format!("{response}\n");
+            if delay.is_zero() {
+                // Split the line across two events so the reader has to reassemble it
+                let (head, tail) = line.split_at(line.len() / 2);
+                for chunk in [head, tail] {
+                    self.output
+                        .0
+                        .try_send(CommandEvent::Stdout(chunk.as_bytes().to_vec()))
+                        .map_err(|e| e.to_string())?;
+                }
+            } else {
+                let output = self.output.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    output.stdout(&line).await;
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn kill(self: Box<Self>) {
+        let _ = self
+            .output
+            .0
+            .try_send(CommandEvent::Terminated(TerminatedPayload {
+                code: None,
+                signal: Some(9),
+            }));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HTTP
+// ---------------------------------------------------------------------------
+
+/// Serve a router on a random local port. Returns the base URL.
+pub(crate) async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+/// Answer a message after the scripted delay, or never for silent replies.
+async fn answer(server: &ScriptedServer, message: &Value) -> Option<Value> {
+    match server.respond(message) {
+        Some((delay, response)) => {
+            tokio::time::sleep(delay).await;
+            Some(response)
+        }
+        None if message.get("id").is_some_and(|id| !id.is_null()) => {
+            futures::future::pending::<()>().await;
+            None
+        }
+        None => None,
+    }
+}
+
+/// A streamable HTTP server at `/mcp`. Responses come back inline, as JSON
+/// or, with `sse`, as an event stream that carries a log notification first.
+pub(crate) fn streamable_http(server: Arc<ScriptedServer>, sse: bool) -> Router {
+    Router::new().route(
+        "/mcp",
+        post(move |Json(message): Json<Value>| {
+            let server = server.clone();
+            async move {
+                let Some(response) = answer(&server, &message).await else {
+                    return StatusCode::ACCEPTED.into_response();
+                };
+                let session = [("mcp-session-id", "session-1")];
+                if !sse {
+                    return (session, Json(response)).into_response();
+                }
+                let log = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/message",
+                    "params": { "level": "info", "data": "working" },
+                });
+                let body =
+                    format!("event: message\ndata: {log}\n\nevent: message\ndata: {response}\n\n");
+                (session, [("content-type", "text/event-stream")], body).into_response()
+            }
+        }),
+    )
+}
+
+#[derive(Clone)]
+struct Legacy {
+    server: Arc<ScriptedServer>,
+    tx: mpsc::UnboundedSender<Value>,
+    rx: Arc<tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<Value>>>>,
+    probes: Arc<AtomicU64>,
+}
+
+/// A legacy HTTP+SSE server: GET `/mcp` opens the stream, which advertises
+/// `/messages` for POSTs and carries their responses. POSTing to `/mcp`
+/// itself is a 405, counted in `probes`.
+pub(crate) fn legacy_sse(server: Arc<ScriptedServer>, probes: Arc<AtomicU64>) -> Router {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let state = Legacy {
+        server,
+        tx,
+        rx: Arc::new(tokio::sync::Mutex::new(Some(rx))),
+        probes,
+    };
+    Router::new()
+        .route("/mcp", get(legacy_events).post(legacy_probe))
+        .route("/messages", post(legacy_message))
+        .with_state(state)
+}
+
+async fn legacy_events(State(s): State<Legacy>) -> impl IntoResponse {
+    let mut rx = s.rx.lock().await.take().expect("one SSE client");
+    let stream = async_stream::stream! {
+        yield Ok::<_, Infallible>(Event::default().event("endpoint").data("/messages?sessionId=1"));
+        while let Some(message) = rx.recv().await {
+            // Pretty-printed, so each response spans several `data:` lines
+            let data = serde_json::to_string_pretty(&message).unwrap();
+            yield Ok(Event::default().event("message").data(data));
+        }
+    };
+    Sse::new(stream)
+}
+
+async fn legacy_probe(State(s): State<Legacy>) -> StatusCode {
+    s.probes.fetch_add(1, Ordering::SeqCst);
+    StatusCode::METHOD_NOT_ALLOWED
+}
+
+async fn legacy_message(State(s): State<Legacy>, Json(message): Json<Value>) -> StatusCode {
+    tokio::spawn(async move {
+        if let Some(response) = answer(&s.server, &message).await {
+            let _ = s.tx.send(response);
+        }
+    });
+    StatusCode::ACCEPTED
+}
+
+// ---------------------------------------------------------------------------
+// Proxy
+// ---------------------------------------------------------------------------
+
+/// A proxy backend over in-memory state, where every connected server is a
+/// scripted one. Recorded tool calls are kept as `(server, tool, is_error)`.
+pub(crate) struct TestBackend {
+    state: SharedState,
+    index: ToolIndex,
+    servers: HashMap<String, Arc<ScriptedServer>>,
+    calls: Mutex<Vec<(String, String, bool)>>,
+    stats: Mutex<HashMap<String, ServerStats>>,
+}
+
+impl TestBackend {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: SharedState::new(AppState::new()),
+            index: ToolIndex::new(),
+            servers: HashMap::new(),
+            calls: Mutex::new(Vec::new()),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add a server. With a script it's connected and lists the script's
+    /// tools; without one it's configured but disconnected, and fails to
+    /// connect as if its command weren't installed.
+    pub(crate) fn server(mut self, id: &str, name: &str, script: Option<ScriptedServer>) -> Self {
+        let status = if script.is_some() {
+            "connected"
+        } else {
+            "disconnected"
+        };
+        let config: ServerConfig = serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "enabled": true,
+            "transport": "stdio",
+            "status": status,
+        }))
+        .unwrap();
+
+        let mut state = self.state.lock().unwrap();
+        state.servers.push(config);
+        if let Some(script) = script {
+            let tools: Vec<McpTool> = script
+                .tool_defs()
+                .into_iter()
+                .map(|def| McpTool {
+                    name: def["name"].as_str().unwrap().to_string(),
+                    title: None,
+                    description: def["description"].as_str().map(String::from),
+                    input_schema: Some(def["inputSchema"].clone()),
+                    server_id: id.to_string(),
+                    server_name: name.to_string(),
+                })
+                .collect();
+            self.index.update(id, &tools);
+            state.tool_catalog.insert(id.to_string(), tools.clone());
+            state
+                .connections
+                .insert(id.to_string(), ConnectionState { tools });
+            drop(state);
+            self.servers.insert(id.to_string(), Arc::new(script));
+        } else {
+            drop(state);
+        }
+        self
+    }
+
+    /// Disconnect a server, keeping its tools in the catalog.
+    pub(crate) fn disconnect(&self, id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.connections.remove(id);
+        if let Some(server) = state.servers.iter_mut().find(|s| s.id == id) {
+            server.status = Some(ServerStatus::Disconnected);
+        }
+    }
+
+    pub(crate) fn status(&self, id: &str) -> Option<ServerStatus> {
+        let state = self.state.lock().unwrap();
+        state.servers.iter().find(|s| s.id == id)?.status.clone()
+    }
+
+    pub(crate) fn disable_tools(&self, id: &str, tools: &[&str]) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(server) = state.servers.iter_mut().find(|s| s.id == id) {
+            server.disabled_tools = Some(tools.iter().map(|t| t.to_string()).collect());
+        }
+    }
+
+    pub(crate) fn set_discovery(&self, enabled: bool) {
+        self.state.lock().unwrap().tool_discovery_enabled = enabled;
+    }
+
+    pub(crate) fn set_search_mode(&self, mode: SearchMode) {
+        self.state.lock().unwrap().discovery_search_mode = mode;
+    }
+
+    pub(crate) fn set_pinning(&self, pinning: ToolPinning) {
+        self.state.lock().unwrap().discovery_pinning = pinning;
+    }
+
+    pub(crate) fn calls(&self) -> Vec<(String, String, bool)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl ProxyBackend for TestBackend {
+    fn state(&self) -> &SharedState {
+        &self.state
+    }
+
+    fn tool_index(&self) -> &ToolIndex {
+        &self.index
+    }
+
+    fn call_tool<'a>(
+        &'a self,
+        server_id: &'a str,
+        tool_name: &'a str,
+        arguments: Value,
+    ) -> BoxFuture<'a, Option<Result<CallToolResult, AppError>>> {
+        async move {
+            let connected = {
+                let state = self.state.lock().unwrap();
+                state.connections.contains_key(server_id)
+            };
+            if !connected {
+                return None;
+            }
+            let server = self.servers.get(server_id)?;
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": tool_name, "arguments": arguments },
+            });
+            let response = answer(server, &request).await?;
+            Some(match response.get("result") {
+                Some(result) => serde_json::from_value(result.clone()).map_err(AppError::from),
+                None => Err(AppError::Protocol(format!(
+                    "{}: {}",
+                    response["error"]["code"],
+                    response["error"]["message"].as_str().unwrap_or_default()
+                ))),
+            })
+        }
+        .boxed()
+    }
+
+    fn connect<'a>(&'a self, server_id: &'a str) -> BoxFuture<'a, Result<(), AppError>> {
+        let result = if self.servers.contains_key(server_id) {
+            let mut state = self.state.lock().unwrap();
+            let tools = state.tool_catalog[server_id].clone();
+            state
+                .connections
+                .insert(server_id.to_string(), ConnectionState { tools });
+            if let Some(server) = state.servers.iter_mut().find(|s| s.id == server_id) {
+                server.status = Some(ServerStatus::Connected);
+            }
+            Ok(())
+        } else {
+            Err(AppError::DependencyNotFound("npx".into()))
+        };
+        async move { result }.boxed()
+    }
+
+    fn record_call<'a>(
+        &'a self,
+        server_id: &'a str,
+        tool_name: &'a str,
+        client_id: &'a str,
+        _duration_ms: u64,
+        is_error: bool,
+    ) -> BoxFuture<'a, ()> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((server_id.to_string(), tool_name.to_string(), is_error));
+        let mut stats = self.stats.lock().unwrap();
+        let tool = stats
+            .entry(server_id.to_string())
+            .or_default()
+            .tools
+            .entry(tool_name.to_string())
+            .or_default();
+        tool.total_calls += 1;
+        *tool.clients.entry(client_id.to_string()).or_insert(0) += 1;
+        async {}.boxed()
+    }
+
+    fn tool_call_counts<'a>(
+        &'a self,
+        client_id: &'a str,
+    ) -> BoxFuture<'a, HashMap<(String, String), u64>> {
+        let counts = tool_call_counts(&self.stats.lock().unwrap(), client_id);
+        async move { counts }.boxed()
+    }
+}
+
+/// Serve the proxy router around a backend. Returns the base URL.
+pub(crate) async fn serve_proxy(backend: Arc<TestBackend>) -> String {
+    let (notify_tx, _) = broadcast::channel(16);
+    serve(proxy::router(ProxyAppState { backend, notify_tx })).await
+}
@@ -0,0 +1,269 @@
+//! Opt-in JSON-RPC traffic inspector.
+//!
+//! When enabled, every message between MCP clients and the proxy, and between
+//! the proxy and backend servers, is kept in a bounded ring buffer and streamed
+//! to the frontend as `traffic-captured` events. Disabled by default: recording
+//! is a single atomic load when off.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::stats::unix_now_millis;
+
+/// Messages kept in the ring buffer; the oldest are dropped first.
+const BUFFER_SIZE: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    /// An MCP client (Claude, Cursor, …) calling the proxy.
+    ClientToProxy,
+    ProxyToClient,
+    /// Agent Hub calling a backend server.
+    ProxyToBackend,
+    /// Responses and notifications from a backend server.
+    BackendToProxy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficEntry {
+    pub id: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub direction: Direction,
+    pub server_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// The method, or for responses the method of the request they answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// For responses, the entry ID of the request they answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<u64>,
+    /// For responses, how long the request took.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+    /// Serialized size in bytes.
+    pub size: usize,
+    pub message: Value,
+}
+
+/// A captured request, handed back to [`response`] to link and time the reply.
+pub struct Exchange {
+    id: u64,
+    method: Option<String>,
+    started: Instant,
+}
+
+pub struct TrafficTap {
+    enabled: AtomicBool,
+    next_id: AtomicU64,
+    buffer: Mutex<VecDeque<TrafficEntry>>,
+}
+
+impl TrafficTap {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            next_id: AtomicU64::new(1),
+            buffer: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn entries(&self) -> Vec<TrafficEntry> {
+        self.buffer.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.buffer.lock().unwrap().clear();
+    }
+
+    fn capture(
+        &self,
+        direction: Direction,
+        server_id: &str,
+        session: Option<&str>,
+        message: &Value,
+        reply: Option<&Exchange>,
+    ) -> TrafficEntry {
+        let method = match reply {
+            Some(exchange) => exchange.method.clone(),
+            None => message
+                .get("method")
+                .and_then(Value::as_str)
+                .map(String::from),
+        };
+        let entry = TrafficEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp: unix_now_millis(),
+            direction,
+            server_id: server_id.to_string(),
+            session: session.map(String::from),
+            method,
+            reply_to: reply.map(|exchange| exchange.id),
+            duration_ms: reply.map(|exchange| exchange.started.elapsed().as_secs_f64() * 1000.0),
+            size: message.to_string().len(),
+            message: message.clone(),
+        };
+
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() == BUFFER_SIZE {
+            buffer.pop_front();
+        }
+        buffer.push_back(entry.clone());
+        entry
+    }
+
+    /// The buffer as a HAR-like document: each request paired with its
+    /// response, notifications on their own.
+    pub fn export(&self) -> Value {
+        let entries = self.entries();
+        let replies: HashMap<u64, &TrafficEntry> = entries
+            .iter()
+            .filter_map(|e| e.reply_to.map(|id| (id, e)))
+            .collect();
+
+        let har_entries: Vec<Value> = entries
+            .iter()
+            .filter(|e| e.reply_to.is_none())
+            .map(|request| {
+                let reply = replies.get(&request.id);
+                serde_json::json!({
+                    "startedDateTime": request.timestamp,
+                    "time": reply.and_then(|r| r.duration_ms),
+                    "serverId": request.server_id,
+                    "session": request.session,
+                    "direction": request.direction,
+                    "method": request.method,
+                    "request": { "bodySize": request.size, "body": request.message },
+                    "response": reply.map(|r| serde_json::json!({
+                        "bodySize": r.size,
+                        "body": r.message,
+                    })),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "Agent Hub", "version": env!("CARGO_PKG_VERSION") },
+                "entries": har_entries,
+            }
+        })
+    }
+}
+
+/// Record an outgoing request or notification. Returns a handle for linking
+/// the response, or `None` when capture is off.
+pub fn request(
+    app: &AppHandle,
+    server_id: &str,
+    direction: Direction,
+    session: Option<&str>,
+    message: &Value,
+) -> Option<Exchange> {
+    let tap = app.try_state::<TrafficTap>().filter(|tap| tap.enabled())?;
+    let entry = tap.capture(direction, server_id, session, message, None);
+    let exchange = Exchange {
+        id: entry.id,
+        method: entry.method.clone(),
+        started: Instant::now(),
+    };
+    let _ = app.emit("traffic-captured", entry);
+    Some(exchange)
+}
+
+/// Record the response to a request captured with [`request`].
+pub fn response(
+    app: &AppHandle,
+    server_id: &str,
+    direction: Direction,
+    session: Option<&str>,
+    message: &Value,
+    exchange: Option<Exchange>,
+) {
+    let Some(tap) = app.try_state::<TrafficTap>().filter(|tap| tap.enabled()) else {
+        return;
+    };
+    let entry = tap.capture(direction, server_id, session, message, exchange.as_ref());
+    let _ = app.emit("traffic-captured", entry);
+}
+
+/// Record a message that isn't part of a captured exchange, e.g. a backend
+/// notification.
+pub fn notification(
+    app: &AppHandle,
+    server_id: &str,
+    direction: Direction,
+    session: Option<&str>,
+    message: &Value,
+) {
+    response(app, server_id, direction, session, message, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_buffer_drops_oldest_entries() {
+        let tap = TrafficTap::new();
+        let message = serde_json::json!({ "jsonrpc": "2.0", "method": "ping" });
+        for _ in 0..BUFFER_SIZE + 5 {
+            tap.capture(Direction::ClientToProxy, "srv", None, &message, None);
+        }
+        let entries = tap.entries();
+        assert_eq!(entries.len(), BUFFER_SIZE);
+        assert_eq!(entries[0].id, 6);
+    }
+
+    #[test]
+    fn export_pairs_requests_with_responses() {
+        let tap = TrafficTap::new();
+        let req = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        let request = tap.capture(Direction::ProxyToBackend, "srv", Some("s1"), &req, None);
+        let exchange = Exchange {
+            id: request.id,
+            method: request.method.clone(),
+            started: Instant::now(),
+        };
+        let resp = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "tools": [] } });
+        let reply = tap.capture(
+            Direction::BackendToProxy,
+            "srv",
+            None,
+            &resp,
+            Some(&exchange),
+        );
+        let note = serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/message" });
+        tap.capture(Direction::BackendToProxy, "srv", None, &note, None);
+
+        assert_eq!(reply.method.as_deref(), Some("tools/list"));
+        assert_eq!(reply.reply_to, Some(request.id));
+        assert!(reply.duration_ms.is_some());
+
+        let har = tap.export();
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["method"], "tools/list");
+        assert_eq!(entries[0]["session"], "s1");
+        assert_eq!(entries[0]["response"]["body"]["id"], 1);
+        assert!(entries[1]["response"].is_null());
+    }
+}
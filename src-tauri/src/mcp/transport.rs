@@ -1,15 +1,22 @@
 use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_shell::process::CommandEvent;
+use tauri::AppHandle;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
 use tokio::sync::{mpsc, oneshot, Mutex};
 use tracing::{debug, error, info, warn};
 
 use crate::error::AppError;
+use crate::mcp::sandbox::{self, SandboxTools};
+use crate::mcp::traffic::{self, Direction};
 use crate::mcp::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::server_logs::{self, LogSource};
+use crate::shell_env;
+use crate::state::SandboxConfig;
 
 /// A pending request awaiting a response from the MCP server.
 type PendingRequest = oneshot::Sender<JsonRpcResponse>;
@@ -17,6 +24,60 @@ type PendingRequest = oneshot::Sender<JsonRpcResponse>;
 /// Max number of recent error-level stderr lines to keep for error context.
 const STDERR_BUFFER_SIZE: usize = 10;
 
+/// How long to wait for the server to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The running server process, as seen by the stdin writer task. Implemented
+/// by the shell plugin's child handle, and by in-process fakes in tests.
+pub(crate) trait ServerProcess: Send + 'static {
+    fn write(&mut self, data: &[u8]) -> Result<(), String>;
+    fn kill(self: Box<Self>);
+}
+
+impl ServerProcess for CommandChild {
+    fn write(&mut self, data: &[u8]) -> Result<(), String> {
+        CommandChild::write(self, data).map_err(|e| e.to_string())
+    }
+
+    fn kill(self: Box<Self>) {
+        let _ = CommandChild::kill(*self);
+    }
+}
+
+/// Sends a server's stderr, stray stdout and lifecycle events to its log and
+/// the traffic inspector. Transports built without an app (tests) only trace.
+#[derive(Clone)]
+struct Reporter {
+    app: Option<AppHandle>,
+    server_id: String,
+}
+
+impl Reporter {
+    fn emit(&self, level: &str, source: LogSource, message: &str) {
+        if let Some(app) = &self.app {
+            server_logs::emit(app, &self.server_id, level, source, message);
+        }
+    }
+
+    fn record(&self, level: &str, source: LogSource, message: &str) {
+        if let Some(app) = &self.app {
+            server_logs::record(app, &self.server_id, level, source, message);
+        }
+    }
+
+    fn notification(&self, message: &serde_json::Value) {
+        if let Some(app) = &self.app {
+            traffic::notification(
+                app,
+                &self.server_id,
+                Direction::BackendToProxy,
+                None,
+                message,
+            );
+        }
+    }
+}
+
 /// Handle for writing to a running MCP server's stdin and tracking pending requests.
 pub struct StdioTransport {
     next_id: AtomicU64,
@@ -28,6 +89,7 @@ pub struct StdioTransport {
     pending: Arc<Mutex<HashMap<u64, PendingRequest>>>,
     /// Recent error-level stderr lines, used to enrich transport error messages.
     recent_stderr: Arc<std::sync::Mutex<VecDeque<String>>>,
+    timeout: Duration,
 }
 
 impl StdioTransport {
@@ -35,29 +97,96 @@ impl StdioTransport {
     ///
     /// `command` is the program name (e.g. "node", "npx", "python").
     /// `args` are the command-line arguments.
-    /// `env` is an optional set of extra environment variables.
+    /// `env` is an optional set of extra environment variables, applied on top
+    /// of the login-shell environment.
+    /// `cwd` is the working directory, if not inherited.
+    /// `sandbox`, when enabled, wraps the command in resource limits and isolation.
     pub fn spawn(
         app: &AppHandle,
         server_id: &str,
         command: &str,
         args: &[String],
         env: &HashMap<String, String>,
+        cwd: Option<&Path>,
+        sandbox: Option<&SandboxConfig>,
     ) -> Result<Self, AppError> {
-        let mut cmd = app.shell().command(command);
+        let sandbox = sandbox.filter(|s| s.enabled).cloned();
+
+        // GUI launches don't inherit the shell PATH, so resolve against the
+        // login-shell environment and report what we found
+        let shell_env = shell_env::get();
+        let lookup = match cwd {
+            Some(dir) if command.contains(std::path::MAIN_SEPARATOR) => {
+                dir.join(command).to_string_lossy().into_owned()
+            }
+            _ => command.to_string(),
+        };
+        let program = shell_env
+            .which(&lookup)
+            .map(|p| p.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                AppError::DependencyNotFound(format!(
+                    "{command} (not found on the login-shell PATH)"
+                ))
+            })?;
+        server_logs::emit(
+            app,
+            server_id,
+            "info",
+            LogSource::Lifecycle,
+            &format!("Resolved {command} to {program}"),
+        );
+
+        let argv = match &sandbox {
+            Some(config) => {
+                let argv = sandbox::wrap_command(config, &SandboxTools::detect(), &program, args)?;
+                info!("Spawning sandboxed server {server_id}: {}", argv.join(" "));
+                argv
+            }
+            None => std::iter::once(program)
+                .chain(args.iter().cloned())
+                .collect(),
+        };
+
+        let mut cmd = app.shell().command(&argv[0]);
 
-        for arg in args {
+        for arg in &argv[1..] {
             cmd = cmd.arg(arg);
         }
-        for (k, v) in env {
+        for (k, v) in shell_env.vars().iter().chain(env) {
             cmd = cmd.env(k, v);
         }
+        if let Some(dir) = cwd {
+            if !dir.is_dir() {
+                return Err(AppError::Transport(format!(
+                    "Working directory does not exist: {}",
+                    dir.display()
+                )));
+            }
+            cmd = cmd.current_dir(dir);
+        }
 
-        let (mut rx, mut child) = cmd
+        let (rx, child) = cmd
             .spawn()
             .map_err(|e| AppError::Transport(format!("Failed to spawn process: {e}")))?;
 
         let pid = child.pid();
+        let reporter = Reporter {
+            app: Some(app.clone()),
+            server_id: server_id.to_string(),
+        };
+        Ok(Self::attach(reporter, pid, rx, Box::new(child), sandbox))
+    }
 
+    /// Wire up the reader and writer tasks for a spawned process: `rx` carries
+    /// its output, `child` takes its input.
+    fn attach(
+        reporter: Reporter,
+        pid: u32,
+        mut rx: mpsc::Receiver<CommandEvent>,
+        mut child: Box<dyn ServerProcess>,
+        sandbox: Option<SandboxConfig>,
+    ) -> Self {
         // Channel for sending lines to stdin
         let (stdin_tx, mut stdin_rx) = mpsc::channel::<String>(64);
 
@@ -71,7 +200,7 @@ impl StdioTransport {
             }
             // When channel closes, kill the child process
             debug!("Stdin channel closed, killing child process");
-            let _ = child.kill();
+            child.kill();
         });
 
         let pending: Arc<Mutex<HashMap<u64, PendingRequest>>> =
@@ -85,8 +214,7 @@ impl StdioTransport {
         // Channel for notifications (server-initiated messages that don't match a pending request)
         let (notification_tx, _notification_rx) = mpsc::channel::<JsonRpcResponse>(64);
 
-        let log_app = app.clone();
-        let log_server_id = server_id.to_string();
+        let log_sandbox = sandbox;
 
         // Stdout/stderr reader task
         tauri::async_runtime::spawn(async move {
@@ -121,10 +249,14 @@ impl StdioTransport {
                                         }
                                     }
                                     // Not a response to a pending request — treat as notification
+                                    if let Ok(message) = serde_json::from_str(&line) {
+                                        reporter.notification(&message);
+                                    }
                                     let _ = notification_tx.send(response).await;
                                 }
                                 Err(e) => {
                                     warn!("Failed to parse JSON-RPC message: {e} — raw: {line}");
+                                    reporter.record("info", LogSource::Stdout, &line);
                                 }
                             }
                         }
@@ -136,6 +268,17 @@ impl StdioTransport {
                             // actual level from the message content instead of
                             // treating everything as an error.
                             let level = detect_log_level(&text);
+                            if let Some(violation) = log_sandbox
+                                .as_ref()
+                                .and_then(|config| sandbox::detect_violation(config, &text))
+                            {
+                                warn!("Sandbox violation ({violation}): {text}");
+                                reporter.emit(
+                                    "error",
+                                    LogSource::Lifecycle,
+                                    &format!("Sandbox violation: {violation}"),
+                                );
+                            }
                             match level {
                                 "error" => {
                                     error!("MCP stderr: {text}");
@@ -148,14 +291,7 @@ impl StdioTransport {
                                 "info" => info!("MCP stderr: {text}"),
                                 _ => warn!("MCP stderr: {text}"),
                             }
-                            let _ = log_app.emit(
-                                "server-log",
-                                serde_json::json!({
-                                    "serverId": log_server_id,
-                                    "level": level,
-                                    "message": text,
-                                }),
-                            );
+                            reporter.emit(level, LogSource::Stderr, &text);
                         }
                     }
                     CommandEvent::Terminated(status) => {
@@ -165,13 +301,21 @@ impl StdioTransport {
                         // timeout. This lets stderr_enriched_error() surface
                         // the real crash reason right away.
                         pending_clone.lock().await.clear();
-                        let _ = log_app.emit(
-                            "server-log",
-                            serde_json::json!({
-                                "serverId": log_server_id,
-                                "level": "info",
-                                "message": format!("Process exited: {status:?}"),
-                            }),
+                        if let Some(reason) = log_sandbox
+                            .as_ref()
+                            .and_then(|config| sandbox::describe_termination(config, status.signal))
+                        {
+                            warn!("Sandboxed server {} {reason}", reporter.server_id);
+                            reporter.emit(
+                                "error",
+                                LogSource::Lifecycle,
+                                &format!("Sandbox violation: {reason}"),
+                            );
+                        }
+                        reporter.emit(
+                            "info",
+                            LogSource::Lifecycle,
+                            &format!("Process exited: {status:?}"),
                         );
                         break;
                     }
@@ -180,23 +324,40 @@ impl StdioTransport {
             }
         });
 
-        Ok(Self {
+        Self {
             next_id: AtomicU64::new(1),
             pid,
             stdin_tx,
             pending,
             recent_stderr,
-        })
+            timeout: REQUEST_TIMEOUT,
+        }
     }
 
-    /// Send a JSON-RPC request and wait for the correlated response.
+    /// Reserve a request ID for [`send_request_with_id`](Self::send_request_with_id).
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Shorthand for tests: send a JSON-RPC request and wait for the correlated response.
+    #[cfg(test)]
     pub async fn send_request(
         &self,
         method: &str,
         params: Option<serde_json::Value>,
     ) -> Result<JsonRpcResponse, AppError> {
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.send_request_with_id(self.next_id(), method, params)
+            .await
+    }
 
+    /// Send a JSON-RPC request with an ID from [`next_id`](Self::next_id) and wait
+    /// for the correlated response.
+    pub async fn send_request_with_id(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
         let request = JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
             id: Some(serde_json::Value::Number(id.into())),
@@ -221,14 +382,19 @@ impl StdioTransport {
 
         debug!("Sent request id={id} method={method}");
 
-        let response = tokio::time::timeout(std::time::Duration::from_secs(60), rx)
-            .await
-            .map_err(|_| {
-                AppError::Transport(format!(
+        let response = match tokio::time::timeout(self.timeout, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(self.stderr_enriched_error("Server process exited unexpectedly"));
+            }
+            Err(_) => {
+                // Forget the request so a late answer is treated as stray output
+                self.pending.lock().await.remove(&id);
+                return Err(AppError::Transport(format!(
                     "Timeout waiting for response to {method} (id={id})"
-                ))
-            })?
-            .map_err(|_| self.stderr_enriched_error("Server process exited unexpectedly"))?;
+                )));
+            }
+        };
 
         if let Some(err) = &response.error {
             return Err(AppError::Protocol(format!("{}: {}", err.code, err.message)));
@@ -313,3 +479,132 @@ fn detect_log_level(text: &str) -> &'static str {
         "warn"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::mcp::test_support::{self, ProcessOutput, Reply, ScriptedServer};
+
+    fn attach(server: ScriptedServer) -> (StdioTransport, ProcessOutput) {
+        let (process, rx, output) = test_support::stdio_process(Arc::new(server));
+        let reporter = Reporter {
+            app: None,
+            server_id: "scripted".to_string(),
+        };
+        let transport = StdioTransport::attach(reporter, 1, rx, process, None);
+        (transport, output)
+    }
+
+    fn call_params(tool: &str) -> Option<serde_json::Value> {
+        Some(json!({ "name": tool, "arguments": { "n": 1 } }))
+    }
+
+    #[tokio::test]
+    async fn correlates_concurrent_responses() {
+        let server = ScriptedServer::new()
+            .tool(
+                "slow",
+                Reply::Delayed(
+                    Duration::from_millis(100),
+                    Box::new(Reply::Text("slow".into())),
+                ),
+            )
+            .tool("fast", Reply::Text("fast".into()));
+        let (transport, _) = attach(server);
+
+        transport.send_request("initialize", None).await.unwrap();
+        // The slow answer arrives last, after the fast one
+        let (slow, fast) = tokio::join!(
+            transport.send_request("tools/call", call_params("slow")),
+            transport.send_request("tools/call", call_params("fast")),
+        );
+        assert_eq!(slow.unwrap().result.unwrap()["content"][0]["text"], "slow");
+        assert_eq!(fast.unwrap().result.unwrap()["content"][0]["text"], "fast");
+    }
+
+    #[tokio::test]
+    async fn skips_notifications_and_non_json_output() {
+        let (transport, output) = attach(ScriptedServer::new().tool("echo", Reply::Echo));
+
+        output.stdout("Server listening on stdio\n").await;
+        output
+            .stdout(r#"{"jsonrpc":"2.0","method":"notifications/message","params":{}}"#)
+            .await;
+        output.stdout("\n").await;
+
+        let resp = transport
+            .send_request("tools/call", call_params("echo"))
+            .await
+            .unwrap();
+        assert_eq!(resp.result.unwrap()["content"][0]["text"], r#"{"n":1}"#);
+    }
+
+    #[tokio::test]
+    async fn json_rpc_errors_become_protocol_errors() {
+        let server = ScriptedServer::new().tool("broken", Reply::RpcError(-32000, "boom".into()));
+        let (transport, _) = attach(server);
+
+        let err = transport
+            .send_request("tools/call", call_params("broken"))
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Protocol(msg) if msg == "-32000: boom"));
+
+        let err = transport
+            .send_request("resources/list", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Protocol(msg) if msg.starts_with("-32601")));
+    }
+
+    #[tokio::test]
+    async fn unanswered_requests_time_out() {
+        let (mut transport, _) = attach(ScriptedServer::new().tool("hang", Reply::Silent));
+        transport.timeout = Duration::from_millis(100);
+
+        let err = transport
+            .send_request("tools/call", call_params("hang"))
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Timeout waiting for response to tools/call"));
+        assert!(transport.pending.lock().await.is_empty());
+
+        transport.send_request("ping", None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn exit_fails_pending_requests_with_stderr() {
+        let (transport, output) = attach(ScriptedServer::new().tool("hang", Reply::Silent));
+
+        let request = transport.send_request("tools/call", call_params("hang"));
+        let crash = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            output
+                .stderr("Error: API_KEY environment variable is not set")
+                .await;
+            output.exit(1).await;
+        };
+        let (result, ()) = tokio::join!(request, crash);
+
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("API_KEY environment variable is not set"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn detects_stderr_log_levels() {
+        assert_eq!(
+            detect_log_level("Traceback (most recent call last):"),
+            "error"
+        );
+        assert_eq!(detect_log_level("2024-01-01 INFO started"), "info");
+        assert_eq!(detect_log_level("DeprecationWarning: old api"), "warn");
+    }
+}
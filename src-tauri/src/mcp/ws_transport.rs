@@ -0,0 +1,503 @@
+//! WebSocket transport for remote MCP servers that speak JSON-RPC over a
+//! persistent socket, one message per text frame.
+//!
+//! The socket is owned by a background task. Outgoing frames go through an
+//! mpsc channel; responses are matched to pending requests by id. If the
+//! socket drops, the next request reconnects and replays the `initialize`
+//! handshake so the server sees a fresh session.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use tauri::AppHandle;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue, StatusCode};
+use tokio_tungstenite::tungstenite::{self, Message};
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+use crate::mcp::traffic::{self, Direction};
+use crate::mcp::types::{JsonRpcRequest, JsonRpcResponse};
+use crate::server_logs::{self, LogSource};
+
+/// Pending request senders, keyed by JSON-RPC id.
+type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>>;
+
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+const RECONNECT_ATTEMPTS: u32 = 3;
+const RECONNECT_BASE_DELAY_MS: u64 = 500;
+
+/// Returns `true` for `ws://` and `wss://` URLs.
+pub fn is_websocket_url(url: &str) -> bool {
+    let lower = url.trim_start().to_ascii_lowercase();
+    lower.starts_with("ws://") || lower.starts_with("wss://")
+}
+
+pub struct WsTransport {
+    next_id: AtomicU64,
+    app: AppHandle,
+    server_id: String,
+    url: String,
+    /// Extra headers sent with the WebSocket upgrade request.
+    headers: HashMap<String, String>,
+    /// OAuth access token, injected as Bearer header on every (re)connect.
+    access_token: Arc<Mutex<Option<String>>>,
+    pending: PendingMap,
+    /// Frame sender for the current socket. Closed once the socket task exits.
+    outgoing: Mutex<mpsc::Sender<Message>>,
+    /// Params of the last `initialize` request, replayed after a reconnect.
+    init_params: Mutex<Option<serde_json::Value>>,
+    /// Set by `shutdown` so a closed socket isn't reopened.
+    closed: AtomicBool,
+}
+
+impl WsTransport {
+    /// Open the WebSocket connection. Returns `AuthRequired` if the upgrade is rejected with 401.
+    pub async fn connect(
+        app: &AppHandle,
+        server_id: &str,
+        url: &str,
+        headers: HashMap<String, String>,
+        access_token: Option<String>,
+    ) -> Result<Self, AppError> {
+        let access_token = Arc::new(Mutex::new(access_token));
+        let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+
+        let outgoing = open_socket(app, server_id, url, &headers, &access_token, &pending).await?;
+        info!("Connected to WebSocket MCP server {url}");
+
+        Ok(Self {
+            next_id: AtomicU64::new(1),
+            app: app.clone(),
+            server_id: server_id.to_string(),
+            url: url.to_string(),
+            headers,
+            access_token,
+            pending,
+            outgoing: Mutex::new(outgoing),
+            init_params: Mutex::new(None),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    /// Reserve a request ID for [`send_request_with_id`](Self::send_request_with_id).
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Send a JSON-RPC request with an ID from [`next_id`](Self::next_id) and wait
+    /// for the matching response.
+    pub async fn send_request_with_id(
+        &self,
+        id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        if method == "initialize" {
+            *self.init_params.lock().await = params.clone();
+        }
+
+        let (tx, reconnected) = self.sender().await?;
+        if reconnected && method != "initialize" {
+            self.reinitialize(&tx).await?;
+        }
+        self.request_on(&tx, id, method, params).await
+    }
+
+    /// Send a JSON-RPC notification (no response expected).
+    pub async fn send_notification(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<(), AppError> {
+        let (tx, reconnected) = self.sender().await?;
+        if reconnected {
+            self.reinitialize(&tx).await?;
+            // The replayed handshake already sent this one.
+            if method == "notifications/initialized" {
+                return Ok(());
+            }
+        }
+        notify_on(&tx, method, params).await
+    }
+
+    /// Use a refreshed OAuth access token from the next (re)connect on.
+    pub async fn set_access_token(&self, token: String) {
+        *self.access_token.lock().await = Some(token);
+    }
+
+    /// Close the socket. Pending requests fail once the socket task exits.
+    pub fn shutdown(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if let Ok(tx) = self.outgoing.try_lock() {
+            let _ = tx.try_send(Message::Close(None));
+        }
+    }
+
+    /// Return a sender for a live socket, reconnecting if the previous one closed.
+    /// The flag is `true` when a new socket was opened.
+    async fn sender(&self) -> Result<(mpsc::Sender<Message>, bool), AppError> {
+        let mut outgoing = self.outgoing.lock().await;
+        if !outgoing.is_closed() {
+            return Ok((outgoing.clone(), false));
+        }
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(AppError::Transport(
+                "WebSocket transport is shut down".into(),
+            ));
+        }
+
+        let mut last_err = None;
+        for attempt in 0..RECONNECT_ATTEMPTS {
+            if attempt > 0 {
+                let delay = RECONNECT_BASE_DELAY_MS * 2u64.pow(attempt - 1);
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+            info!("Reconnecting to {} (attempt {})", self.url, attempt + 1);
+            match open_socket(
+                &self.app,
+                &self.server_id,
+                &self.url,
+                &self.headers,
+                &self.access_token,
+                &self.pending,
+            )
+            .await
+            {
+                Ok(tx) => {
+                    *outgoing = tx.clone();
+                    return Ok((tx, true));
+                }
+                // Retrying won't fix credentials.
+                Err(e @ AppError::AuthRequired(_)) => return Err(e),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| AppError::Transport("WebSocket reconnect failed".into())))
+    }
+
+    /// Replay the `initialize` handshake on a fresh socket.
+    async fn reinitialize(&self, tx: &mpsc::Sender<Message>) -> Result<(), AppError> {
+        let Some(params) = self.init_params.lock().await.clone() else {
+            return Ok(());
+        };
+        self.request_on(tx, self.next_id(), "initialize", Some(params))
+            .await?;
+        notify_on(tx, "notifications/initialized", None).await?;
+        emit_log(
+            &self.app,
+            &self.server_id,
+            "info",
+            "WebSocket reconnected — session re-initialized",
+        );
+        Ok(())
+    }
+
+    async fn request_on(
+        &self,
+        tx: &mpsc::Sender<Message>,
+        id: u64,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, AppError> {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(serde_json::Value::Number(id.into())),
+            method: method.to_string(),
+            params,
+        };
+        let text = serde_json::to_string(&request)
+            .map_err(|e| AppError::Transport(format!("Failed to serialize request: {e}")))?;
+
+        let (resp_tx, resp_rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, resp_tx);
+
+        debug!("WS send_request id={id} method={method} -> {}", self.url);
+        if tx.send(Message::text(text)).await.is_err() {
+            self.pending.lock().await.remove(&id);
+            return Err(AppError::Transport("WebSocket closed".into()));
+        }
+
+        let response =
+            match tokio::time::timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), resp_rx).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(_)) => {
+                    return Err(AppError::Transport(format!(
+                        "WebSocket closed before {method} response"
+                    )))
+                }
+                Err(_) => {
+                    self.pending.lock().await.remove(&id);
+                    return Err(AppError::Transport(format!(
+                        "Request {method} timed out after {REQUEST_TIMEOUT_SECS}s"
+                    )));
+                }
+            };
+
+        if let Some(err) = &response.error {
+            return Err(AppError::Protocol(format!("{}: {}", err.code, err.message)));
+        }
+        Ok(response)
+    }
+}
+
+async fn notify_on(
+    tx: &mpsc::Sender<Message>,
+    method: &str,
+    params: Option<serde_json::Value>,
+) -> Result<(), AppError> {
+    let notification = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: None,
+        method: method.to_string(),
+        params,
+    };
+    let text = serde_json::to_string(&notification)
+        .map_err(|e| AppError::Transport(format!("Failed to serialize notification: {e}")))?;
+    debug!("WS send_notification method={method}");
+    tx.send(Message::text(text))
+        .await
+        .map_err(|_| AppError::Transport("WebSocket closed".into()))
+}
+
+/// Perform the WebSocket upgrade and spawn the socket task. Returns the frame sender.
+async fn open_socket(
+    app: &AppHandle,
+    server_id: &str,
+    url: &str,
+    headers: &HashMap<String, String>,
+    access_token: &Arc<Mutex<Option<String>>>,
+    pending: &PendingMap,
+) -> Result<mpsc::Sender<Message>, AppError> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| AppError::ConnectionFailed(format!("Invalid WebSocket URL {url}: {e}")))?;
+
+    for (k, v) in headers {
+        let name = HeaderName::from_bytes(k.as_bytes())
+            .map_err(|e| AppError::ConnectionFailed(format!("Invalid header name {k}: {e}")))?;
+        let value = HeaderValue::from_str(v).map_err(|e| {
+            AppError::ConnectionFailed(format!("Invalid value for header {k}: {e}"))
+        })?;
+        request.headers_mut().insert(name, value);
+    }
+    if let Some(token) = access_token.lock().await.as_ref() {
+        let value = HeaderValue::from_str(&format!("Bearer {token}"))
+            .map_err(|e| AppError::ConnectionFailed(format!("Invalid access token: {e}")))?;
+        request.headers_mut().insert("authorization", value);
+    }
+
+    let (socket, _) = match tokio_tungstenite::connect_async(request).await {
+        Ok(ok) => ok,
+        Err(tungstenite::Error::Http(response))
+            if response.status() == StatusCode::UNAUTHORIZED =>
+        {
+            return Err(AppError::AuthRequired(url.to_string()));
+        }
+        Err(e) => {
+            return Err(AppError::ConnectionFailed(format!(
+                "WebSocket connection to {url} failed: {e}"
+            )))
+        }
+    };
+
+    let (tx, rx) = mpsc::channel::<Message>(64);
+    tauri::async_runtime::spawn(run_socket(
+        socket,
+        rx,
+        pending.clone(),
+        app.clone(),
+        server_id.to_string(),
+    ));
+    Ok(tx)
+}
+
+/// Pump frames between the socket and the transport until either side closes.
+async fn run_socket<S>(
+    socket: tokio_tungstenite::WebSocketStream<S>,
+    mut rx: mpsc::Receiver<Message>,
+    pending: PendingMap,
+    app: AppHandle,
+    server_id: String,
+) where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut sink, mut stream) = socket.split();
+
+    loop {
+        tokio::select! {
+            outgoing = rx.recv() => {
+                // Channel closed means the transport was dropped.
+                let msg = outgoing.unwrap_or(Message::Close(None));
+                let is_close = matches!(msg, Message::Close(_));
+                if let Err(e) = sink.send(msg).await {
+                    warn!("WebSocket send failed for {server_id}: {e}");
+                    break;
+                }
+                if is_close {
+                    break;
+                }
+            }
+            incoming = stream.next() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text.as_str().to_string(),
+                    Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).into_owned(),
+                    Some(Ok(Message::Close(frame))) => {
+                        info!("WebSocket closed by server {server_id}: {frame:?}");
+                        break;
+                    }
+                    // Ping/pong are answered by tungstenite itself.
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        warn!("WebSocket error for {server_id}: {e}");
+                        break;
+                    }
+                    None => break,
+                };
+                if let Some(reply) = handle_message(&text, &pending, &app, &server_id).await {
+                    if sink.send(Message::text(reply)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // Fail in-flight requests immediately instead of waiting for the timeout.
+    pending.lock().await.clear();
+    emit_log(&app, &server_id, "info", "WebSocket disconnected");
+}
+
+/// Route one incoming JSON-RPC message. Returns a reply to send for
+/// server-initiated requests.
+async fn handle_message(
+    text: &str,
+    pending: &PendingMap,
+    app: &AppHandle,
+    server_id: &str,
+) -> Option<String> {
+    let value: serde_json::Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Failed to parse WebSocket message: {e} — raw: {text}");
+            return None;
+        }
+    };
+
+    if let Some(method) = value.get("method").and_then(|m| m.as_str()) {
+        let params = value.get("params").cloned();
+        return match value.get("id") {
+            Some(id) => {
+                let exchange =
+                    traffic::request(app, server_id, Direction::BackendToProxy, None, &value);
+                let reply = reply_to_server_request(id, method);
+                traffic::response(
+                    app,
+                    server_id,
+                    Direction::ProxyToBackend,
+                    None,
+                    &reply,
+                    exchange,
+                );
+                Some(reply.to_string())
+            }
+            None => {
+                traffic::notification(app, server_id, Direction::BackendToProxy, None, &value);
+                dispatch_notification(app, server_id, method, params);
+                None
+            }
+        };
+    }
+
+    match serde_json::from_value::<JsonRpcResponse>(value) {
+        Ok(response) => {
+            let id = response.id.as_ref().and_then(|id| id.as_u64());
+            match id {
+                Some(id) => match pending.lock().await.remove(&id) {
+                    Some(sender) => {
+                        let _ = sender.send(response);
+                    }
+                    None => debug!("Response for unknown request id {id}"),
+                },
+                None => debug!("Response without numeric id: {response:?}"),
+            }
+        }
+        Err(e) => warn!("Unexpected WebSocket message: {e} — raw: {text}"),
+    }
+    None
+}
+
+/// Answer a request initiated by the server. Only `ping` is supported; we
+/// don't advertise any client capabilities that would invite others.
+fn reply_to_server_request(id: &serde_json::Value, method: &str) -> serde_json::Value {
+    if method == "ping" {
+        serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+    } else {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("Method not found: {method}") },
+        })
+    }
+}
+
+/// Surface server log messages in the server log. Other notifications are
+/// only recorded as traffic.
+fn dispatch_notification(
+    app: &AppHandle,
+    server_id: &str,
+    method: &str,
+    params: Option<serde_json::Value>,
+) {
+    debug!("WS notification from {server_id}: {method}");
+    if method != "notifications/message" {
+        return;
+    }
+    let params = params.unwrap_or_default();
+    let level = match params.get("level").and_then(|l| l.as_str()) {
+        Some("debug") => "debug",
+        Some("error" | "critical" | "alert" | "emergency") => "error",
+        _ => "info",
+    };
+    let message = match params.get("data") {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    server_logs::emit(app, server_id, level, LogSource::Server, &message);
+}
+
+fn emit_log(app: &AppHandle, server_id: &str, level: &str, message: &str) {
+    server_logs::emit(app, server_id, level, LogSource::Lifecycle, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn websocket_url_detection() {
+        assert!(is_websocket_url("ws://localhost:8080/mcp"));
+        assert!(is_websocket_url("WSS://gateway.example.com/mcp"));
+        assert!(!is_websocket_url("https://mcp.example.com/mcp"));
+        assert!(!is_websocket_url("http://localhost/ws"));
+    }
+
+    #[test]
+    fn server_ping_gets_empty_result() {
+        let reply = reply_to_server_request(&serde_json::json!(7), "ping");
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["result"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn unknown_server_request_gets_method_not_found() {
+        let reply = reply_to_server_request(&serde_json::json!("abc"), "sampling/createMessage");
+        assert_eq!(reply["id"], "abc");
+        assert_eq!(reply["error"]["code"], -32601);
+    }
+}
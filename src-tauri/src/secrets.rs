@@ -0,0 +1,512 @@
+//! Secret storage for API keys, OAuth tokens and `${secret:NAME}` values in
+//! server configs.
+//!
+//! Secrets live in the Linux Secret Service (through `secret-tool`) when a
+//! keyring is running. Otherwise they go to a file encrypted with
+//! ChaCha20-Poly1305 under a master key, which is either derived from the
+//! `AGENT_HUB_MASTER_KEY` passphrase or generated once and kept next to the file
+//! with owner-only permissions. config.json only ever holds references.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::Rng;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+
+/// `service` attribute of every Secret Service item we own.
+const SERVICE: &str = "agent-hub";
+
+/// Names of secrets the app keeps for itself. They're hidden from `names()`,
+/// and user-chosen names can't collide since they may not contain `/`.
+pub const OAUTH_STORE_SECRET: &str = "agent-hub/oauth-store";
+pub const OPENAI_API_KEY_SECRET: &str = "agent-hub/openai-api-key";
+const INTERNAL_PREFIX: &str = "agent-hub/";
+
+const SECRETS_FILE: &str = "secrets.enc";
+const MASTER_KEY_FILE: &str = "master.key";
+const MASTER_KEY_ENV: &str = "AGENT_HUB_MASTER_KEY";
+const PBKDF2_ITERATIONS: u32 = 210_000;
+
+pub struct SecretStore {
+    backend: Mutex<Backend>,
+}
+
+enum Backend {
+    SecretService,
+    EncryptedFile(Box<EncryptedFile>),
+}
+
+impl SecretStore {
+    /// Use the Secret Service if one is reachable, else the encrypted file in `data_dir`.
+    pub fn open(data_dir: &Path) -> Self {
+        let backend = if secret_service_available() {
+            info!("Storing secrets in the Secret Service");
+            Backend::SecretService
+        } else {
+            info!("No Secret Service available, storing secrets in an encrypted file");
+            Backend::EncryptedFile(Box::new(EncryptedFile::open(data_dir)))
+        };
+        Self {
+            backend: Mutex::new(backend),
+        }
+    }
+
+    /// A store backed by an encrypted file in `dir`, for tests.
+    #[cfg(test)]
+    pub(crate) fn encrypted_file(dir: &Path) -> Self {
+        Self {
+            backend: Mutex::new(Backend::EncryptedFile(Box::new(EncryptedFile::open(dir)))),
+        }
+    }
+
+    /// Short description of where secrets are kept, for the UI.
+    pub fn backend_name(&self) -> &'static str {
+        match *self.backend.lock().unwrap() {
+            Backend::SecretService => "Secret Service",
+            Backend::EncryptedFile(_) => "Encrypted file",
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        match &*self.backend.lock().unwrap() {
+            Backend::SecretService => secret_tool_lookup(name),
+            Backend::EncryptedFile(file) => file.entries.get(name).cloned(),
+        }
+    }
+
+    pub fn set(&self, name: &str, value: &str) -> Result<(), AppError> {
+        match &mut *self.backend.lock().unwrap() {
+            Backend::SecretService => secret_tool_store(name, value),
+            Backend::EncryptedFile(file) => {
+                file.entries.insert(name.to_string(), value.to_string());
+                file.save()
+            }
+        }
+    }
+
+    /// Remove a secret. Returns whether it existed.
+    pub fn delete(&self, name: &str) -> Result<bool, AppError> {
+        match &mut *self.backend.lock().unwrap() {
+            Backend::SecretService => {
+                if secret_tool_lookup(name).is_none() {
+                    return Ok(false);
+                }
+                secret_tool_clear(name)?;
+                Ok(true)
+            }
+            Backend::EncryptedFile(file) => {
+                if file.entries.remove(name).is_none() {
+                    return Ok(false);
+                }
+                file.save()?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Names of the user's secrets, sorted. Internal entries are left out.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = match &*self.backend.lock().unwrap() {
+            Backend::SecretService => secret_tool_names(),
+            Backend::EncryptedFile(file) => file.entries.keys().cloned().collect(),
+        };
+        names.retain(|n| !n.starts_with(INTERNAL_PREFIX));
+        names.sort();
+        names
+    }
+
+    /// Replace every `${secret:NAME}` in `text` with the secret's value.
+    /// `$${secret:NAME}` is an escape for the literal `${secret:NAME}`.
+    pub fn expand(&self, text: &str) -> Result<String, AppError> {
+        expand_with(text, |name| self.get(name))
+    }
+
+    /// Expand secret references in every value of a map.
+    pub fn expand_values(&self, map: &mut HashMap<String, String>) -> Result<(), AppError> {
+        for value in map.values_mut() {
+            *value = self.expand(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Secret names are kept to characters that are safe in env var names and
+/// references: letters, digits, `_`, `-` and `.`.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// The config value that refers to a secret.
+pub fn reference(name: &str) -> String {
+    format!("${{secret:{name}}}")
+}
+
+fn expand_with(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, AppError> {
+    const OPEN: &str = "${secret:";
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(OPEN) {
+        let after = &rest[start + OPEN.len()..];
+        // `$${secret:NAME}` stays a literal reference
+        if let Some(before) = rest[..start].strip_suffix('$') {
+            out.push_str(before);
+            out.push_str(OPEN);
+            rest = after;
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let Some(end) = after.find('}') else {
+            // Unterminated reference: leave it as written
+            out.push_str(&rest[start..]);
+            return Ok(out);
+        };
+        let name = &after[..end];
+        let value = lookup(name)
+            .ok_or_else(|| AppError::Validation(format!("Secret not found: {name}")))?;
+        out.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// --- Encrypted file backend ---
+
+struct EncryptedFile {
+    path: PathBuf,
+    key: LessSafeKey,
+    /// PBKDF2 salt when the key comes from a passphrase.
+    salt: Option<Vec<u8>>,
+    /// The file exists but the master key doesn't open it, e.g. after the
+    /// passphrase changed. Writes are refused so the old secrets survive.
+    unreadable: bool,
+    entries: HashMap<String, String>,
+}
+
+/// On-disk layout of `secrets.enc`.
+#[derive(Serialize, Deserialize)]
+struct SealedFile {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    salt: Option<String>,
+    nonce: String,
+    data: String,
+}
+
+impl EncryptedFile {
+    fn open(dir: &Path) -> Self {
+        let path = dir.join(SECRETS_FILE);
+        let sealed: Option<SealedFile> = std::fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice(&raw).ok());
+        let passphrase = std::env::var(MASTER_KEY_ENV).ok().filter(|p| !p.is_empty());
+
+        let (key, salt) = match passphrase {
+            Some(passphrase) => {
+                let salt = sealed
+                    .as_ref()
+                    .and_then(|s| s.salt.as_deref())
+                    .and_then(|s| STANDARD.decode(s).ok())
+                    .unwrap_or_else(|| random_bytes::<16>().to_vec());
+                (key_from_passphrase(&passphrase, &salt), Some(salt))
+            }
+            None => (key_from_file(&dir.join(MASTER_KEY_FILE)), None),
+        };
+
+        let mut unreadable = false;
+        let entries = match sealed {
+            Some(sealed) => unseal(&key, &sealed).unwrap_or_else(|e| {
+                warn!("Could not decrypt {}: {e}", path.display());
+                unreadable = true;
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+
+        Self {
+            path,
+            key: LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &key).expect("32-byte key")),
+            salt,
+            unreadable,
+            entries,
+        }
+    }
+
+    fn save(&self) -> Result<(), AppError> {
+        if self.unreadable {
+            return Err(AppError::Validation(format!(
+                "{} could not be decrypted with the current master key; not overwriting it",
+                self.path.display()
+            )));
+        }
+        let nonce = random_bytes::<NONCE_LEN>();
+        let mut data = serde_json::to_vec(&self.entries)?;
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| AppError::Validation("Failed to encrypt secrets".into()))?;
+
+        let sealed = SealedFile {
+            version: 1,
+            salt: self.salt.as_ref().map(|s| STANDARD.encode(s)),
+            nonce: STANDARD.encode(nonce),
+            data: STANDARD.encode(&data),
+        };
+        write_private(&self.path, &serde_json::to_vec(&sealed)?)
+    }
+}
+
+fn unseal(key: &[u8; 32], sealed: &SealedFile) -> Result<HashMap<String, String>, AppError> {
+    let bad = |what: &str| AppError::Validation(format!("Invalid secrets file: {what}"));
+    let nonce: [u8; NONCE_LEN] = STANDARD
+        .decode(&sealed.nonce)
+        .ok()
+        .and_then(|n| n.try_into().ok())
+        .ok_or_else(|| bad("nonce"))?;
+    let mut data = STANDARD.decode(&sealed.data).map_err(|_| bad("data"))?;
+
+    let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).expect("32-byte key"));
+    let plain = key
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| bad("wrong master key or corrupted data"))?;
+    Ok(serde_json::from_slice(plain)?)
+}
+
+fn key_from_passphrase(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).expect("non-zero iterations"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+/// Read the generated master key, creating it on first use.
+fn key_from_file(path: &Path) -> [u8; 32] {
+    let existing = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| STANDARD.decode(raw.trim()).ok())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+    if let Some(key) = existing {
+        return key;
+    }
+
+    let key = random_bytes::<32>();
+    if let Err(e) = write_private(path, STANDARD.encode(key).as_bytes()) {
+        warn!("Failed to save master key to {}: {e}", path.display());
+    }
+    key
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rng().fill(&mut bytes[..]);
+    bytes
+}
+
+/// Write a file readable only by the current user, replacing it atomically.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), AppError> {
+    use std::io::Write;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+// --- Secret Service backend (libsecret's `secret-tool`) ---
+
+#[cfg(target_os = "linux")]
+fn secret_service_available() -> bool {
+    // A lookup that finds nothing exits 1 quietly; any complaint means no usable keyring
+    match secret_tool(
+        &["lookup", "service", SERVICE, "name", "agent-hub/probe"],
+        None,
+    ) {
+        Ok(output) => output.stderr.is_empty(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn secret_service_available() -> bool {
+    false
+}
+
+fn secret_tool(args: &[&str], stdin: Option<&str>) -> std::io::Result<std::process::Output> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("secret-tool")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    {
+        let mut pipe = child.stdin.take().expect("piped stdin");
+        if let Some(input) = stdin {
+            pipe.write_all(input.as_bytes())?;
+        }
+    }
+    child.wait_with_output()
+}
+
+fn secret_tool_lookup(name: &str) -> Option<String> {
+    let output = secret_tool(&["lookup", "service", SERVICE, "name", name], None).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn secret_tool_store(name: &str, value: &str) -> Result<(), AppError> {
+    let label = format!("Agent Hub: {name}");
+    let output = secret_tool(
+        &["store", "--label", &label, "service", SERVICE, "name", name],
+        Some(value),
+    )?;
+    if !output.status.success() {
+        return Err(AppError::Validation(format!(
+            "Failed to store secret {name}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn secret_tool_clear(name: &str) -> Result<(), AppError> {
+    let output = secret_tool(&["clear", "service", SERVICE, "name", name], None)?;
+    if !output.status.success() {
+        return Err(AppError::Validation(format!(
+            "Failed to delete secret {name}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn secret_tool_names() -> Vec<String> {
+    let Ok(output) = secret_tool(&["search", "--all", "service", SERVICE], None) else {
+        return Vec::new();
+    };
+    // Item attributes are printed as `attribute.name = VALUE`, on stdout or stderr
+    // depending on the libsecret version
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    text.lines()
+        .filter_map(|line| line.strip_prefix("attribute.name = "))
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("agent-hub-secrets-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn expands_references() {
+        let lookup = |name: &str| (name == "TOKEN").then(|| "abc".to_string());
+        assert_eq!(
+            expand_with("Bearer ${secret:TOKEN}", lookup).unwrap(),
+            "Bearer abc"
+        );
+        assert_eq!(
+            expand_with("plain ${HOME}", lookup).unwrap(),
+            "plain ${HOME}"
+        );
+        assert_eq!(
+            expand_with("${secret:TOKEN", lookup).unwrap(),
+            "${secret:TOKEN"
+        );
+        assert_eq!(
+            expand_with("$${secret:TOKEN} is ${secret:TOKEN}", lookup).unwrap(),
+            "${secret:TOKEN} is abc"
+        );
+        assert!(matches!(
+            expand_with("${secret:MISSING}", lookup),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn encrypted_file_round_trips_and_hides_values() {
+        let dir = temp_dir();
+        let store = SecretStore::encrypted_file(&dir);
+        store.set("GITHUB_TOKEN", "ghp_secret").unwrap();
+        store.set(OAUTH_STORE_SECRET, "{}").unwrap();
+
+        let raw = std::fs::read_to_string(dir.join(SECRETS_FILE)).unwrap();
+        assert!(!raw.contains("ghp_secret"));
+
+        let reopened = EncryptedFile::open(&dir);
+        assert_eq!(reopened.entries["GITHUB_TOKEN"], "ghp_secret");
+        assert_eq!(store.names(), vec!["GITHUB_TOKEN".to_string()]);
+
+        assert!(store.delete("GITHUB_TOKEN").unwrap());
+        assert!(!store.delete("GITHUB_TOKEN").unwrap());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn wrong_key_does_not_decrypt() {
+        let key = random_bytes::<32>();
+        let nonce = random_bytes::<NONCE_LEN>();
+        let mut data = br#"{"A":"1"}"#.to_vec();
+        LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &key).unwrap())
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .unwrap();
+        let sealed = SealedFile {
+            version: 1,
+            salt: None,
+            nonce: STANDARD.encode(nonce),
+            data: STANDARD.encode(&data),
+        };
+
+        assert_eq!(unseal(&key, &sealed).unwrap()["A"], "1");
+        assert!(unseal(&random_bytes::<32>(), &sealed).is_err());
+    }
+
+    #[test]
+    fn validates_names() {
+        assert!(is_valid_name("OPENAI_API_KEY"));
+        assert!(is_valid_name("gateway.token-2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("agent-hub/oauth-store"));
+        assert!(!is_valid_name("has space"));
+        assert_eq!(reference("X"), "${secret:X}");
+    }
+}
@@ -0,0 +1,348 @@
+//! Persistent per-server logs.
+//!
+//! Every `server-log` event, plus stdout lines that aren't JSON-RPC, is
+//! appended as a JSON line to `logs/<server-id>.log` under the app data dir.
+//! Files rotate at [`MAX_FILE_BYTES`], keeping [`MAX_FILES`] per server.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::stats::unix_now_millis;
+
+/// Rotate a server's log once it grows past this size.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// The current file plus rotated ones (`.1` is the newest rotated file).
+const MAX_FILES: usize = 5;
+/// Cap on tail and search results, keeping the most recent entries.
+const MAX_RESULTS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogSource {
+    /// Non-JSON-RPC output on a stdio server's stdout.
+    Stdout,
+    Stderr,
+    /// Log messages the server sent as MCP notifications.
+    Server,
+    /// Connects, disconnects, exits and other events from Agent Hub itself.
+    Lifecycle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub level: String,
+    pub source: LogSource,
+    pub message: String,
+}
+
+/// Filters for [`ServerLogs::search`]. Times are milliseconds since the epoch.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQuery {
+    pub pattern: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+struct OpenLog {
+    file: File,
+    size: u64,
+}
+
+pub struct ServerLogs {
+    dir: PathBuf,
+    open: Mutex<HashMap<String, OpenLog>>,
+}
+
+impl ServerLogs {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn append(&self, server_id: &str, level: &str, source: LogSource, message: &str) {
+        let entry = LogEntry {
+            timestamp: unix_now_millis(),
+            level: level.to_string(),
+            source,
+            message: message.to_string(),
+        };
+        if let Err(e) = self.write(server_id, &entry) {
+            warn!("Failed to write log for {server_id}: {e}");
+        }
+    }
+
+    fn write(&self, server_id: &str, entry: &LogEntry) -> Result<(), AppError> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut open = self.open.lock().unwrap();
+        if open
+            .get(server_id)
+            .is_some_and(|log| log.size + line.len() as u64 > MAX_FILE_BYTES)
+        {
+            open.remove(server_id);
+            self.rotate(server_id)?;
+        }
+        let log = match open.entry(server_id.to_string()) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                fs::create_dir_all(&self.dir)?;
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.path(server_id, 0))?;
+                let size = file.metadata()?.len();
+                e.insert(OpenLog { file, size })
+            }
+        };
+        log.file.write_all(line.as_bytes())?;
+        log.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `.log` → `.1` → `.2` …, dropping the oldest.
+    fn rotate(&self, server_id: &str) -> Result<(), AppError> {
+        let _ = fs::remove_file(self.path(server_id, MAX_FILES - 1));
+        for index in (0..MAX_FILES - 1).rev() {
+            let from = self.path(server_id, index);
+            if from.exists() {
+                fs::rename(&from, self.path(server_id, index + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The last `count` entries (at most [`MAX_RESULTS`]), oldest first.
+    pub fn tail(&self, server_id: &str, count: usize) -> Vec<LogEntry> {
+        let count = count.min(MAX_RESULTS);
+        if count == 0 {
+            return Vec::new();
+        }
+        let mut tail = VecDeque::with_capacity(count);
+        for entry in self.entries(server_id) {
+            if tail.len() == count {
+                tail.pop_front();
+            }
+            tail.push_back(entry);
+        }
+        tail.into()
+    }
+
+    /// Entries matching the query, oldest first. Keeps the most recent
+    /// matches when there are more than the limit.
+    pub fn search(&self, server_id: &str, query: &LogQuery) -> Result<Vec<LogEntry>, AppError> {
+        let pattern = query
+            .pattern
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| AppError::Validation(format!("Invalid search pattern: {e}")))?;
+        let limit = query.limit.unwrap_or(MAX_RESULTS).min(MAX_RESULTS);
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut matches = VecDeque::new();
+        for entry in self.entries(server_id) {
+            if query.since.is_some_and(|since| entry.timestamp < since)
+                || query.until.is_some_and(|until| entry.timestamp > until)
+                || pattern
+                    .as_ref()
+                    .is_some_and(|re| !re.is_match(&entry.message))
+            {
+                continue;
+            }
+            if matches.len() == limit {
+                matches.pop_front();
+            }
+            matches.push_back(entry);
+        }
+        Ok(matches.into())
+    }
+
+    /// Copy every retained entry to `dest` as JSON lines. Returns the count.
+    pub fn export(&self, server_id: &str, dest: &Path) -> Result<usize, AppError> {
+        let mut out = File::create(dest)?;
+        let mut count = 0;
+        for entry in self.entries(server_id) {
+            serde_json::to_writer(&mut out, &entry)?;
+            out.write_all(b"\n")?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Delete a server's logs, e.g. when the server is removed.
+    pub fn remove(&self, server_id: &str) {
+        self.open.lock().unwrap().remove(server_id);
+        for index in 0..MAX_FILES {
+            let _ = fs::remove_file(self.path(server_id, index));
+        }
+    }
+
+    /// All retained entries, oldest file first. Unparseable lines are skipped.
+    fn entries(&self, server_id: &str) -> impl Iterator<Item = LogEntry> {
+        let paths: Vec<PathBuf> = (0..MAX_FILES)
+            .rev()
+            .map(|index| self.path(server_id, index))
+            .collect();
+        paths
+            .into_iter()
+            .filter_map(|path| File::open(path).ok())
+            .flat_map(|file| BufReader::new(file).lines().map_while(Result::ok))
+            .filter_map(|line| serde_json::from_str(&line).ok())
+    }
+
+    fn path(&self, server_id: &str, index: usize) -> PathBuf {
+        let name = file_stem(server_id);
+        if index == 0 {
+            self.dir.join(format!("{name}.log"))
+        } else {
+            self.dir.join(format!("{name}.log.{index}"))
+        }
+    }
+}
+
+/// Emit a `server-log` event and append it to the server's log file.
+pub fn emit(app: &AppHandle, server_id: &str, level: &str, source: LogSource, message: &str) {
+    let _ = app.emit(
+        "server-log",
+        serde_json::json!({
+            "serverId": server_id,
+            "level": level,
+            "message": message,
+        }),
+    );
+    record(app, server_id, level, source, message);
+}
+
+/// Append to the server's log file without emitting an event.
+pub fn record(app: &AppHandle, server_id: &str, level: &str, source: LogSource, message: &str) {
+    if let Some(logs) = app.try_state::<ServerLogs>() {
+        logs.append(server_id, level, source, message);
+    }
+}
+
+/// Server IDs become file names, so keep only characters that are safe there.
+fn file_stem(server_id: &str) -> String {
+    server_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_logs(name: &str) -> ServerLogs {
+        let dir =
+            std::env::temp_dir().join(format!("agent-hub-logs-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ServerLogs::new(dir)
+    }
+
+    #[test]
+    fn tails_and_searches_entries() {
+        let logs = temp_logs("search");
+        logs.append("srv", "info", LogSource::Lifecycle, "Connected");
+        logs.append(
+            "srv",
+            "error",
+            LogSource::Stderr,
+            "ECONNREFUSED 127.0.0.1:5432",
+        );
+        logs.append("srv", "info", LogSource::Stdout, "listening on stdio");
+
+        let tail = logs.tail("srv", 2);
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[1].message, "listening on stdio");
+        assert!(logs.tail("srv", 0).is_empty());
+        assert_eq!(logs.tail("srv", usize::MAX).len(), 3);
+
+        let none = LogQuery {
+            limit: Some(0),
+            ..Default::default()
+        };
+        assert!(logs.search("srv", &none).unwrap().is_empty());
+        let huge = LogQuery {
+            limit: Some(usize::MAX),
+            ..Default::default()
+        };
+        assert_eq!(logs.search("srv", &huge).unwrap().len(), 3);
+
+        let query = LogQuery {
+            pattern: Some("ECONN\\w+".into()),
+            ..Default::default()
+        };
+        let found = logs.search("srv", &query).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].source, LogSource::Stderr);
+
+        let future = LogQuery {
+            since: Some(unix_now_millis() + 60_000),
+            ..Default::default()
+        };
+        assert!(logs.search("srv", &future).unwrap().is_empty());
+
+        let bad = LogQuery {
+            pattern: Some("(".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            logs.search("srv", &bad),
+            Err(AppError::Validation(_))
+        ));
+        logs.remove("srv");
+    }
+
+    #[test]
+    fn rotates_and_keeps_a_bounded_number_of_files() {
+        let logs = temp_logs("rotate");
+        let message = "x".repeat(64 * 1024);
+        for _ in 0..(MAX_FILES * 20) {
+            logs.append("srv", "info", LogSource::Stderr, &message);
+        }
+
+        assert!(logs.path("srv", MAX_FILES - 1).exists());
+        assert!(!logs.path("srv", MAX_FILES).exists());
+        for index in 0..MAX_FILES {
+            let size = fs::metadata(logs.path("srv", index)).unwrap().len();
+            assert!(size <= MAX_FILE_BYTES);
+        }
+        logs.remove("srv");
+        assert!(!logs.path("srv", 0).exists());
+    }
+
+    #[test]
+    fn sanitizes_server_ids_in_file_names() {
+        let logs = temp_logs("names");
+        assert_eq!(
+            logs.path("../etc/passwd", 0).file_name().unwrap(),
+            "___etc_passwd.log"
+        );
+    }
+}
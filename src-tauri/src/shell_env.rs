@@ -0,0 +1,273 @@
+//! The user's login-shell environment.
+//!
+//! Apps launched from Finder, the dock or a desktop launcher inherit a minimal
+//! environment, so tools installed through nvm, asdf, mise or Homebrew aren't
+//! on `PATH`. We run `$SHELL -ilc env` once and use the result as the base
+//! environment for every spawned server.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+/// How long to wait for the login shell before falling back to our own environment.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Printed around the `env` output so shell startup noise (motd, prompts,
+/// plugin managers) can be told apart from the variables.
+const MARKER: &str = "__AGENT_HUB_ENV__";
+
+/// Variables describing the capturing shell itself rather than the user's setup.
+const SKIPPED: &[&str] = &["_", "PWD", "OLDPWD", "SHLVL"];
+
+static SHELL_ENV: OnceLock<ShellEnv> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct ShellEnv {
+    vars: HashMap<String, String>,
+    /// Whether the variables came from the login shell, as opposed to a fallback
+    /// to this process's environment.
+    captured: bool,
+}
+
+impl ShellEnv {
+    pub fn vars(&self) -> &HashMap<String, String> {
+        &self.vars
+    }
+
+    /// Find an executable by name on the captured `PATH`. Names containing a
+    /// path separator are checked as given.
+    pub fn which(&self, name: &str) -> Option<PathBuf> {
+        if name.contains(std::path::MAIN_SEPARATOR) {
+            let path = PathBuf::from(name);
+            return is_executable(&path).then_some(path);
+        }
+        let path = self.vars.get("PATH")?;
+        std::env::split_paths(path)
+            .map(|dir| dir.join(name))
+            .find(|candidate| is_executable(candidate))
+    }
+}
+
+/// The login-shell environment, captured on first use. Callers block while a
+/// capture is in progress, so warm it up with [`init`] at startup.
+pub fn get() -> &'static ShellEnv {
+    SHELL_ENV.get_or_init(capture)
+}
+
+/// Start capturing in the background so the first server spawn doesn't wait.
+pub fn init() {
+    std::thread::spawn(|| {
+        let env = get();
+        if env.captured {
+            info!("Captured {} variables from the login shell", env.vars.len());
+        }
+    });
+}
+
+fn capture() -> ShellEnv {
+    let mut vars: HashMap<String, String> = std::env::vars().collect();
+    let captured = match run_login_shell() {
+        Ok(shell_vars) => {
+            vars.extend(shell_vars);
+            true
+        }
+        Err(e) => {
+            warn!("Using the app environment, login shell capture failed: {e}");
+            false
+        }
+    };
+    ShellEnv { vars, captured }
+}
+
+#[cfg(unix)]
+fn run_login_shell() -> Result<HashMap<String, String>, String> {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    let script = format!("echo {MARKER}; env; echo {MARKER}");
+
+    let mut child = Command::new(&shell)
+        .args(["-ilc", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to start {shell}: {e}"))?;
+
+    let stdout = child.stdout.take().ok_or("no stdout")?;
+    let output = read_env_output(stdout, CAPTURE_TIMEOUT);
+    // Whatever is still running is stuck in the user's startup files or done
+    let _ = child.kill();
+    let _ = child.wait();
+
+    let vars = parse_env_output(&output.map_err(|e| format!("{shell} {e}"))?);
+    if vars.contains_key("PATH") {
+        Ok(vars)
+    } else {
+        Err(format!("{shell} printed no PATH"))
+    }
+}
+
+#[cfg(not(unix))]
+fn run_login_shell() -> Result<HashMap<String, String>, String> {
+    Err("not supported on this platform".to_string())
+}
+
+/// Read the shell's output until the closing marker, end of file or `timeout`.
+/// A daemon started from the user's profile can inherit stdout and hold it
+/// open long after the shell exits, so end of file can't be waited for.
+#[cfg(unix)]
+fn read_env_output(
+    mut stdout: impl Read + Send + 'static,
+    timeout: Duration,
+) -> Result<String, String> {
+    // The reader thread is left blocked if the pipe never closes
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0; 8192];
+        while let Ok(n @ 1..) = stdout.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + timeout;
+    let mut output = Vec::new();
+    loop {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(chunk) => {
+                output.extend_from_slice(&chunk);
+                if String::from_utf8_lossy(&output).matches(MARKER).count() >= 2 {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => return Err(format!("timed out after {timeout:?}")),
+        }
+    }
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Parse `env` output between the markers. Lines that don't start a new
+/// `NAME=` entry continue the previous value (multi-line variables).
+fn parse_env_output(output: &str) -> HashMap<String, String> {
+    let body = output
+        .split_once(MARKER)
+        .and_then(|(_, rest)| rest.split_once(MARKER))
+        .map_or("", |(body, _)| body);
+
+    let mut vars = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for line in body.lines() {
+        match line.split_once('=') {
+            Some((name, value)) if is_name(name) => {
+                if let Some((name, value)) = current.take() {
+                    vars.insert(name, value);
+                }
+                current = Some((name.to_string(), value.to_string()));
+            }
+            _ => {
+                if let Some((_, value)) = current.as_mut() {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+    if let Some((name, value)) = current {
+        vars.insert(name, value);
+    }
+    vars.retain(|name, _| !SKIPPED.contains(&name.as_str()));
+    vars
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_env_between_markers() {
+        let output = format!(
+            "Welcome to zsh!\n{MARKER}\nPATH=/usr/bin:/opt/bin\nMULTI=one\ntwo\nSHLVL=2\nEMPTY=\n{MARKER}\nbye\n"
+        );
+        let vars = parse_env_output(&output);
+
+        assert_eq!(vars["PATH"], "/usr/bin:/opt/bin");
+        assert_eq!(vars["MULTI"], "one\ntwo");
+        assert_eq!(vars["EMPTY"], "");
+        assert!(!vars.contains_key("SHLVL"));
+        assert_eq!(vars.len(), 3);
+    }
+
+    #[test]
+    fn missing_markers_yield_nothing() {
+        assert!(parse_env_output("PATH=/usr/bin\n").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reading_stops_at_the_closing_marker_or_deadline() {
+        // `sleep` holds stdout open like a daemon started from a profile would
+        let spawn = |script: &str| {
+            Command::new("/bin/sh")
+                .args(["-c", script])
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap()
+        };
+        let mut child = spawn(&format!(
+            "echo {MARKER}; echo PATH=/bin; echo {MARKER}; sleep 30"
+        ));
+        let started = Instant::now();
+        let output = read_env_output(child.stdout.take().unwrap(), CAPTURE_TIMEOUT).unwrap();
+        assert!(started.elapsed() < CAPTURE_TIMEOUT);
+        assert_eq!(parse_env_output(&output)["PATH"], "/bin");
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let mut child = spawn(&format!("echo {MARKER}; sleep 30"));
+        let result = read_env_output(child.stdout.take().unwrap(), Duration::from_millis(200));
+        assert!(result.unwrap_err().starts_with("timed out"));
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn which_searches_captured_path() {
+        let env = ShellEnv {
+            vars: HashMap::from([("PATH".to_string(), "/nonexistent:/bin".to_string())]),
+            captured: true,
+        };
+        assert_eq!(env.which("sh"), Some(PathBuf::from("/bin/sh")));
+        assert_eq!(env.which("/bin/sh"), Some(PathBuf::from("/bin/sh")));
+        assert_eq!(env.which("definitely-not-a-command"), None);
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for servers that run inside a Docker or Podman container.
+///
+/// The server's `args` are appended after the image (as the container command)
+/// and its `env` is passed through with `-e`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerConfig {
+    #[serde(default)]
+    pub runtime: ContainerRuntime,
+    /// Image name without tag, e.g. `mcp/fetch` or `ghcr.io/org/server`.
+    pub image: String,
+    /// Image tag. Defaults to `latest` when unset.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub volumes: Vec<VolumeMount>,
+    /// Network to attach to (`--network`), e.g. `none`, `host` or a named network.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub mode: ContainerMode,
+    /// Port the server listens on inside the container (HTTP mode only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub port: Option<u16>,
+    /// Endpoint path for HTTP mode. Defaults to `/mcp`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntime {
+    /// Use docker if installed, otherwise podman.
+    #[default]
+    Auto,
+    Docker,
+    Podman,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerMode {
+    /// Speak MCP over the container's stdin/stdout (`run -i`).
+    #[default]
+    Stdio,
+    /// Publish the container's HTTP port on localhost and connect over HTTP.
+    Http,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeMount {
+    /// Host path (`~` expands to the home dir) or named volume.
+    pub host: String,
+    pub container: String,
+    #[serde(default)]
+    pub read_only: bool,
+}
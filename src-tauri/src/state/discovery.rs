@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// How `discover_tools` ranks results.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMode {
+    /// BM25 over tool names, descriptions and parameters.
+    #[default]
+    Keyword,
+    /// Similarity between embeddings of the query and of each tool.
+    Semantic,
+    /// Keyword and semantic rankings fused together.
+    Hybrid,
+}
+
+/// A tool the discovery endpoint lists directly, next to its meta-tools.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedTool {
+    pub server_id: String,
+    pub tool_name: String,
+}
+
+/// Which tools the discovery endpoint lists directly. With neither set, it
+/// lists only the meta-tools and everything else goes through `discover_tools`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolPinning {
+    /// List each client's most-called tools, up to this many.
+    pub top_n: usize,
+    /// Tools listed for every client, ahead of the most-called ones.
+    pub pinned: Vec<PinnedTool>,
+}
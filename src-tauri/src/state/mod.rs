@@ -1,18 +1,27 @@
+mod container;
+mod discovery;
 mod embedding;
 mod oauth;
 pub mod plugin;
 mod providers;
 pub mod registry;
+mod replay;
+mod sandbox;
 pub mod skill;
 pub mod skills_registry;
 mod server;
 
+pub use container::*;
+pub use discovery::*;
 pub use embedding::*;
 pub use oauth::*;
+pub use replay::*;
+pub use sandbox::*;
 pub use server::*;
 pub use skill::InstalledSkill;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Mutex;
 
 /// A log entry buffered before the frontend is ready.
@@ -27,17 +36,29 @@ pub struct BufferedLog {
 pub struct AppState {
     pub servers: Vec<ServerConfig>,
     pub connections: HashMap<String, ConnectionState>,
+    /// Last-known tools of every server that has connected, by server ID. Kept
+    /// across disconnects and restarts so discovery can still find them.
+    pub tool_catalog: HashMap<String, Vec<McpTool>>,
     /// IDs of AI tool integrations that Agent Hub is configured to manage.
     pub enabled_integrations: Vec<String>,
+    /// Project folders of enabled VS Code workspace integrations, so they stay
+    /// managed after VS Code drops the folder from its recent list.
+    pub vscode_workspaces: Vec<PathBuf>,
     pub embedding_config: EmbeddingConfig,
     /// Logs emitted before the frontend event listener is ready.
     pub log_buffer: Vec<BufferedLog>,
     /// When true, integrations get a single discovery endpoint instead of per-server entries.
     pub tool_discovery_enabled: bool,
+    /// How `discover_tools` ranks results.
+    pub discovery_search_mode: SearchMode,
+    /// Tools the discovery endpoint lists directly instead of hiding behind `discover_tools`.
+    pub discovery_pinning: ToolPinning,
     /// Skills installed from the skills.sh marketplace.
     pub installed_skills: Vec<InstalledSkill>,
     /// IDs of AI tools that should receive SKILL.md files (separate from MCP integrations).
     pub enabled_skill_integrations: Vec<String>,
+    /// Device flow sign-ins waiting for the user, keyed by server ID.
+    pub device_codes: HashMap<String, DeviceCodePrompt>,
 }
 
 pub struct ConnectionState {
@@ -49,12 +70,17 @@ impl AppState {
         Self {
             servers: Vec::new(),
             connections: HashMap::new(),
+            tool_catalog: HashMap::new(),
             enabled_integrations: Vec::new(),
+            vscode_workspaces: Vec::new(),
             embedding_config: EmbeddingConfig::default(),
             log_buffer: Vec::new(),
             tool_discovery_enabled: false,
+            discovery_search_mode: SearchMode::default(),
+            discovery_pinning: ToolPinning::default(),
             installed_skills: Vec::new(),
             enabled_skill_integrations: Vec::new(),
+            device_codes: HashMap::new(),
         }
     }
 }
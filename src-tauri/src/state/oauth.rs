@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthTokens {
@@ -11,6 +12,9 @@ pub struct OAuthTokens {
     pub expires_in: Option<u64>,
     /// Unix timestamp (seconds) when these tokens were obtained.
     pub obtained_at: u64,
+    /// Space-separated scopes granted, when the server reported them.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub scope: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +24,12 @@ pub struct AuthServerMetadata {
     pub token_endpoint: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub registration_endpoint: Option<String>,
+    /// RFC 8628 endpoint, present when the server supports the device flow.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub device_authorization_endpoint: Option<String>,
+    /// RFC 7009 endpoint for revoking tokens on sign-out.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub revocation_endpoint: Option<String>,
     #[serde(default)]
     pub scopes_supported: Vec<String>,
     #[serde(default)]
@@ -35,15 +45,103 @@ pub struct OAuthState {
     pub client_secret: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tokens: Option<OAuthTokens>,
+    /// RFC 8707 resource the tokens were issued for, sent again on refresh.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resource: Option<String>,
+    /// How the tokens were obtained, which decides how they're renewed.
+    #[serde(default)]
+    pub mode: AuthMode,
+    /// Unix timestamp (seconds) of the last successful refresh.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_refreshed_at: Option<u64>,
+}
+
+/// How an HTTP server authenticates.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthMode {
+    /// Authorization code + PKCE (or device flow) sign-in by the user.
+    #[default]
+    Interactive,
+    /// Machine-to-machine client credentials grant, fetched without user interaction.
+    ClientCredentials,
+    /// A long-lived bearer token kept in the secret store.
+    StaticToken,
+}
+
+/// Per-server OAuth settings, for authorization servers that don't support
+/// dynamic client registration or expect particular scopes or parameters.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthConfig {
+    /// Pre-registered client ID. Skips dynamic registration when set.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub client_secret: Option<String>,
+    /// Scopes to request. Empty means all the server advertises.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub scopes: Vec<String>,
+    /// Extra query parameters for the authorization request, e.g. `audience` or `prompt`.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub extra_params: HashMap<String, String>,
+    /// RFC 8707 resource indicator. Defaults to the server's URL.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resource: Option<String>,
+    /// Always sign in with the device flow when the server supports it, even
+    /// if a local browser seems available.
+    #[serde(default)]
+    pub device_flow: bool,
+    #[serde(default)]
+    pub mode: AuthMode,
+    /// Token endpoint for client credentials, for gateways that don't publish
+    /// authorization server metadata.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub token_endpoint: Option<String>,
+    /// Name of the secret holding the bearer token in static token mode.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub token_secret: Option<String>,
+}
+
+/// A device flow sign-in waiting for the user, shown in the UI and tray.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCodePrompt {
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_uri_complete: Option<String>,
+}
+
+/// A server's current grant, as reported by `get_token_status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenStatus {
+    pub server_id: String,
+    pub server_name: String,
+    pub mode: AuthMode,
+    pub scopes: Vec<String>,
+    /// Unix timestamp (seconds) when the access token expires, if known.
+    pub expires_at: Option<u64>,
+    pub obtained_at: Option<u64>,
+    pub has_refresh_token: bool,
+    pub last_refreshed_at: Option<u64>,
+    /// Whether sign-out can revoke the tokens at the provider.
+    pub revocable: bool,
 }
 
 pub struct OAuthStore {
     entries: HashMap<String, OAuthState>,
+    /// Per-server locks that keep token refreshes from overlapping.
+    refresh_locks: HashMap<String, Arc<tokio::sync::Mutex<()>>>,
 }
 
 impl OAuthStore {
     pub fn from_entries(entries: HashMap<String, OAuthState>) -> Self {
-        Self { entries }
+        Self {
+            entries,
+            refresh_locks: HashMap::new(),
+        }
     }
 
     pub fn entries(&self) -> &HashMap<String, OAuthState> {
@@ -65,6 +163,14 @@ impl OAuthStore {
     pub fn entries_mut(&mut self) -> &mut HashMap<String, OAuthState> {
         &mut self.entries
     }
+
+    /// The lock a server's token refreshes run under.
+    pub fn refresh_lock(&mut self, server_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.refresh_locks
+            .entry(server_id.to_string())
+            .or_default()
+            .clone()
+    }
 }
 
 pub type SharedOAuthStore = tokio::sync::Mutex<OAuthStore>;
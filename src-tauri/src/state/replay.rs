@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for replay servers, which answer from a recorded fixture file
+/// instead of running a real server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayConfig {
+    /// Path to the fixture file (`~` expands to the home dir).
+    pub fixture: String,
+    #[serde(default)]
+    pub matching: MatchStrategy,
+    /// Argument fields left out of the comparison under `IgnoreFields`, as
+    /// dot-separated paths, e.g. `requestId` or `options.timestamp`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub ignore_fields: Vec<String>,
+}
+
+/// How `tools/call` arguments are matched against recorded ones.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchStrategy {
+    /// Arguments must be equal as JSON values.
+    Exact,
+    /// Like `Exact`, but `1` matches `1.0` and null fields match missing ones.
+    #[default]
+    Canonical,
+    /// `Canonical` after removing the configured `ignore_fields`.
+    IgnoreFields,
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Per-server sandbox settings for stdio servers (Linux only).
+///
+/// Applied by wrapping the server command in `systemd-run` (cgroup limits),
+/// `prlimit` (rlimits) and `bwrap` (filesystem/network isolation).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    /// Hard memory ceiling in MiB (cgroup `MemoryMax`, or `RLIMIT_AS` as fallback).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u64>,
+    /// CPU bandwidth as a percentage of one core (cgroup `CPUQuota`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_quota_percent: Option<u32>,
+    /// Total CPU time in seconds before the kernel sends SIGXCPU (`RLIMIT_CPU`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_time_secs: Option<u64>,
+    /// Mount the host filesystem read-only, except for `writable_paths`.
+    pub read_only_root: bool,
+    /// Paths bind-mounted read-write inside the sandbox. `~` expands to the home dir.
+    /// Package runners usually need their cache here (e.g. `~/.npm`, `~/.cache/uv`).
+    pub writable_paths: Vec<String>,
+    /// Allow network access. When false the server runs in an empty network namespace.
+    pub network: bool,
+    /// Give the server its own empty `/tmp`.
+    pub private_tmp: bool,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            memory_limit_mb: None,
+            cpu_quota_percent: None,
+            cpu_time_secs: None,
+            read_only_root: true,
+            writable_paths: Vec::new(),
+            network: true,
+            private_tmp: true,
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::{ContainerConfig, OAuthConfig, ReplayConfig, SandboxConfig};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerConfig {
@@ -14,6 +16,13 @@ pub struct ServerConfig {
     pub args: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+    /// Working directory for stdio servers. Defaults to the app's own.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cwd: Option<String>,
+    /// Dotenv files loaded into the server's environment, in order. Their
+    /// variables are also available to `${VAR}` references.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub env_file: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -32,6 +41,47 @@ pub struct ServerConfig {
     pub managed_by: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub registry_name: Option<String>,
+    /// Resource limits and isolation for stdio servers. `None` means unsandboxed.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sandbox: Option<SandboxConfig>,
+    /// Streamable HTTP vs legacy SSE, detected on first connect and reused after.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub http_mode: Option<HttpMode>,
+    /// Image and runtime settings for container servers.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub container: Option<ContainerConfig>,
+    /// Client, scope and resource settings for HTTP servers that use OAuth.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Fixture and matching settings for replay servers.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub replay: Option<ReplayConfig>,
+    /// Tools hidden from AI clients: left out of `tools/list` and discovery,
+    /// and calls to them are refused.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub disabled_tools: Option<Vec<String>>,
+    /// When set, the only tools AI clients get, e.g. from Gemini CLI's
+    /// `includeTools`. Replaced by `disabled_tools` once those are edited.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub enabled_tools: Option<Vec<String>>,
+    /// Per-server settings from an imported client config that Agent Hub
+    /// doesn't model, like Gemini CLI's `trust` and `timeout`. Written back
+    /// when the client's own config is restored.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub extra: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl ServerConfig {
+    /// Whether AI clients may see and call `tool`.
+    pub fn tool_enabled(&self, tool: &str) -> bool {
+        self.enabled_tools
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|t| t == tool))
+            && self
+                .disabled_tools
+                .as_ref()
+                .is_none_or(|disabled| !disabled.iter().any(|t| t == tool))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +89,16 @@ pub struct ServerConfig {
 pub enum ServerTransport {
     Stdio,
     Http,
+    Container,
+    /// Answers from a recorded fixture instead of a real server.
+    Replay,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HttpMode {
+    Streamable,
+    LegacySse,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -51,6 +111,7 @@ pub enum ServerStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ServerConfigInput {
     pub name: String,
     pub enabled: bool,
@@ -58,12 +119,22 @@ pub struct ServerConfigInput {
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
     pub env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env_file: Option<Vec<String>>,
     pub url: Option<String>,
     pub headers: Option<HashMap<String, String>>,
     pub tags: Option<Vec<String>>,
+    pub container: Option<ContainerConfig>,
+    pub oauth: Option<OAuthConfig>,
+    #[serde(default)]
+    pub replay: Option<ReplayConfig>,
+    #[serde(default)]
+    pub sandbox: Option<SandboxConfig>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpTool {
     pub name: String,
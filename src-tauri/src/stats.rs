@@ -12,6 +12,9 @@ pub struct ToolStats {
     pub total_calls: u64,
     pub errors: u64,
     pub total_duration_ms: u64,
+    /// Calls by client ID.
+    #[serde(default)]
+    pub clients: HashMap<String, u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +51,28 @@ impl ServerStats {
     }
 }
 
+/// Calls per `(server ID, tool name)` made by `client`, or by any client if
+/// `client` is empty.
+pub fn tool_call_counts(
+    stats: &HashMap<String, ServerStats>,
+    client: &str,
+) -> HashMap<(String, String), u64> {
+    let mut counts = HashMap::new();
+    for (server_id, server_stats) in stats {
+        for (tool_name, tool_stats) in &server_stats.tools {
+            let calls = if client.is_empty() {
+                tool_stats.total_calls
+            } else {
+                tool_stats.clients.get(client).copied().unwrap_or(0)
+            };
+            if calls > 0 {
+                counts.insert((server_id.clone(), tool_name.clone()), calls);
+            }
+        }
+    }
+    counts
+}
+
 pub fn unix_now() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -55,4 +80,11 @@ pub fn unix_now() -> u64 {
         .as_secs()
 }
 
+pub fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("system clock before UNIX epoch")
+        .as_millis() as u64
+}
+
 pub type StatsStore = Arc<RwLock<HashMap<String, ServerStats>>>;
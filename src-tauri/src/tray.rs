@@ -1,6 +1,7 @@
 use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::TrayIconBuilder;
 use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
 use tracing::error;
 
 use crate::state::{ServerStatus, SharedState};
@@ -66,6 +67,27 @@ fn build_tray_menu(
         }
     }
 
+    // Pending device flow sign-ins, so headless users can find the code
+    if !s.device_codes.is_empty() {
+        builder = builder.separator();
+        for (server_id, prompt) in &s.device_codes {
+            let name = s
+                .servers
+                .iter()
+                .find(|srv| &srv.id == server_id)
+                .map(|srv| srv.name.as_str())
+                .unwrap_or(server_id);
+            let label = format!(
+                "Sign in to {name}: enter {} at {}",
+                prompt.user_code, prompt.verification_uri
+            );
+            let item = MenuItemBuilder::new(label)
+                .id(format!("device:{server_id}"))
+                .build(app)?;
+            builder = builder.item(&item);
+        }
+    }
+
     builder = builder.separator();
 
     let show = MenuItemBuilder::new("Show Agent Hub")
@@ -89,6 +111,23 @@ fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
         "show" => {
             focus_main_window(app);
         }
+        _ if id.starts_with("device:") => {
+            let server_id = &id["device:".len()..];
+            let uri = {
+                let state = app.state::<SharedState>();
+                let s = state.lock().unwrap();
+                s.device_codes.get(server_id).map(|p| {
+                    p.verification_uri_complete
+                        .clone()
+                        .unwrap_or_else(|| p.verification_uri.clone())
+                })
+            };
+            if let Some(uri) = uri {
+                if let Err(e) = app.opener().open_url(&uri, None::<&str>) {
+                    error!("Failed to open verification URL: {e}");
+                }
+            }
+        }
         _ if id.starts_with("server:") => {
             let server_id = &id["server:".len()..];
             focus_main_window(app);
@@ -2,7 +2,7 @@ import { onMounted, onUnmounted } from 'vue';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { useRouter } from 'vue-router';
 import { useServersStore } from '@/stores/servers';
-import type { OAuthStatus } from '@/types/oauth';
+import type { DeviceCodePrompt, OAuthStatus } from '@/types/oauth';
 import { useToolsStore } from '@/stores/tools';
 import type { ServerStatus } from '@/types/server';
 import type { McpTool } from '@/types/mcp';
@@ -28,9 +28,15 @@ interface OAuthRequiredPayload {
   serverId: string;
 }
 
+interface OAuthRefreshFailedPayload {
+  serverId: string;
+  error: string;
+}
+
 interface OAuthStatusPayload {
   serverId: string;
   status: OAuthStatus;
+  device?: DeviceCodePrompt;
 }
 
 interface NavigateToServerPayload {
@@ -75,9 +81,16 @@ export function useEvents() {
       })
     );
 
+    unlisteners.push(
+      await listen<OAuthRefreshFailedPayload>('oauth-refresh-failed', (event) => {
+        serversStore.setOAuthStatus(event.payload.serverId, 'idle');
+        serversStore.setError(event.payload.serverId, `Authentication required: ${event.payload.error}`);
+      })
+    );
+
     unlisteners.push(
       await listen<OAuthStatusPayload>('oauth-status-changed', (event) => {
-        serversStore.setOAuthStatus(event.payload.serverId, event.payload.status);
+        serversStore.setOAuthStatus(event.payload.serverId, event.payload.status, event.payload.device);
         // Clear OAuth status when connected successfully
         if (event.payload.status === 'authorized') {
           // Will be cleared once server-status-changed fires with 'connected'
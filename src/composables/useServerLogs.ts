@@ -3,6 +3,12 @@ import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
 import { useLogsStore, type LogEntry } from '@/stores/logs';
 
+interface ContainerPullPayload {
+  serverId: string;
+  image: string;
+  message: string;
+}
+
 interface ServerLogPayload {
   serverId: string;
   level: LogEntry['level'];
@@ -11,6 +17,7 @@ interface ServerLogPayload {
 
 export function useServerLogs() {
   let unlisten: UnlistenFn | null = null;
+  let unlistenPull: UnlistenFn | null = null;
 
   onMounted(async () => {
     const logsStore = useLogsStore();
@@ -24,6 +31,15 @@ export function useServerLogs() {
       });
     });
 
+    unlistenPull = await listen<ContainerPullPayload>('container-pull-progress', (event) => {
+      logsStore.addLog({
+        timestamp: new Date().toISOString(),
+        serverId: event.payload.serverId,
+        level: 'info',
+        message: `[pull] ${event.payload.message}`,
+      });
+    });
+
     // Drain logs that were buffered before we started listening
     // (e.g. HTTP server connections during reconnect_on_startup)
     const buffered = await invoke<ServerLogPayload[]>('drain_log_buffer');
@@ -39,5 +55,6 @@ export function useServerLogs() {
 
   onUnmounted(() => {
     unlisten?.();
+    unlistenPull?.();
   });
 }
@@ -8,9 +8,16 @@ const CLIENT_LABELS: Record<string, string> = {
   'claude-code': 'Claude Code',
   'claude-desktop': 'Claude Desktop',
   windsurf: 'Windsurf',
+  vscode: 'VS Code',
+  'vscode-insiders': 'VS Code Insiders',
+  'gemini-cli': 'Gemini CLI',
+  goose: 'Goose',
+  continue: 'Continue',
 };
 
 export function formatClientName(id: string): string {
+  // One integration per workspace folder, e.g. vscode-workspace-1a2b3c4d
+  if (id.startsWith('vscode-workspace-')) return 'VS Code Workspace';
   return CLIENT_LABELS[id] ?? id;
 }
 
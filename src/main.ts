@@ -83,6 +83,11 @@ const router = createRouter({
       name: 'status',
       component: () => import('./views/SystemStatusView.vue'),
     },
+    {
+      path: '/traffic',
+      name: 'traffic',
+      component: () => import('./views/TrafficView.vue'),
+    },
     {
       path: '/marketplace',
       redirect: '/add',
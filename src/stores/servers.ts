@@ -2,12 +2,13 @@ import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
 import type { ServerConfig, ServerConfigInput } from '@/types/server';
-import type { OAuthStatus } from '@/types/oauth';
+import type { DeviceCodePrompt, OAuthStatus } from '@/types/oauth';
 
 export const useServersStore = defineStore('servers', () => {
   const servers = ref<ServerConfig[]>([]);
   const lastError = ref<Record<string, string>>({});
   const oauthStatus = ref<Record<string, OAuthStatus>>({});
+  const deviceCodes = ref<Record<string, DeviceCodePrompt>>({});
 
   async function loadServers() {
     try {
@@ -41,6 +42,16 @@ export const useServersStore = defineStore('servers', () => {
     }
   }
 
+  async function setDisabledTools(id: string, tools: string[]) {
+    try {
+      const updated = await invoke<ServerConfig>('set_disabled_tools', { id, tools });
+      const idx = servers.value.findIndex(s => s.id === id);
+      if (idx !== -1) servers.value[idx] = updated;
+    } catch (e) {
+      console.error('Failed to update disabled tools:', e);
+    }
+  }
+
   async function removeServer(id: string) {
     try {
       await invoke('remove_server', { id });
@@ -92,8 +103,13 @@ export const useServersStore = defineStore('servers', () => {
     delete lastError.value[serverId];
   }
 
-  function setOAuthStatus(serverId: string, status: OAuthStatus) {
+  function setOAuthStatus(serverId: string, status: OAuthStatus, device?: DeviceCodePrompt) {
     oauthStatus.value[serverId] = status;
+    if (device) {
+      deviceCodes.value[serverId] = device;
+    } else {
+      delete deviceCodes.value[serverId];
+    }
   }
 
   function clearOAuthStatus(serverId: string) {
@@ -107,6 +123,7 @@ export const useServersStore = defineStore('servers', () => {
       await invoke('start_oauth_flow', { id });
     } catch (e) {
       oauthStatus.value[id] = 'error';
+      delete deviceCodes.value[id];
       setError(id, String(e));
     }
   }
@@ -124,10 +141,12 @@ export const useServersStore = defineStore('servers', () => {
     servers,
     lastError,
     oauthStatus,
+    deviceCodes,
     loadServers,
     autoConnectServers,
     addServer,
     updateServer,
+    setDisabledTools,
     removeServer,
     connectServer,
     disconnectServer,
@@ -1,3 +1,20 @@
+export type SearchMode = 'keyword' | 'semantic' | 'hybrid';
+
+export interface PinnedTool {
+  serverId: string;
+  toolName: string;
+}
+
+export interface ToolPinning {
+  /** List each client's most-called tools, up to this many. */
+  topN: number;
+  /** Tools listed for every client. */
+  pinned: PinnedTool[];
+}
+
 export interface DiscoveryStatus {
   enabled: boolean;
+  searchMode: SearchMode;
+  embeddedTools: number;
+  pinning: ToolPinning;
 }
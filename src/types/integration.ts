@@ -10,8 +10,17 @@ export interface AiToolInfo {
   id: string;
   name: string;
   installed: boolean;
+  /** Why the tool was or wasn't detected, e.g. "Found /usr/bin/code". */
+  detection: string;
   enabled: boolean;
   configPath: string;
   configuredPort: number;
   existingServers: ExistingMcpServer[];
+  /** Secrets the servers just imported reference but that aren't set yet. */
+  missingSecrets?: MissingSecret[];
+}
+
+export interface MissingSecret {
+  name: string;
+  description?: string;
 }
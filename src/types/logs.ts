@@ -0,0 +1,18 @@
+export type LogSource = 'stdout' | 'stderr' | 'server' | 'lifecycle';
+
+/** An entry from a server's persisted log files. */
+export interface ServerLogEntry {
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number;
+  level: string;
+  source: LogSource;
+  message: string;
+}
+
+export interface LogQuery {
+  /** Regular expression matched against the message. */
+  pattern?: string;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
@@ -1 +1,23 @@
-export type OAuthStatus = 'idle' | 'discovering' | 'awaiting_browser' | 'exchanging_code' | 'authorized' | 'error';
+import type { AuthMode } from './server';
+
+export type OAuthStatus = 'idle' | 'discovering' | 'awaiting_browser' | 'awaiting_device' | 'exchanging_code' | 'authorized' | 'error';
+
+/** A device flow sign-in waiting for the user to enter the code. */
+export interface DeviceCodePrompt {
+  userCode: string;
+  verificationUri: string;
+  verificationUriComplete?: string;
+}
+
+/** A server's current grant, from `get_token_status`. Timestamps are Unix seconds. */
+export interface TokenStatus {
+  serverId: string;
+  serverName: string;
+  mode: AuthMode;
+  scopes: string[];
+  expiresAt: number | null;
+  obtainedAt: number | null;
+  hasRefreshToken: boolean;
+  lastRefreshedAt: number | null;
+  revocable: boolean;
+}
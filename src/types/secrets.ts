@@ -0,0 +1,5 @@
+export interface SecretList {
+  /** Where secrets are kept, e.g. "Secret Service" or "Encrypted file". */
+  backend: string;
+  names: string[];
+}
@@ -1,4 +1,4 @@
-export type ServerTransport = 'stdio' | 'http';
+export type ServerTransport = 'stdio' | 'http' | 'container' | 'replay';
 export type ServerStatus = 'connected' | 'connecting' | 'disconnected' | 'error';
 
 export interface ServerConfig {
@@ -10,9 +10,26 @@ export interface ServerConfig {
   command?: string;
   args?: string[];
   env?: Record<string, string>;
+  // working directory and dotenv files (relative paths resolve against cwd)
+  cwd?: string;
+  envFile?: string[];
   // http
   url?: string;
   headers?: Record<string, string>;
+  // detected HTTP transport, remembered so reconnects skip the probe
+  httpMode?: HttpMode;
+  // OAuth client, scopes and resource (http)
+  oauth?: OAuthConfig;
+  // container (args and env are passed to the container)
+  container?: ContainerConfig;
+  // replay (answers from a recorded fixture)
+  replay?: ReplayConfig;
+  // tools hidden from AI clients
+  disabledTools?: string[];
+  // when set, the only tools AI clients get (e.g. Gemini CLI's includeTools)
+  enabledTools?: string[];
+  // client settings Agent Hub doesn't model (e.g. Gemini CLI's trust), kept for restores
+  extra?: Record<string, unknown>;
   // metadata
   tags?: string[];
   status?: ServerStatus;
@@ -20,6 +37,79 @@ export interface ServerConfig {
   managed?: boolean;
   managedBy?: string;
   registryName?: string;
+  sandbox?: SandboxConfig;
 }
 
-export type ServerConfigInput = Omit<ServerConfig, 'id' | 'status' | 'lastConnected'>;
+export type HttpMode = 'streamable' | 'legacySse';
+
+export type AuthMode = 'interactive' | 'clientCredentials' | 'staticToken';
+
+export interface OAuthConfig {
+  mode?: AuthMode;
+  clientId?: string;
+  clientSecret?: string;
+  scopes?: string[];
+  extraParams?: Record<string, string>;
+  resource?: string;
+  deviceFlow?: boolean;
+  // Client credentials: token endpoint when the server publishes no metadata
+  tokenEndpoint?: string;
+  // Static token: name of the secret holding the bearer token
+  tokenSecret?: string;
+}
+
+export type ContainerRuntime = 'auto' | 'docker' | 'podman';
+export type ContainerMode = 'stdio' | 'http';
+
+export interface VolumeMount {
+  host: string;
+  container: string;
+  readOnly: boolean;
+}
+
+export interface ContainerConfig {
+  runtime: ContainerRuntime;
+  image: string;
+  tag?: string;
+  volumes?: VolumeMount[];
+  network?: string;
+  mode: ContainerMode;
+  port?: number;
+  path?: string;
+}
+
+export type MatchStrategy = 'exact' | 'canonical' | 'ignoreFields';
+
+export interface ReplayConfig {
+  fixture: string;
+  matching?: MatchStrategy;
+  // dot-separated argument paths skipped under 'ignoreFields'
+  ignoreFields?: string[];
+}
+
+export interface RecordingStatus {
+  serverId: string;
+  path: string;
+  exchanges: number;
+}
+
+export interface SandboxConfig {
+  enabled: boolean;
+  memoryLimitMb?: number;
+  cpuQuotaPercent?: number;
+  cpuTimeSecs?: number;
+  readOnlyRoot: boolean;
+  writablePaths: string[];
+  network: boolean;
+  privateTmp: boolean;
+}
+
+export interface SandboxProfile {
+  enabled: boolean;
+  supported: boolean;
+  rules: string[];
+  problem?: string;
+  commandLine?: string[];
+}
+
+export type ServerConfigInput = Omit<ServerConfig, 'id' | 'status' | 'lastConnected' | 'httpMode'>;
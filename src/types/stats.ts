@@ -2,6 +2,8 @@ export interface ToolStats {
   totalCalls: number;
   errors: number;
   totalDurationMs: number;
+  /** Calls by client ID. */
+  clients: Record<string, number>;
 }
 
 export interface ToolCallEntry {
@@ -0,0 +1,22 @@
+export type TrafficDirection = 'clientToProxy' | 'proxyToClient' | 'proxyToBackend' | 'backendToProxy';
+
+export interface TrafficEntry {
+  id: number;
+  /** Milliseconds since the Unix epoch. */
+  timestamp: number;
+  direction: TrafficDirection;
+  serverId: string;
+  session?: string;
+  /** The method, or for responses the method of the request they answer. */
+  method?: string;
+  /** For responses, the entry ID of the request they answer. */
+  replyTo?: number;
+  durationMs?: number;
+  size: number;
+  message: unknown;
+}
+
+export interface TrafficSnapshot {
+  enabled: boolean;
+  entries: TrafficEntry[];
+}